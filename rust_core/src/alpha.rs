@@ -1,38 +1,64 @@
 use crate::lob::LimitOrderBook;
+use crate::warmup::{WarmupGate, WarmupPolicy, DEFAULT_WARMUP_POLICY};
 use pyo3::prelude::*;
 
+/// Default number of book levels per side fed to the slope regression.
+const DEFAULT_DEPTH_LEVELS: usize = 10;
+
+/// Depth-slope imbalance, EWMA-smoothed over `window_size` updates.
+///
+/// Each side regresses `log(volume + 1)` on the level's position in the book
+/// (1 = best) over the top `depth_levels` levels. Phantom levels with
+/// quantity <= 0 are dropped before indexing, so a side with such levels
+/// gives the same slope as the side without them; fewer than two real
+/// levels gives a slope of 0.
+///
+/// Warm once `window_size` updates have been seen; before that the output
+/// follows `warmup_policy` ("legacy", "zero", "nan" or "hold").
 #[pyclass]
 pub struct AlphaDepthSlope {
     alpha: f64,
     ewma_signal: f64,
     initialized: bool,
+    window_size: usize,
+    depth_levels: usize,
+    raw_signal: f64,
+    n_updates: usize,
+    warmup: WarmupGate,
 }
 
 #[pymethods]
 impl AlphaDepthSlope {
     #[new]
-    pub fn new(window_size: usize) -> Self {
+    #[pyo3(signature = (window_size, warmup_policy=DEFAULT_WARMUP_POLICY, depth_levels=DEFAULT_DEPTH_LEVELS))]
+    pub fn new(window_size: usize, warmup_policy: &str, depth_levels: usize) -> PyResult<Self> {
+        if depth_levels < 2 {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "depth_levels must be >= 2, got {depth_levels}"
+            )));
+        }
         let alpha = 2.0 / (window_size as f64 + 1.0);
-        AlphaDepthSlope {
+        Ok(AlphaDepthSlope {
             alpha,
             ewma_signal: 0.0,
             initialized: false,
-        }
+            window_size,
+            depth_levels,
+            raw_signal: 0.0,
+            n_updates: 0,
+            warmup: WarmupGate::new(WarmupPolicy::parse(warmup_policy)?),
+        })
     }
 
     pub fn calculate(&mut self, lob: &LimitOrderBook) -> f64 {
         // Compute depth slope similar to Python implementation
-        // We need top N levels. Let's use 10 levels for regressions.
-
-        let depth_levels = 10;
-
-        // Helper to compute slope
         // Returns slope of (level_idx vs log(volume))
-        let bid_slope = Self::compute_side_slope(&lob.bids, depth_levels, true);
-        let ask_slope = Self::compute_side_slope(&lob.asks, depth_levels, false);
+        let bid_slope = Self::compute_side_slope(&lob.bids, self.depth_levels, true);
+        let ask_slope = Self::compute_side_slope(&lob.asks, self.depth_levels, false);
 
         // Raw Signal
         let raw_signal = bid_slope - ask_slope;
+        self.raw_signal = raw_signal;
 
         // EWMA Smoothing
         if !self.initialized {
@@ -42,7 +68,34 @@ impl AlphaDepthSlope {
             self.ewma_signal = self.alpha * raw_signal + (1.0 - self.alpha) * self.ewma_signal;
         }
 
-        self.ewma_signal
+        self.n_updates = self.n_updates.saturating_add(1);
+        self.warmup
+            .emit(self.n_updates >= self.window_size, self.ewma_signal)
+    }
+
+    /// Clear the EWMA state (e.g. at session start).
+    /// The last warm signal is kept so "hold" bridges the new warmup.
+    pub fn reset(&mut self) {
+        self.ewma_signal = 0.0;
+        self.initialized = false;
+        self.n_updates = 0;
+        self.raw_signal = 0.0;
+    }
+
+    /// Unsmoothed bid-minus-ask slope from the last `calculate` call.
+    #[getter]
+    pub fn get_raw_signal(&self) -> f64 {
+        self.raw_signal
+    }
+
+    #[getter]
+    pub fn get_depth_levels(&self) -> usize {
+        self.depth_levels
+    }
+
+    #[getter]
+    pub fn get_warmup_policy(&self) -> &'static str {
+        self.warmup.policy().name()
     }
 }
 
@@ -58,19 +111,32 @@ impl AlphaDepthSlope {
         // So for Bids (high prices), we need iter().rev()
         // For Asks (low prices), we need iter()
 
-        let volumes: Vec<f64> = if reverse {
-            book.iter().rev().take(n_levels).map(|(_, v)| *v).collect()
+        // (position among real levels, volume): phantom (<= 0) levels are
+        // filtered before indexing and don't use up the level budget
+        let real = |(_, v): (&u64, &f64)| (*v > 0.0).then_some(*v);
+        let points: Vec<(f64, f64)> = if reverse {
+            book.iter()
+                .rev()
+                .filter_map(real)
+                .take(n_levels)
+                .enumerate()
+                .map(|(i, v)| ((i + 1) as f64, v))
+                .collect()
         } else {
-            book.iter().take(n_levels).map(|(_, v)| *v).collect()
+            book.iter()
+                .filter_map(real)
+                .take(n_levels)
+                .enumerate()
+                .map(|(i, v)| ((i + 1) as f64, v))
+                .collect()
         };
 
-        let n = volumes.len();
+        let n = points.len();
         if n < 2 {
             return 0.0;
         }
 
         // Linear Regression: Level (x) vs Log(Volume) (y)
-        // x = 1, 2, ..., n
         // y = log(v + 1)
 
         let mut sum_x = 0.0;
@@ -78,8 +144,7 @@ impl AlphaDepthSlope {
         let mut sum_xy = 0.0;
         let mut sum_x2 = 0.0;
 
-        for (i, v) in volumes.iter().enumerate() {
-            let x = (i + 1) as f64;
+        for &(x, v) in &points {
             let y = (v + 1.0).ln();
 
             sum_x += x;
@@ -100,3 +165,97 @@ impl AlphaDepthSlope {
         cov_xy / var_x
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(bid_vols: &[f64], ask_vols: &[f64]) -> LimitOrderBook {
+        let mut lob = LimitOrderBook::new("TEST".to_string(), false);
+        for (i, v) in bid_vols.iter().enumerate() {
+            lob.update(true, 100.0 - i as f64, *v, None);
+        }
+        for (i, v) in ask_vols.iter().enumerate() {
+            lob.update(false, 101.0 + i as f64, *v, None);
+        }
+        lob
+    }
+
+    #[test]
+    fn test_shallow_book_uses_only_present_levels() {
+        // 5-level feed: slope must not depend on asking for 10 levels
+        let lob = book(
+            &[10.0, 20.0, 40.0, 80.0, 160.0],
+            &[10.0, 10.0, 10.0, 10.0, 10.0],
+        );
+        let mut a5 = AlphaDepthSlope::new(1, "zero", 5).unwrap();
+        let mut a10 = AlphaDepthSlope::new(1, "zero", 10).unwrap();
+        a5.calculate(&lob);
+        a10.calculate(&lob);
+        assert!(a5.get_raw_signal() > 0.0);
+        assert!((a5.get_raw_signal() - a10.get_raw_signal()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_depth_levels_truncates_regression() {
+        // Volumes grow to level 3 then collapse; 3 levels sees only growth
+        let lob = book(&[10.0, 100.0, 1000.0, 1.0, 1.0], &[]);
+        let mut a3 = AlphaDepthSlope::new(1, "zero", 3).unwrap();
+        let mut a5 = AlphaDepthSlope::new(1, "zero", 5).unwrap();
+        a3.calculate(&lob);
+        a5.calculate(&lob);
+        assert!(a3.get_raw_signal() > a5.get_raw_signal());
+        assert!(AlphaDepthSlope::new(1, "zero", 1).is_err());
+    }
+
+    #[test]
+    fn test_side_slope_skips_phantom_levels() {
+        let mut side = std::collections::BTreeMap::new();
+        side.insert(1, 1.0);
+        side.insert(2, 0.0); // phantom level at position 2
+        side.insert(3, 3.0);
+        // Points are x=1 and x=2 → slope = ln 4 - ln 2
+        let slope = AlphaDepthSlope::compute_side_slope(&side, 10, false);
+        assert!((slope - (4f64.ln() - 2f64.ln())).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_interleaved_zero_levels_match_clean_book() {
+        let vols = [5.0, 9.0, 2.0, 30.0, 7.0];
+        let mut clean = std::collections::BTreeMap::new();
+        let mut dirty = std::collections::BTreeMap::new();
+        for (i, v) in vols.iter().enumerate() {
+            let p = 1000 - 10 * i as u64;
+            clean.insert(p, *v);
+            dirty.insert(p, *v);
+            dirty.insert(p - 5, if i % 2 == 0 { 0.0 } else { -1.0 });
+        }
+        for levels in [2, 3, 5, 10] {
+            for reverse in [true, false] {
+                let a = AlphaDepthSlope::compute_side_slope(&clean, levels, reverse);
+                let b = AlphaDepthSlope::compute_side_slope(&dirty, levels, reverse);
+                assert_eq!(a.to_bits(), b.to_bits());
+            }
+        }
+
+        // Only one real level left: 0, not NaN
+        let mut sparse = std::collections::BTreeMap::new();
+        sparse.insert(1, 0.0);
+        sparse.insert(2, 4.0);
+        sparse.insert(3, 0.0);
+        assert_eq!(AlphaDepthSlope::compute_side_slope(&sparse, 10, true), 0.0);
+    }
+
+    #[test]
+    fn test_default_policy_emits_ewma_while_cold() {
+        let lob = book(&[10.0, 20.0, 40.0], &[10.0, 10.0, 10.0]);
+        let mut legacy =
+            AlphaDepthSlope::new(5, DEFAULT_WARMUP_POLICY, DEFAULT_DEPTH_LEVELS).unwrap();
+        let mut zero = AlphaDepthSlope::new(5, "zero", DEFAULT_DEPTH_LEVELS).unwrap();
+        // First update seeds the EWMA with the raw signal, as before policies
+        let first = legacy.calculate(&lob);
+        assert!(first > 0.0);
+        assert_eq!(first.to_bits(), legacy.get_raw_signal().to_bits());
+        assert_eq!(zero.calculate(&lob), 0.0);
+    }
+}
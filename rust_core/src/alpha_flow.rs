@@ -1,3 +1,4 @@
+use crate::warmup::{WarmupGate, WarmupPolicy, DEFAULT_WARMUP_POLICY};
 use pyo3::prelude::*;
 use std::collections::VecDeque;
 
@@ -5,6 +6,9 @@ use std::collections::VecDeque;
 ///
 /// Normalizes Net Trade Flow by Long-Term Volume (Capacity).
 /// Signal = RollingSum(SignedFlow, fast) / RollingMean(Volume, slow)
+///
+/// Warm once `slow_window` trades have been seen; before that the output
+/// follows `warmup_policy` ("legacy", "zero", "nan" or "hold").
 #[pyclass]
 pub struct MatchedFilterTradeFlow {
     fast_window: usize,
@@ -17,24 +21,31 @@ pub struct MatchedFilterTradeFlow {
     // Running sums for O(1) updates
     sum_signed_flow_fast: f64,
     sum_vol_slow: f64,
+    fills_seen: usize,
+
+    warmup: WarmupGate,
 }
 
 #[pymethods]
 impl MatchedFilterTradeFlow {
     #[new]
-    pub fn new(fast_window: usize, slow_window: usize) -> Self {
-        MatchedFilterTradeFlow {
+    #[pyo3(signature = (fast_window, slow_window, warmup_policy=DEFAULT_WARMUP_POLICY))]
+    pub fn new(fast_window: usize, slow_window: usize, warmup_policy: &str) -> PyResult<Self> {
+        Ok(MatchedFilterTradeFlow {
             fast_window,
             slow_window,
-            trade_vol_history: VecDeque::with_capacity(slow_window),
-            trade_side_history: VecDeque::with_capacity(slow_window),
+            trade_vol_history: VecDeque::with_capacity(fast_window.max(slow_window) + 1),
+            trade_side_history: VecDeque::with_capacity(fast_window.max(slow_window) + 1),
             sum_signed_flow_fast: 0.0,
             sum_vol_slow: 0.0,
-        }
+            fills_seen: 0,
+            warmup: WarmupGate::new(WarmupPolicy::parse(warmup_policy)?),
+        })
     }
 
     pub fn update(&mut self, trade_vol: f64, trade_side: f64) -> f64 {
         let signed_flow = trade_vol * trade_side;
+        self.fills_seen = self.fills_seen.saturating_add(1);
 
         // Add new
         self.trade_vol_history.push_back(trade_vol);
@@ -43,34 +54,205 @@ impl MatchedFilterTradeFlow {
         self.sum_signed_flow_fast += signed_flow;
         self.sum_vol_slow += trade_vol;
 
+        // History is kept for the longer of the two windows; evicted
+        // elements are subtracted by index before anything is popped.
+        let len = self.trade_vol_history.len();
+
         // Remove old (Fast)
-        if self.trade_vol_history.len() > self.fast_window {
-            let old_vol =
-                self.trade_vol_history[self.trade_vol_history.len() - 1 - self.fast_window];
-            let old_side =
-                self.trade_side_history[self.trade_side_history.len() - 1 - self.fast_window];
-            self.sum_signed_flow_fast -= old_vol * old_side;
+        if len > self.fast_window {
+            let i = len - 1 - self.fast_window;
+            self.sum_signed_flow_fast -= self.trade_vol_history[i] * self.trade_side_history[i];
         }
 
         // Remove old (Slow)
-        if self.trade_vol_history.len() > self.slow_window {
-            let old_vol = self.trade_vol_history.pop_front().unwrap_or(0.0);
-            let _ = self.trade_side_history.pop_front().unwrap_or(0.0);
-            self.sum_vol_slow -= old_vol;
+        if len > self.slow_window {
+            self.sum_vol_slow -= self.trade_vol_history[len - 1 - self.slow_window];
+        }
+
+        if len > self.fast_window.max(self.slow_window) {
+            self.trade_vol_history.pop_front();
+            self.trade_side_history.pop_front();
         }
 
         // Compute Signal
         // Capacity = Avg Volume = Sum / N
-        if self.trade_vol_history.len() < self.slow_window {
-            return 0.0; // Warming up
+        if !self.is_warm() {
+            return self.warmup.emit(false, 0.0); // Warming up
         }
 
         let capacity = self.sum_vol_slow / (self.slow_window as f64);
 
-        if capacity > 1e-8 {
+        let signal = if capacity > 1e-8 {
             self.sum_signed_flow_fast / capacity
         } else {
             0.0
+        };
+        self.warmup.emit(true, signal)
+    }
+
+    /// Clear the rolling windows (e.g. at session start).
+    /// The last warm signal is kept so "hold" bridges the new warmup.
+    pub fn reset(&mut self) {
+        self.trade_vol_history.clear();
+        self.trade_side_history.clear();
+        self.sum_signed_flow_fast = 0.0;
+        self.sum_vol_slow = 0.0;
+        self.fills_seen = 0;
+    }
+
+    /// True once `slow_window` trades are in the window (signal is live).
+    pub fn is_warm(&self) -> bool {
+        self.trade_vol_history.len() >= self.slow_window
+    }
+
+    /// Current average trade volume over the slow window (0.0 if empty).
+    pub fn capacity(&self) -> f64 {
+        let n = self.trade_vol_history.len().min(self.slow_window);
+        if n == 0 {
+            0.0
+        } else {
+            self.sum_vol_slow / n as f64
+        }
+    }
+
+    /// Trades seen since construction or the last `reset`.
+    pub fn fills_seen(&self) -> usize {
+        self.fills_seen
+    }
+
+    #[getter]
+    pub fn get_warmup_policy(&self) -> &'static str {
+        self.warmup.policy().name()
+    }
+}
+
+/// Lee-Ready aggressor side of a trade print: +1 buy, -1 sell, 0 unknown.
+///
+/// Quote rule first: above the mid is a buy, below is a sell. At the mid,
+/// or when the quote is one-sided or crossed, fall back to the tick test
+/// against `prev_price` (uptick buy, downtick sell); a zero tick or no
+/// previous price gives 0. The result can be fed as `trade_side` to the
+/// flow factors.
+#[pyfunction]
+#[pyo3(signature = (trade_price, best_bid, best_ask, prev_price=None))]
+pub fn classify_trade(
+    trade_price: f64,
+    best_bid: f64,
+    best_ask: f64,
+    prev_price: Option<f64>,
+) -> i8 {
+    if best_bid > 0.0 && best_ask >= best_bid {
+        let mid = (best_bid + best_ask) * 0.5;
+        if trade_price > mid {
+            return 1;
+        }
+        if trade_price < mid {
+            return -1;
+        }
+    }
+    match prev_price {
+        Some(prev) if trade_price > prev => 1,
+        Some(prev) if trade_price < prev => -1,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warmup_zero_and_default() {
+        // The flow emitted 0.0 while cold before policies existed, so the
+        // default and "zero" agree here.
+        for policy in [DEFAULT_WARMUP_POLICY, "zero"] {
+            let mut f = MatchedFilterTradeFlow::new(2, 3, policy).unwrap();
+            assert_eq!(f.update(10.0, 1.0), 0.0);
+            assert_eq!(f.update(10.0, 1.0), 0.0);
+            assert!(f.update(10.0, 1.0) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_warmup_nan() {
+        let mut f = MatchedFilterTradeFlow::new(2, 3, "nan").unwrap();
+        assert!(f.update(10.0, 1.0).is_nan());
+    }
+
+    #[test]
+    fn test_warmup_hold_across_reset() {
+        let mut f = MatchedFilterTradeFlow::new(2, 2, "hold").unwrap();
+        assert_eq!(f.update(10.0, 1.0), 0.0); // nothing to hold yet
+        let warm = f.update(10.0, 1.0);
+        assert!(warm > 0.0);
+        f.reset();
+        assert_eq!(f.update(5.0, -1.0), warm);
+    }
+
+    #[test]
+    fn test_readiness_accessors() {
+        let mut f = MatchedFilterTradeFlow::new(2, 3, "zero").unwrap();
+        assert!(!f.is_warm());
+        assert_eq!(f.capacity(), 0.0);
+        f.update(10.0, 1.0);
+        f.update(20.0, -1.0);
+        assert!(!f.is_warm());
+        assert_eq!(f.capacity(), 15.0);
+        f.update(30.0, 1.0);
+        f.update(40.0, 1.0);
+        assert!(f.is_warm());
+        assert_eq!(f.capacity(), 30.0); // slow window holds 20, 30, 40
+        assert_eq!(f.fills_seen(), 4);
+        f.reset();
+        assert_eq!(f.fills_seen(), 0);
+        assert!(!f.is_warm());
+    }
+
+    #[test]
+    fn test_incremental_sums_match_brute_force() {
+        // Integer-valued inputs so float sums are exact
+        let mut seed: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        for (fast, slow) in [(5, 50), (49, 50), (50, 50), (51, 50), (80, 20), (1, 1)] {
+            let mut f = MatchedFilterTradeFlow::new(fast, slow, "zero").unwrap();
+            let mut trades: Vec<(f64, f64)> = Vec::new();
+            for _ in 0..3000 {
+                let vol = (next() % 100 + 1) as f64;
+                let side = if next() % 2 == 0 { 1.0 } else { -1.0 };
+                f.update(vol, side);
+                trades.push((vol, side));
+
+                let fast_sum: f64 = trades.iter().rev().take(fast).map(|(v, s)| v * s).sum();
+                let slow_sum: f64 = trades.iter().rev().take(slow).map(|(v, _)| v).sum();
+                assert_eq!(f.sum_signed_flow_fast, fast_sum, "fast={fast} slow={slow}");
+                assert_eq!(f.sum_vol_slow, slow_sum, "fast={fast} slow={slow}");
+            }
         }
     }
+
+    #[test]
+    fn test_unknown_policy_rejected() {
+        assert!(MatchedFilterTradeFlow::new(2, 3, "last").is_err());
+    }
+
+    #[test]
+    fn test_classify_trade_lee_ready() {
+        // Quote rule
+        assert_eq!(classify_trade(101.0, 100.0, 101.0, None), 1);
+        assert_eq!(classify_trade(100.0, 100.0, 101.0, Some(102.0)), -1);
+        assert_eq!(classify_trade(100.8, 100.0, 101.0, Some(101.0)), 1);
+        // At the mid: tick test
+        assert_eq!(classify_trade(100.5, 100.0, 101.0, Some(100.0)), 1);
+        assert_eq!(classify_trade(100.5, 100.0, 101.0, Some(101.0)), -1);
+        assert_eq!(classify_trade(100.5, 100.0, 101.0, Some(100.5)), 0);
+        assert_eq!(classify_trade(100.5, 100.0, 101.0, None), 0);
+        // One-sided or crossed quote: tick test only
+        assert_eq!(classify_trade(105.0, 0.0, 101.0, Some(104.0)), 1);
+        assert_eq!(classify_trade(100.0, 101.0, 100.0, Some(100.5)), -1);
+    }
 }
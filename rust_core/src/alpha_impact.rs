@@ -0,0 +1,151 @@
+use pyo3::prelude::*;
+use std::collections::VecDeque;
+
+/// Kyle's lambda: rolling OLS slope of price change on signed volume.
+///
+/// `update(signed_volume, price_change)` keeps the last `window`
+/// observations in running sums (same regression as
+/// `AlphaDepthSlope::compute_side_slope`), so each update is O(1).
+/// `lambda()` is 0 until `min_obs` observations are in the window, or when
+/// signed volume has no variance.
+#[pyclass]
+pub struct KyleLambda {
+    window: usize,
+    min_obs: usize,
+    history: VecDeque<(f64, f64)>,
+
+    // Running sums for O(1) updates
+    sum_x: f64,
+    sum_y: f64,
+    sum_xy: f64,
+    sum_x2: f64,
+}
+
+#[pymethods]
+impl KyleLambda {
+    #[new]
+    #[pyo3(signature = (window, min_obs=None))]
+    pub fn new(window: usize, min_obs: Option<usize>) -> PyResult<Self> {
+        let min_obs = min_obs.unwrap_or(window);
+        if window < 2 || !(2..=window).contains(&min_obs) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "need 2 <= min_obs <= window, got min_obs={min_obs} window={window}"
+            )));
+        }
+        Ok(KyleLambda {
+            window,
+            min_obs,
+            history: VecDeque::with_capacity(window + 1),
+            sum_x: 0.0,
+            sum_y: 0.0,
+            sum_xy: 0.0,
+            sum_x2: 0.0,
+        })
+    }
+
+    /// Add one (signed_volume, price_change) observation; returns `lambda()`.
+    pub fn update(&mut self, signed_volume: f64, price_change: f64) -> f64 {
+        let (x, y) = (signed_volume, price_change);
+        self.history.push_back((x, y));
+        self.sum_x += x;
+        self.sum_y += y;
+        self.sum_xy += x * y;
+        self.sum_x2 += x * x;
+
+        if self.history.len() > self.window {
+            if let Some((ox, oy)) = self.history.pop_front() {
+                self.sum_x -= ox;
+                self.sum_y -= oy;
+                self.sum_xy -= ox * oy;
+                self.sum_x2 -= ox * ox;
+            }
+        }
+
+        self.lambda()
+    }
+
+    /// Price change per unit of signed volume over the window.
+    pub fn lambda(&self) -> f64 {
+        let n = self.history.len();
+        if n < self.min_obs {
+            return 0.0;
+        }
+
+        let n_f = n as f64;
+        let var_x = self.sum_x2 - (self.sum_x * self.sum_x) / n_f;
+        if var_x.abs() < 1e-9 {
+            return 0.0;
+        }
+        let cov_xy = self.sum_xy - (self.sum_x * self.sum_y) / n_f;
+
+        cov_xy / var_x
+    }
+
+    /// Expected price change for trading `signed_volume` at the current lambda.
+    pub fn expected_impact(&self, signed_volume: f64) -> f64 {
+        self.lambda() * signed_volume
+    }
+
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.sum_x = 0.0;
+        self.sum_y = 0.0;
+        self.sum_xy = 0.0;
+        self.sum_x2 = 0.0;
+    }
+
+    #[getter]
+    pub fn get_window(&self) -> usize {
+        self.window
+    }
+
+    #[getter]
+    pub fn get_n_obs(&self) -> usize {
+        self.history.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovers_linear_impact() {
+        let mut k = KyleLambda::new(10, Some(3)).unwrap();
+        assert_eq!(k.update(5.0, 0.5 * 5.0 + 0.1), 0.0);
+        assert_eq!(k.update(-2.0, 0.5 * -2.0 + 0.1), 0.0);
+        // Third observation: slope of dp = 0.5 * v + 0.1
+        let lam = k.update(8.0, 0.5 * 8.0 + 0.1);
+        assert!((lam - 0.5).abs() < 1e-12);
+        assert!((k.expected_impact(4.0) - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_window_forgets_old_regime() {
+        let mut k = KyleLambda::new(4, None).unwrap();
+        let vols = [1.0, -3.0, 2.0, 5.0];
+        for v in vols {
+            k.update(v, 2.0 * v);
+        }
+        assert!((k.lambda() - 2.0).abs() < 1e-9);
+        // A full window at a new slope replaces the old one
+        for v in vols {
+            k.update(v, -v);
+        }
+        assert!((k.lambda() + 1.0).abs() < 1e-9);
+        assert_eq!(k.get_n_obs(), 4);
+    }
+
+    #[test]
+    fn test_degenerate_inputs() {
+        let mut k = KyleLambda::new(3, None).unwrap();
+        for _ in 0..3 {
+            k.update(1.0, 0.2); // no variance in signed volume
+        }
+        assert_eq!(k.lambda(), 0.0);
+        k.reset();
+        assert_eq!(k.get_n_obs(), 0);
+        assert!(KyleLambda::new(1, None).is_err());
+        assert!(KyleLambda::new(5, Some(6)).is_err());
+    }
+}
@@ -1,25 +1,50 @@
-use numpy::ndarray::Array1;
+use numpy::ndarray::{Array1, ArrayView1};
 use numpy::{IntoPyArray, PyArray1, PyReadonlyArray1};
 use pyo3::prelude::*;
 
+/// Online Markov estimate of the next return, conditioned on the sign of
+/// recent returns.
+///
+/// `order=1` conditions on sign(r[t]) (up/down/flat). `order=2` conditions on
+/// the pair (sign(r[t-1]), sign(r[t])), giving nine states; the first row of
+/// each `compute` call has no defined state and emits 0.
+///
+/// `update` is the tick-by-tick equivalent of `compute`: it learns from the
+/// previous state using the new return, then predicts the next return.
 #[pyclass]
 pub struct AlphaMarkovTransition {
     alpha: f64,
+    order: u8,
     est_up: f64,
     est_dn: f64,
     est_flat: f64,
+    /// Order-2 estimates indexed by `3 * sign_idx(r[t-1]) + sign_idx(r[t])`.
+    est_pair: [f64; 9],
+    /// Last two returns seen by `update` (r[t-1], r[t]).
+    prev_r: Option<f64>,
+    last_r: Option<f64>,
 }
 
 #[pymethods]
 impl AlphaMarkovTransition {
     #[new]
-    pub fn new(alpha: f64) -> Self {
-        AlphaMarkovTransition {
+    #[pyo3(signature = (alpha, order=1))]
+    pub fn new(alpha: f64, order: u8) -> PyResult<Self> {
+        if order != 1 && order != 2 {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "order must be 1 or 2, got {order}"
+            )));
+        }
+        Ok(AlphaMarkovTransition {
             alpha,
+            order,
             est_up: 0.0,
             est_dn: 0.0,
             est_flat: 0.0,
-        }
+            est_pair: [0.0; 9],
+            prev_r: None,
+            last_r: None,
+        })
     }
 
     /// Compute Markov Transition Signal
@@ -30,7 +55,68 @@ impl AlphaMarkovTransition {
         py: Python<'py>,
         returns: PyReadonlyArray1<'py, f64>,
     ) -> PyResult<Py<PyArray1<f64>>> {
-        let returns = returns.as_array();
+        let signal = match self.order {
+            2 => self.compute_order2(returns.as_array()),
+            _ => self.compute_order1(returns.as_array()),
+        };
+
+        // Final Signal Logic from optimization:
+        // "Inverted the MarkovTransition signal by returning +signal instead of -signal"
+        // Wait, the Python code had: return -_compute_markov_numba(returns) INITIALLY
+        // Then I changed it to return +_compute_markov_numba(returns)??
+        // Let's check `factor_registry.py` history or content.
+        // History says: "Inverted the MarkovTransition signal by returning +signal instead of -signal to correct the negative correlation"
+        // So the raw signal from `_compute_markov_numba` was correct, but previously it was being negated.
+        // My Rust code here implements `_compute_markov_numba`.
+        // So I should return `signal` as is.
+
+        Ok(signal.into_pyarray_bound(py).unbind())
+    }
+
+    /// Feed one return; returns the prediction for the next return.
+    ///
+    /// The estimate for the previous call's state is updated with `r` first,
+    /// so signals match `compute` run over the same sequence. With order 2
+    /// the first call emits 0 (state undefined).
+    pub fn update(&mut self, r: f64) -> f64 {
+        let prediction = match self.order {
+            2 => {
+                if let (Some(pp), Some(p)) = (self.prev_r, self.last_r) {
+                    let state = 3 * sign_idx(pp) + sign_idx(p);
+                    self.est_pair[state] =
+                        self.est_pair[state] * (1.0 - self.alpha) + r * self.alpha;
+                }
+                match self.last_r {
+                    Some(p) => self.est_pair[3 * sign_idx(p) + sign_idx(r)],
+                    None => 0.0,
+                }
+            }
+            _ => {
+                if let Some(p) = self.last_r {
+                    self.learn_order1(p, r);
+                }
+                self.predict_order1(r)
+            }
+        };
+        self.prev_r = self.last_r;
+        self.last_r = Some(r);
+        prediction
+    }
+
+    /// Forget the streaming return history (estimates are kept).
+    pub fn reset(&mut self) {
+        self.prev_r = None;
+        self.last_r = None;
+    }
+
+    #[getter]
+    pub fn get_order(&self) -> u8 {
+        self.order
+    }
+}
+
+impl AlphaMarkovTransition {
+    fn compute_order1(&mut self, returns: ArrayView1<f64>) -> Array1<f64> {
         let n = returns.len();
 
         // Output array
@@ -40,42 +126,136 @@ impl AlphaMarkovTransition {
         // note: signal[i] is prediction for returns[i+1] based on state at i
         // state[i] is based on returns[i]
 
-        for i in 0..(n - 1) {
+        for i in 0..n.saturating_sub(1) {
             let r = returns[i];
             let target = returns[i + 1];
 
-            // Determine state
-            // 1: Up, -1: Down, 0: Flat
-            let prediction = if r > 0.0 {
-                self.est_up
-            } else if r < 0.0 {
-                self.est_dn
-            } else {
-                self.est_flat
-            };
-
-            signal[i] = prediction;
+            signal[i] = self.predict_order1(r);
 
             // Update expectation for the *current* state using the *target* (next return)
-            if r > 0.0 {
-                self.est_up = self.est_up * (1.0 - self.alpha) + target * self.alpha;
-            } else if r < 0.0 {
-                self.est_dn = self.est_dn * (1.0 - self.alpha) + target * self.alpha;
-            } else {
-                self.est_flat = self.est_flat * (1.0 - self.alpha) + target * self.alpha;
+            self.learn_order1(r, target);
+        }
+
+        signal
+    }
+
+    /// Determine state from sign(r) — 1: Up, -1: Down, 0: Flat
+    #[inline(always)]
+    fn predict_order1(&self, r: f64) -> f64 {
+        if r > 0.0 {
+            self.est_up
+        } else if r < 0.0 {
+            self.est_dn
+        } else {
+            self.est_flat
+        }
+    }
+
+    #[inline(always)]
+    fn learn_order1(&mut self, r: f64, target: f64) {
+        if r > 0.0 {
+            self.est_up = self.est_up * (1.0 - self.alpha) + target * self.alpha;
+        } else if r < 0.0 {
+            self.est_dn = self.est_dn * (1.0 - self.alpha) + target * self.alpha;
+        } else {
+            self.est_flat = self.est_flat * (1.0 - self.alpha) + target * self.alpha;
+        }
+    }
+
+    fn compute_order2(&mut self, returns: ArrayView1<f64>) -> Array1<f64> {
+        let n = returns.len();
+        let mut signal = Array1::<f64>::zeros(n);
+
+        // signal[0] stays 0: state (sign[-1], sign[0]) is undefined
+        for i in 1..n.saturating_sub(1) {
+            let state = 3 * sign_idx(returns[i - 1]) + sign_idx(returns[i]);
+            let target = returns[i + 1];
+
+            signal[i] = self.est_pair[state];
+            self.est_pair[state] = self.est_pair[state] * (1.0 - self.alpha) + target * self.alpha;
+        }
+
+        signal
+    }
+}
+
+/// 0: down, 1: flat, 2: up
+#[inline(always)]
+fn sign_idx(r: f64) -> usize {
+    if r > 0.0 {
+        2
+    } else if r < 0.0 {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use numpy::ndarray::array;
+
+    #[test]
+    fn test_order1_learns_momentum() {
+        let mut m = AlphaMarkovTransition::new(0.5, 1).unwrap();
+        let r = array![1.0, 1.0, 1.0, 1.0];
+        let s = m.compute_order1(r.view());
+        assert_eq!(s.to_vec(), vec![0.0, 0.5, 0.75, 0.0]);
+        assert!(m.compute_order1(array![].view()).is_empty());
+    }
+
+    #[test]
+    fn test_order2_distinguishes_pairs() {
+        let mut m = AlphaMarkovTransition::new(1.0, 2).unwrap();
+        // After (up, up) comes -1; after (up, down) comes +2
+        let r = array![1.0, 1.0, -1.0, 2.0, 1.0, 1.0, -3.0];
+        let s = m.compute_order2(r.view());
+        // Warm-up row
+        assert_eq!(s[0], 0.0);
+        // i=4: state (up, up) last saw target -1 (at i=1)
+        assert_eq!(s[4], -1.0);
+        // i=5: state (up, up) just learned target 1 at i=4
+        assert_eq!(s[5], 1.0);
+        // (up, down) → +2
+        assert_eq!(m.est_pair[3 * sign_idx(1.0) + sign_idx(-1.0)], 2.0);
+        assert_eq!(s[6], 0.0); // last row has no target
+    }
+
+    #[test]
+    fn test_update_matches_compute() {
+        let r = array![0.5, -0.2, 0.0, 0.3, 0.3, -0.1, 0.4, 0.0, -0.6, 0.2];
+        for order in [1, 2] {
+            let mut batch = AlphaMarkovTransition::new(0.3, order).unwrap();
+            let expected = match order {
+                2 => batch.compute_order2(r.view()),
+                _ => batch.compute_order1(r.view()),
+            };
+            let mut live = AlphaMarkovTransition::new(0.3, order).unwrap();
+            let got: Vec<f64> = r.iter().map(|x| live.update(*x)).collect();
+            // The batch's last row has no target and is left at 0
+            for i in 0..r.len() - 1 {
+                assert!(
+                    (got[i] - expected[i]).abs() < 1e-15,
+                    "order {order} row {i}"
+                );
             }
         }
+    }
 
-        // Final Signal Logic from optimization:
-        // "Inverted the MarkovTransition signal by returning +signal instead of -signal"
-        // Wait, the Python code had: return -_compute_markov_numba(returns) INITIALLY
-        // Then I changed it to return +_compute_markov_numba(returns)??
-        // Let's check `factor_registry.py` history or content.
-        // History says: "Inverted the MarkovTransition signal by returning +signal instead of -signal to correct the negative correlation"
-        // So the raw signal from `_compute_markov_numba` was correct, but previously it was being negated.
-        // My Rust code here implements `_compute_markov_numba`.
-        // So I should return `signal` as is.
+    #[test]
+    fn test_update_reset_clears_history_only() {
+        let mut m = AlphaMarkovTransition::new(1.0, 1).unwrap();
+        m.update(1.0);
+        assert_eq!(m.update(2.0), 2.0); // learned up → 2
+        m.reset();
+        // No previous return: nothing learned, prediction from kept estimate
+        assert_eq!(m.update(1.0), 2.0);
+    }
 
-        Ok(signal.into_pyarray_bound(py).unbind())
+    #[test]
+    fn test_order_validation() {
+        assert!(AlphaMarkovTransition::new(0.1, 0).is_err());
+        assert!(AlphaMarkovTransition::new(0.1, 3).is_err());
     }
 }
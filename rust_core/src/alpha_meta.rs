@@ -1,56 +1,175 @@
 //! Meta Alpha Factor - Rust implementation for production latency
 //!
 //! Combines DynamicEnsembleAlpha + InteractionAlpha components:
-//! - Rolling IC-based weighting (currently simplified)
+//! - Rolling IC-based weighting (opt-in via `ic_window`)
 //! - Volatility regime detection
 //! - Factor interaction (Hawkes x OFI)
 //! - Confirmation boost when signals agree
 
+use crate::vol::{compute_vol_regime, DEFAULT_REGIME_HIGH, DEFAULT_REGIME_LOW};
 use pyo3::prelude::*;
 use std::collections::VecDeque;
 
-/// Volatility Regime Detector
-/// Returns: 1.0 = high vol, 0.0 = normal, -1.0 = low vol
-fn compute_vol_regime(returns: &VecDeque<f64>, short_window: usize, long_window: usize) -> f64 {
-    if returns.len() < long_window {
-        return 0.0;
-    }
-
-    let n = returns.len();
-
-    // Short vol
-    let short_start = n.saturating_sub(short_window);
-    let short_slice: Vec<f64> = returns.iter().skip(short_start).copied().collect();
-    let short_mean: f64 = short_slice.iter().sum::<f64>() / short_slice.len() as f64;
-    let short_var: f64 = short_slice
-        .iter()
-        .map(|x| (x - short_mean).powi(2))
-        .sum::<f64>()
-        / short_slice.len() as f64;
-    let short_vol = short_var.sqrt();
-
-    // Long vol
-    let long_start = n.saturating_sub(long_window);
-    let long_slice: Vec<f64> = returns.iter().skip(long_start).copied().collect();
-    let long_mean: f64 = long_slice.iter().sum::<f64>() / long_slice.len() as f64;
-    let long_var: f64 = long_slice
-        .iter()
-        .map(|x| (x - long_mean).powi(2))
-        .sum::<f64>()
-        / long_slice.len() as f64;
-    let long_vol = long_var.sqrt();
-
-    if long_vol > 1e-10 {
-        let vol_ratio = short_vol / long_vol;
-        if vol_ratio > 1.5 {
-            1.0 // High vol
-        } else if vol_ratio < 0.7 {
-            -1.0 // Low vol
+/// Default blend weights and agreement multiplier.
+const DEFAULT_DYNAMIC_WEIGHT: f64 = 0.57;
+const DEFAULT_INTERACTION_WEIGHT: f64 = 0.43;
+const DEFAULT_CONFIRMATION_BOOST: f64 = 1.5;
+
+/// Default Hawkes baseline, jump size and decay rate (per tick, or per
+/// second when `update` is given timestamps).
+const DEFAULT_HAWKES_MU: f64 = 0.02;
+const DEFAULT_HAWKES_ALPHA: f64 = 0.2;
+const DEFAULT_HAWKES_BETA: f64 = 0.1;
+/// Default intensity mapped to a Hawkes signal of 1.
+const DEFAULT_HAWKES_CEILING: f64 = 2.0;
+
+/// Default number of ticks kept for `history`.
+const DEFAULT_MAX_HISTORY: usize = 1000;
+
+fn check_hawkes(mu: f64, alpha: f64, beta: f64, ceiling: f64) -> PyResult<()> {
+    let ok = [mu, alpha, beta, ceiling].iter().all(|x| x.is_finite())
+        && mu >= 0.0
+        && alpha >= 0.0
+        && beta > 0.0
+        && ceiling > 0.0;
+    if ok {
+        Ok(())
+    } else {
+        Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "need mu >= 0, alpha >= 0, beta > 0, ceiling > 0, got \
+             mu={mu} alpha={alpha} beta={beta} ceiling={ceiling}"
+        )))
+    }
+}
+
+fn check_finite(name: &str, x: f64) -> PyResult<()> {
+    if x.is_finite() {
+        Ok(())
+    } else {
+        Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{name} must be finite, got {x}"
+        )))
+    }
+}
+
+/// Running sums for a windowed Pearson correlation; the caller owns the
+/// window and removes evicted pairs.
+#[derive(Default)]
+struct RollingCorr {
+    sx: f64,
+    sy: f64,
+    sxx: f64,
+    syy: f64,
+    sxy: f64,
+}
+
+impl RollingCorr {
+    #[inline(always)]
+    fn add(&mut self, x: f64, y: f64) {
+        self.sx += x;
+        self.sy += y;
+        self.sxx += x * x;
+        self.syy += y * y;
+        self.sxy += x * y;
+    }
+
+    #[inline(always)]
+    fn remove(&mut self, x: f64, y: f64) {
+        self.sx -= x;
+        self.sy -= y;
+        self.sxx -= x * x;
+        self.syy -= y * y;
+        self.sxy -= x * y;
+    }
+
+    /// Correlation over `n` pairs; 0.0 if either side has no variance.
+    #[inline(always)]
+    fn corr(&self, n: usize) -> f64 {
+        let n = n as f64;
+        let var_x = n * self.sxx - self.sx * self.sx;
+        let var_y = n * self.syy - self.sy * self.sy;
+        if var_x <= 1e-12 || var_y <= 1e-12 {
+            return 0.0;
+        }
+        ((n * self.sxy - self.sx * self.sy) / (var_x * var_y).sqrt()).clamp(-1.0, 1.0)
+    }
+
+    /// OLS slope of y on x over `n` pairs; 0.0 if x has no variance.
+    #[inline(always)]
+    fn beta(&self, n: usize) -> f64 {
+        let n = n as f64;
+        let var_x = n * self.sxx - self.sx * self.sx;
+        if var_x <= 1e-12 {
+            return 0.0;
+        }
+        (n * self.sxy - self.sx * self.sy) / var_x
+    }
+}
+
+/// Pearson correlation and beta (slope of y on x) over the last `window`
+/// pairs, O(1) per update. Both read 0.0 until the window is full or when
+/// a side has no variance.
+#[pyclass]
+pub struct RollingCorrelation {
+    window: usize,
+    pairs: VecDeque<(f64, f64)>,
+    sums: RollingCorr,
+}
+
+#[pymethods]
+impl RollingCorrelation {
+    #[new]
+    pub fn new(window: usize) -> PyResult<Self> {
+        if window < 2 {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "window must be >= 2, got {window}"
+            )));
+        }
+        Ok(Self {
+            window,
+            pairs: VecDeque::with_capacity(window + 1),
+            sums: RollingCorr::default(),
+        })
+    }
+
+    /// Add a pair and return the current correlation.
+    pub fn update(&mut self, x: f64, y: f64) -> PyResult<f64> {
+        check_finite("x", x)?;
+        check_finite("y", y)?;
+        self.pairs.push_back((x, y));
+        self.sums.add(x, y);
+        if self.pairs.len() > self.window {
+            if let Some((ox, oy)) = self.pairs.pop_front() {
+                self.sums.remove(ox, oy);
+            }
+        }
+        Ok(self.corr())
+    }
+
+    pub fn corr(&self) -> f64 {
+        if self.is_warm() {
+            self.sums.corr(self.window)
+        } else {
+            0.0
+        }
+    }
+
+    /// Slope of y on x, e.g. a hedge ratio.
+    pub fn beta(&self) -> f64 {
+        if self.is_warm() {
+            self.sums.beta(self.window)
         } else {
-            0.0 // Normal
+            0.0
         }
-    } else {
-        0.0
+    }
+
+    pub fn is_warm(&self) -> bool {
+        self.pairs.len() == self.window
+    }
+
+    pub fn reset(&mut self) {
+        self.pairs.clear();
+        self.sums = RollingCorr::default();
     }
 }
 
@@ -61,6 +180,17 @@ fn compute_vol_regime(returns: &VecDeque<f64>, short_window: usize, long_window:
 /// - Rolling signal combination
 /// - Volatility regime detection
 /// - Confirmation boost
+///
+/// Weights and the confirmation boost can be changed between runs with
+/// `set_weights` / `set_confirmation_boost`; `reset` leaves them alone.
+///
+/// With `ic_window > 0` each component's signal is paired with the next
+/// tick's mid return, and once `ic_window` pairs are collected the blend
+/// weights follow the components' positive rolling ICs (renormalized to sum
+/// to 1). If neither IC is positive the current weights are kept. The
+/// IC-derived weights are held apart from the configured ones, which apply
+/// until the window fills and again after `reset`; `effective_weights`
+/// reports the pair in use.
 #[pyclass]
 pub struct MetaAlpha {
     // Windows
@@ -80,9 +210,10 @@ pub struct MetaAlpha {
     sum_signed_flow_fast: f64,
     sum_vol_slow: f64,
 
-    // State - OFI (simplified)
-    bid_qty_history: VecDeque<f64>,
-    ask_qty_history: VecDeque<f64>,
+    // State - OFI (exact rolling sum of per-tick contributions over fast_window)
+    last_bid_qty: Option<f64>,
+    last_ask_qty: Option<f64>,
+    ofi_history: VecDeque<f64>,
     ofi_sum: f64,
 
     // State - Hawkes-like intensity tracker
@@ -90,14 +221,32 @@ pub struct MetaAlpha {
     hawkes_mu: f64,
     hawkes_alpha: f64,
     hawkes_beta: f64,
+    /// Intensity at which the normalized Hawkes signal saturates at 1.
+    hawkes_ceiling: f64,
+    /// Timestamp (ns) of the last timestamped update.
+    hawkes_last_ts: Option<i64>,
 
     // State - Returns for vol regime
     returns_history: VecDeque<f64>,
     last_price: f64,
 
+    // State - Rolling IC: (dynamic, interaction, next return) per tick
+    ic_window: usize,
+    ic_pairs: VecDeque<(f64, f64, f64)>,
+    ic_dynamic: RollingCorr,
+    ic_interaction: RollingCorr,
+    /// (dynamic, interaction) weights from the rolling ICs, once adapted.
+    ic_weights: Option<(f64, f64)>,
+
     // Output signals
     signal_dynamic: f64,
     signal_interaction: f64,
+
+    // Diagnostics - last `max_history` (dynamic, interaction, combined)
+    max_history: usize,
+    history_dynamic: VecDeque<f64>,
+    history_interaction: VecDeque<f64>,
+    history_combined: VecDeque<f64>,
 }
 
 #[pymethods]
@@ -107,48 +256,90 @@ impl MetaAlpha {
         fast_window = 20,
         slow_window = 300,
         vol_short_window = 100,
-        vol_long_window = 500
+        vol_long_window = 500,
+        dynamic_weight = DEFAULT_DYNAMIC_WEIGHT,
+        interaction_weight = DEFAULT_INTERACTION_WEIGHT,
+        confirmation_boost = DEFAULT_CONFIRMATION_BOOST,
+        ic_window = 0,
+        max_history = DEFAULT_MAX_HISTORY,
+        hawkes_mu = DEFAULT_HAWKES_MU,
+        hawkes_alpha = DEFAULT_HAWKES_ALPHA,
+        hawkes_beta = DEFAULT_HAWKES_BETA,
+        hawkes_ceiling = DEFAULT_HAWKES_CEILING
     ))]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         fast_window: usize,
         slow_window: usize,
         vol_short_window: usize,
         vol_long_window: usize,
-    ) -> Self {
-        MetaAlpha {
+        dynamic_weight: f64,
+        interaction_weight: f64,
+        confirmation_boost: f64,
+        ic_window: usize,
+        max_history: usize,
+        hawkes_mu: f64,
+        hawkes_alpha: f64,
+        hawkes_beta: f64,
+        hawkes_ceiling: f64,
+    ) -> PyResult<Self> {
+        check_hawkes(hawkes_mu, hawkes_alpha, hawkes_beta, hawkes_ceiling)?;
+        check_finite("dynamic_weight", dynamic_weight)?;
+        check_finite("interaction_weight", interaction_weight)?;
+        check_finite("confirmation_boost", confirmation_boost)?;
+        Ok(MetaAlpha {
             fast_window,
             slow_window,
             vol_short_window,
             vol_long_window,
 
-            dynamic_weight: 0.57,
-            interaction_weight: 0.43,
-            confirmation_boost: 1.5,
+            dynamic_weight,
+            interaction_weight,
+            confirmation_boost,
 
             trade_vol_history: VecDeque::with_capacity(slow_window),
             trade_side_history: VecDeque::with_capacity(slow_window),
             sum_signed_flow_fast: 0.0,
             sum_vol_slow: 0.0,
 
-            bid_qty_history: VecDeque::with_capacity(fast_window),
-            ask_qty_history: VecDeque::with_capacity(fast_window),
+            last_bid_qty: None,
+            last_ask_qty: None,
+            ofi_history: VecDeque::with_capacity(fast_window + 1),
             ofi_sum: 0.0,
 
             hawkes_intensity: 0.0,
-            hawkes_mu: 0.02,
-            hawkes_alpha: 0.2,
-            hawkes_beta: 0.1,
+            hawkes_mu,
+            hawkes_alpha,
+            hawkes_beta,
+            hawkes_ceiling,
+            hawkes_last_ts: None,
 
             returns_history: VecDeque::with_capacity(vol_long_window),
             last_price: 0.0,
 
+            ic_window,
+            ic_pairs: VecDeque::with_capacity(ic_window + 1),
+            ic_dynamic: RollingCorr::default(),
+            ic_interaction: RollingCorr::default(),
+            ic_weights: None,
+
             signal_dynamic: 0.0,
             signal_interaction: 0.0,
-        }
+
+            max_history,
+            history_dynamic: VecDeque::with_capacity(max_history),
+            history_interaction: VecDeque::with_capacity(max_history),
+            history_combined: VecDeque::with_capacity(max_history),
+        })
     }
 
     /// Update with new tick data
     /// Returns the combined MetaAlpha signal
+    ///
+    /// With `ts` (ns) the Hawkes intensity decays over the real elapsed time
+    /// since the previous timestamped tick; without it each call counts as
+    /// dt = 1.
+    #[pyo3(signature = (trade_vol, trade_side, bid_qty, ask_qty, mid_price, ts=None))]
     pub fn update(
         &mut self,
         trade_vol: f64,
@@ -156,6 +347,7 @@ impl MetaAlpha {
         bid_qty: f64,
         ask_qty: f64,
         mid_price: f64,
+        ts: Option<i64>,
     ) -> f64 {
         // --- Update returns history ---
         if self.last_price > 0.0 {
@@ -164,19 +356,37 @@ impl MetaAlpha {
             if self.returns_history.len() > self.vol_long_window {
                 self.returns_history.pop_front();
             }
+            if self.ic_window > 0 {
+                self.update_ic_weights(ret);
+            }
         }
         self.last_price = mid_price;
 
         // --- Hawkes intensity update (O(1)) ---
         // λ(t+dt) = μ + (λ(t) - μ) * e^(-β*dt) + α * (event)
-        let decay = (-self.hawkes_beta).exp();
-        let event_indicator = if trade_vol > 0.0 { 1.0 } else { 0.0 };
-        self.hawkes_intensity = self.hawkes_mu
-            + (self.hawkes_intensity - self.hawkes_mu) * decay
-            + self.hawkes_alpha * event_indicator;
+        let dt = match ts {
+            Some(ts) => {
+                // First timestamped tick has nothing to decay from
+                let dt = self
+                    .hawkes_last_ts
+                    .map_or(0.0, |last| (ts - last) as f64 / 1e9); // ns to seconds
+                self.hawkes_last_ts = Some(ts);
+                dt
+            }
+            None => 1.0,
+        };
+        if dt > 0.0 {
+            let decay = (-self.hawkes_beta * dt).exp();
+            self.hawkes_intensity =
+                self.hawkes_mu + (self.hawkes_intensity - self.hawkes_mu) * decay;
+        }
+        if trade_vol > 0.0 {
+            self.hawkes_intensity += self.hawkes_alpha;
+        }
 
         // Normalize Hawkes to [0, 1] range
-        let hawkes_signal = (self.hawkes_intensity.min(2.0) / 2.0).max(0.0);
+        let hawkes_signal =
+            (self.hawkes_intensity.min(self.hawkes_ceiling) / self.hawkes_ceiling).max(0.0);
 
         // --- Trade Flow (MatchedFilter style) ---
         let signed_flow = trade_vol * trade_side;
@@ -212,23 +422,21 @@ impl MetaAlpha {
         };
 
         // --- OFI update ---
-        let prev_bid = self.bid_qty_history.back().copied().unwrap_or(bid_qty);
-        let prev_ask = self.ask_qty_history.back().copied().unwrap_or(ask_qty);
+        let prev_bid = self.last_bid_qty.unwrap_or(bid_qty);
+        let prev_ask = self.last_ask_qty.unwrap_or(ask_qty);
+        self.last_bid_qty = Some(bid_qty);
+        self.last_ask_qty = Some(ask_qty);
 
         let delta_bid = bid_qty - prev_bid;
         let delta_ask = ask_qty - prev_ask;
         let ofi_tick = delta_bid - delta_ask;
 
-        self.bid_qty_history.push_back(bid_qty);
-        self.ask_qty_history.push_back(ask_qty);
+        self.ofi_history.push_back(ofi_tick);
         self.ofi_sum += ofi_tick;
 
-        if self.bid_qty_history.len() > self.fast_window {
-            // Remove oldest contribution (approximate)
-            self.bid_qty_history.pop_front();
-            self.ask_qty_history.pop_front();
-            // Recalculate OFI sum would be O(n), so we approximate
-            self.ofi_sum *= 0.95; // Decay factor for approximation
+        // Remove old (fast): subtract the evicted tick's contribution
+        if self.ofi_history.len() > self.fast_window {
+            self.ofi_sum -= self.ofi_history.pop_front().unwrap_or(0.0);
         }
 
         let ofi_signal = self.ofi_sum / (self.fast_window as f64);
@@ -238,6 +446,8 @@ impl MetaAlpha {
             &self.returns_history,
             self.vol_short_window,
             self.vol_long_window,
+            DEFAULT_REGIME_HIGH,
+            DEFAULT_REGIME_LOW,
         );
 
         // --- Dynamic Ensemble Signal ---
@@ -260,8 +470,9 @@ impl MetaAlpha {
         self.signal_interaction = 0.5 * hawkes_interaction + 0.5 * dampened_flow;
 
         // --- Meta combination ---
-        let mut combined = self.dynamic_weight * self.signal_dynamic
-            + self.interaction_weight * self.signal_interaction;
+        let (w_dynamic, w_interaction) = self.effective_weights();
+        let mut combined =
+            w_dynamic * self.signal_dynamic + w_interaction * self.signal_interaction;
 
         // Confirmation boost
         if self.signal_dynamic.signum() == self.signal_interaction.signum()
@@ -271,6 +482,7 @@ impl MetaAlpha {
             combined *= self.confirmation_boost;
         }
 
+        self.record_history(combined);
         combined
     }
 
@@ -279,19 +491,462 @@ impl MetaAlpha {
         (self.signal_dynamic, self.signal_interaction)
     }
 
-    /// Reset state
+    /// Set the configured dynamic / interaction blend weights.
+    pub fn set_weights(&mut self, dynamic: f64, interaction: f64) -> PyResult<()> {
+        check_finite("dynamic_weight", dynamic)?;
+        check_finite("interaction_weight", interaction)?;
+        self.dynamic_weight = dynamic;
+        self.interaction_weight = interaction;
+        Ok(())
+    }
+
+    /// Set the multiplier applied when both components agree strongly.
+    pub fn set_confirmation_boost(&mut self, boost: f64) -> PyResult<()> {
+        check_finite("confirmation_boost", boost)?;
+        self.confirmation_boost = boost;
+        Ok(())
+    }
+
+    /// Replace the Hawkes baseline `mu`, jump `alpha` and decay `beta`, and
+    /// optionally the normalization `ceiling`. The current intensity is kept.
+    #[pyo3(signature = (mu, alpha, beta, ceiling=None))]
+    pub fn set_hawkes_params(
+        &mut self,
+        mu: f64,
+        alpha: f64,
+        beta: f64,
+        ceiling: Option<f64>,
+    ) -> PyResult<()> {
+        let ceiling = ceiling.unwrap_or(self.hawkes_ceiling);
+        check_hawkes(mu, alpha, beta, ceiling)?;
+        self.hawkes_mu = mu;
+        self.hawkes_alpha = alpha;
+        self.hawkes_beta = beta;
+        self.hawkes_ceiling = ceiling;
+        Ok(())
+    }
+
+    /// Current (un-normalized) Hawkes intensity.
+    pub fn hawkes_intensity(&self) -> f64 {
+        self.hawkes_intensity
+    }
+
+    /// (mu, alpha, beta, ceiling).
+    #[getter]
+    pub fn get_hawkes_params(&self) -> (f64, f64, f64, f64) {
+        (
+            self.hawkes_mu,
+            self.hawkes_alpha,
+            self.hawkes_beta,
+            self.hawkes_ceiling,
+        )
+    }
+
+    /// Rolling (dynamic, interaction) information coefficients.
+    pub fn current_ics(&self) -> (f64, f64) {
+        let n = self.ic_pairs.len();
+        (self.ic_dynamic.corr(n), self.ic_interaction.corr(n))
+    }
+
+    #[getter]
+    pub fn get_ic_window(&self) -> usize {
+        self.ic_window
+    }
+
+    /// (dynamic, interaction) weights applied to the blend: the IC-derived
+    /// pair once adapted, otherwise the configured weights.
+    pub fn effective_weights(&self) -> (f64, f64) {
+        self.ic_weights
+            .unwrap_or((self.dynamic_weight, self.interaction_weight))
+    }
+
+    #[getter]
+    pub fn get_dynamic_weight(&self) -> f64 {
+        self.dynamic_weight
+    }
+
+    #[getter]
+    pub fn get_interaction_weight(&self) -> f64 {
+        self.interaction_weight
+    }
+
+    #[getter]
+    pub fn get_confirmation_boost(&self) -> f64 {
+        self.confirmation_boost
+    }
+
+    /// Last `n` (dynamic, interaction, combined) signals, oldest first.
+    /// At most `max_history` ticks are retained.
+    pub fn history(&self, n: usize) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        let skip = self.history_combined.len().saturating_sub(n);
+        (
+            self.history_dynamic.iter().skip(skip).copied().collect(),
+            self.history_interaction
+                .iter()
+                .skip(skip)
+                .copied()
+                .collect(),
+            self.history_combined.iter().skip(skip).copied().collect(),
+        )
+    }
+
+    #[getter]
+    pub fn get_max_history(&self) -> usize {
+        self.max_history
+    }
+
+    /// Reset state (configured weights and boost are kept; IC-derived
+    /// weights are dropped)
     pub fn reset(&mut self) {
         self.trade_vol_history.clear();
         self.trade_side_history.clear();
         self.sum_signed_flow_fast = 0.0;
         self.sum_vol_slow = 0.0;
-        self.bid_qty_history.clear();
-        self.ask_qty_history.clear();
+        self.last_bid_qty = None;
+        self.last_ask_qty = None;
+        self.ofi_history.clear();
         self.ofi_sum = 0.0;
         self.hawkes_intensity = 0.0;
+        self.hawkes_last_ts = None;
         self.returns_history.clear();
         self.last_price = 0.0;
+        self.ic_pairs.clear();
+        self.ic_dynamic = RollingCorr::default();
+        self.ic_interaction = RollingCorr::default();
+        self.ic_weights = None;
         self.signal_dynamic = 0.0;
         self.signal_interaction = 0.0;
+        self.history_dynamic.clear();
+        self.history_interaction.clear();
+        self.history_combined.clear();
+    }
+}
+
+impl MetaAlpha {
+    fn record_history(&mut self, combined: f64) {
+        if self.max_history == 0 {
+            return;
+        }
+        if self.history_combined.len() == self.max_history {
+            self.history_dynamic.pop_front();
+            self.history_interaction.pop_front();
+            self.history_combined.pop_front();
+        }
+        self.history_dynamic.push_back(self.signal_dynamic);
+        self.history_interaction.push_back(self.signal_interaction);
+        self.history_combined.push_back(combined);
+    }
+
+    /// Pair the previous tick's component signals with the return realized
+    /// since, then re-weight once the IC window is full.
+    fn update_ic_weights(&mut self, ret: f64) {
+        let (d, i) = (self.signal_dynamic, self.signal_interaction);
+        self.ic_pairs.push_back((d, i, ret));
+        self.ic_dynamic.add(d, ret);
+        self.ic_interaction.add(i, ret);
+        if self.ic_pairs.len() > self.ic_window {
+            if let Some((od, oi, oy)) = self.ic_pairs.pop_front() {
+                self.ic_dynamic.remove(od, oy);
+                self.ic_interaction.remove(oi, oy);
+            }
+        }
+        if self.ic_pairs.len() < self.ic_window {
+            return;
+        }
+
+        let (ic_d, ic_i) = self.current_ics();
+        let (pos_d, pos_i) = (ic_d.max(0.0), ic_i.max(0.0));
+        let total = pos_d + pos_i;
+        if total > 0.0 {
+            self.ic_weights = Some((
+                (pos_d / total).clamp(0.0, 1.0),
+                (pos_i / total).clamp(0.0, 1.0),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(fast: usize, slow: usize, vol_short: usize, vol_long: usize) -> MetaAlpha {
+        MetaAlpha::new(
+            fast,
+            slow,
+            vol_short,
+            vol_long,
+            DEFAULT_DYNAMIC_WEIGHT,
+            DEFAULT_INTERACTION_WEIGHT,
+            DEFAULT_CONFIRMATION_BOOST,
+            0,
+            DEFAULT_MAX_HISTORY,
+            DEFAULT_HAWKES_MU,
+            DEFAULT_HAWKES_ALPHA,
+            DEFAULT_HAWKES_BETA,
+            DEFAULT_HAWKES_CEILING,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ofi_sum_matches_brute_force_window() {
+        // Integer-valued queues so float sums are exact
+        let mut seed: u64 = 0xD1B5_4A32_D192_ED03;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        for fast in [1, 5, 20] {
+            let mut m = meta(fast, 300, 100, 500);
+            let mut ticks: Vec<f64> = Vec::new();
+            let mut prev: Option<(f64, f64)> = None;
+            for _ in 0..2000 {
+                let bid = (next() % 500) as f64;
+                let ask = (next() % 500) as f64;
+                m.update(0.0, 0.0, bid, ask, 100.0, None);
+
+                let (pb, pa) = prev.unwrap_or((bid, ask));
+                ticks.push((bid - pb) - (ask - pa));
+                prev = Some((bid, ask));
+
+                let window: f64 = ticks.iter().rev().take(fast).sum();
+                assert_eq!(m.ofi_sum, window, "fast={fast}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_reset_clears_ofi_window() {
+        let mut m = meta(3, 10, 5, 10);
+        m.update(1.0, 1.0, 10.0, 10.0, 100.0, None);
+        m.update(1.0, 1.0, 20.0, 5.0, 100.0, None);
+        assert_eq!(m.ofi_sum, 15.0);
+        m.reset();
+        // First tick after reset has no previous queue: contributes 0
+        m.update(1.0, 1.0, 50.0, 1.0, 100.0, None);
+        assert_eq!(m.ofi_sum, 0.0);
+    }
+
+    #[test]
+    fn test_weights_settable_and_survive_reset() {
+        let mut m = meta(3, 10, 5, 10);
+        assert_eq!(m.get_dynamic_weight(), DEFAULT_DYNAMIC_WEIGHT);
+        m.set_weights(1.0, 0.0).unwrap();
+        m.set_confirmation_boost(1.0).unwrap();
+        m.reset();
+        assert_eq!(m.get_dynamic_weight(), 1.0);
+        assert_eq!(m.get_interaction_weight(), 0.0);
+        assert_eq!(m.get_confirmation_boost(), 1.0);
+
+        // Interaction weight 0: output is exactly the dynamic component
+        let out = m.update(2.0, 1.0, 10.0, 5.0, 100.0, None);
+        assert_eq!(out, m.get_signals().0);
+
+        assert!(m.set_weights(f64::NAN, 0.5).is_err());
+        assert!(m.set_confirmation_boost(f64::INFINITY).is_err());
+        assert!(MetaAlpha::new(
+            20,
+            300,
+            100,
+            500,
+            0.5,
+            f64::NAN,
+            1.5,
+            0,
+            10,
+            0.02,
+            0.2,
+            0.1,
+            2.0
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_rolling_corr_matches_brute_force() {
+        let xs = [1.0, 3.0, 2.0, 5.0, 4.0, 7.0, 6.0, 9.0];
+        let ys = [2.0, 1.0, 4.0, 3.0, 6.0, 5.0, 9.0, 7.0];
+        let w = 4;
+        let mut rc = RollingCorr::default();
+        for t in 0..xs.len() {
+            rc.add(xs[t], ys[t]);
+            if t >= w {
+                rc.remove(xs[t - w], ys[t - w]);
+            }
+            if t + 1 >= w {
+                let (x, y) = (&xs[t + 1 - w..=t], &ys[t + 1 - w..=t]);
+                let mx = x.iter().sum::<f64>() / w as f64;
+                let my = y.iter().sum::<f64>() / w as f64;
+                let cov: f64 = x.iter().zip(y).map(|(a, b)| (a - mx) * (b - my)).sum();
+                let vx: f64 = x.iter().map(|a| (a - mx).powi(2)).sum();
+                let vy: f64 = y.iter().map(|b| (b - my).powi(2)).sum();
+                assert!((rc.corr(w) - cov / (vx * vy).sqrt()).abs() < 1e-12, "t={t}");
+            }
+        }
+        // Constant side has no defined correlation
+        let mut flat = RollingCorr::default();
+        flat.add(1.0, 2.0);
+        flat.add(1.0, 3.0);
+        assert_eq!(flat.corr(2), 0.0);
+    }
+
+    #[test]
+    fn test_rolling_correlation_and_beta() {
+        let mut rc = RollingCorrelation::new(3).unwrap();
+        assert_eq!(rc.update(1.0, 3.0).unwrap(), 0.0);
+        assert_eq!(rc.update(2.0, 5.0).unwrap(), 0.0); // not warm yet
+        assert_eq!(rc.beta(), 0.0);
+        // y = 2x + 1 exactly
+        assert!((rc.update(3.0, 7.0).unwrap() - 1.0).abs() < 1e-12);
+        assert!((rc.beta() - 2.0).abs() < 1e-12);
+        // Window slides: y = -0.5x + 10 over the last three pairs
+        rc.update(10.0, 5.0).unwrap();
+        rc.update(12.0, 4.0).unwrap();
+        let corr = rc.update(14.0, 3.0).unwrap();
+        assert!((corr + 1.0).abs() < 1e-12);
+        assert!((rc.beta() + 0.5).abs() < 1e-12);
+
+        let mut flat = RollingCorrelation::new(2).unwrap();
+        flat.update(1.0, 1.0).unwrap();
+        assert_eq!(flat.update(1.0, 2.0).unwrap(), 0.0);
+        assert_eq!(flat.beta(), 0.0);
+        assert!(RollingCorrelation::new(1).is_err());
+        assert!(rc.update(f64::NAN, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_ic_weights_follow_positive_ic() {
+        let mut m =
+            MetaAlpha::new(3, 10, 5, 10, 0.57, 0.43, 1.5, 4, 10, 0.02, 0.2, 0.1, 2.0).unwrap();
+        let rets = [0.01, -0.02, 0.03, -0.01, 0.02, 0.0];
+        let feed = |m: &mut MetaAlpha, f: &dyn Fn(f64) -> (f64, f64)| {
+            for &r in &rets {
+                (m.signal_dynamic, m.signal_interaction) = f(r);
+                m.update_ic_weights(r);
+            }
+        };
+
+        // Neither component predicts the return: weights stay put
+        feed(&mut m, &|r| (-r, -2.0 * r));
+        assert_eq!(m.effective_weights(), (0.57, 0.43));
+
+        // Only the dynamic component has positive IC
+        feed(&mut m, &|r| (r, -r));
+        let (ic_d, ic_i) = m.current_ics();
+        assert!((ic_d - 1.0).abs() < 1e-9 && (ic_i + 1.0).abs() < 1e-9);
+        assert_eq!(m.effective_weights(), (1.0, 0.0));
+
+        // Both positive: weights proportional to IC and sum to 1
+        feed(&mut m, &|r| (r, r + if r > 0.015 { -0.03 } else { 0.0 }));
+        let (ic_d, ic_i) = m.current_ics();
+        assert!(ic_i > 0.0 && ic_i < ic_d);
+        let (w_d, w_i) = m.effective_weights();
+        assert!((w_d - ic_d / (ic_d + ic_i)).abs() < 1e-12);
+        assert!((w_d + w_i - 1.0).abs() < 1e-12);
+        // Adaptation never overwrites the configured weights
+        assert_eq!(m.get_dynamic_weight(), 0.57);
+        assert_eq!(m.get_interaction_weight(), 0.43);
+
+        // Reset starts again from the configured weights
+        m.reset();
+        assert_eq!(m.current_ics(), (0.0, 0.0));
+        assert_eq!(m.effective_weights(), (0.57, 0.43));
+
+        // Disabled: static weights, no ICs
+        let mut fixed = meta(3, 10, 5, 10);
+        for i in 0..40 {
+            fixed.update(5.0, 1.0, 10.0, 10.0, 100.0 + i as f64, None);
+        }
+        assert_eq!(fixed.current_ics(), (0.0, 0.0));
+        assert_eq!(fixed.get_dynamic_weight(), DEFAULT_DYNAMIC_WEIGHT);
+    }
+
+    #[test]
+    fn test_history_bounded_and_ordered() {
+        let mut m =
+            MetaAlpha::new(3, 10, 5, 10, 0.57, 0.43, 1.5, 0, 3, 0.02, 0.2, 0.1, 2.0).unwrap();
+        let mut combined = Vec::new();
+        for i in 0..5 {
+            combined.push(m.update(5.0, 1.0, 10.0 + i as f64, 10.0, 100.0, None));
+        }
+        let (d, i, c) = m.history(10);
+        assert_eq!(c, combined[2..].to_vec());
+        assert_eq!((d.len(), i.len()), (3, 3));
+        assert_eq!((*d.last().unwrap(), *i.last().unwrap()), m.get_signals());
+
+        let (_, _, last) = m.history(1);
+        assert_eq!(last, vec![combined[4]]);
+
+        m.reset();
+        assert!(m.history(10).2.is_empty());
+
+        let mut off =
+            MetaAlpha::new(3, 10, 5, 10, 0.57, 0.43, 1.5, 0, 0, 0.02, 0.2, 0.1, 2.0).unwrap();
+        off.update(5.0, 1.0, 10.0, 10.0, 100.0, None);
+        assert!(off.history(10).2.is_empty());
+    }
+
+    #[test]
+    fn test_hawkes_params_configurable() {
+        let mut m = meta(3, 10, 5, 10);
+        assert_eq!(m.get_hawkes_params(), (0.02, 0.2, 0.1, 2.0));
+        for _ in 0..50 {
+            m.update(1.0, 1.0, 10.0, 10.0, 100.0, None);
+        }
+        // Steady state with the defaults: mu + alpha / (1 - e^-beta) ~ 2.1
+        let saturated = m.hawkes_intensity();
+        assert!(saturated > 2.0);
+
+        let mut wide =
+            MetaAlpha::new(3, 10, 5, 10, 0.57, 0.43, 1.5, 0, 10, 0.02, 0.2, 0.1, 10.0).unwrap();
+        for _ in 0..50 {
+            wide.update(1.0, 1.0, 10.0, 10.0, 100.0, None);
+        }
+        assert_eq!(wide.hawkes_intensity(), saturated);
+        // Default ceiling saturates the normalized signal, fully damping the
+        // flow term; ceiling=10 leaves 0.5 * flow * (1 - intensity / 10)
+        let (dyn_m, inter_m) = m.get_signals();
+        let (dyn_w, inter_w) = wide.get_signals();
+        assert_eq!(dyn_m, dyn_w);
+        assert_eq!(inter_m, 0.0);
+        assert!((inter_w - 0.5 * 3.0 * (1.0 - saturated / 10.0)).abs() < 1e-12);
+
+        m.set_hawkes_params(0.5, 0.0, 1.0, None).unwrap();
+        for _ in 0..100 {
+            m.update(0.0, 1.0, 10.0, 10.0, 100.0, None);
+        }
+        assert!((m.hawkes_intensity() - 0.5).abs() < 1e-9);
+        assert_eq!(m.get_hawkes_params().3, 2.0);
+        assert!(m.set_hawkes_params(0.1, 0.1, 0.0, None).is_err());
+        assert!(m.set_hawkes_params(0.1, 0.1, 0.1, Some(0.0)).is_err());
+    }
+
+    #[test]
+    fn test_hawkes_decays_by_real_time() {
+        let mut m = meta(3, 10, 5, 10);
+        m.set_hawkes_params(0.0, 1.0, 1.0, None).unwrap();
+        m.update(1.0, 1.0, 10.0, 10.0, 100.0, Some(1_000_000_000));
+        assert_eq!(m.hawkes_intensity(), 1.0);
+        // Two seconds of silence decay twice as far as one
+        m.update(0.0, 1.0, 10.0, 10.0, 100.0, Some(3_000_000_000));
+        assert!((m.hawkes_intensity() - (-2.0f64).exp()).abs() < 1e-12);
+        // Same timestamp: no decay, just the jump
+        let before = m.hawkes_intensity();
+        m.update(1.0, 1.0, 10.0, 10.0, 100.0, Some(3_000_000_000));
+        assert!((m.hawkes_intensity() - (before + 1.0)).abs() < 1e-12);
+
+        // Without timestamps every call is one unit of time
+        let mut untimed = meta(3, 10, 5, 10);
+        untimed.set_hawkes_params(0.0, 1.0, 1.0, None).unwrap();
+        untimed.update(1.0, 1.0, 10.0, 10.0, 100.0, None);
+        untimed.update(0.0, 1.0, 10.0, 10.0, 100.0, None);
+        assert!((untimed.hawkes_intensity() - (-1.0f64).exp()).abs() < 1e-12);
+
+        m.reset();
+        m.update(1.0, 1.0, 10.0, 10.0, 100.0, Some(10_000_000_000));
+        assert_eq!(m.hawkes_intensity(), 1.0);
     }
 }
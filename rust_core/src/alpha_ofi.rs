@@ -1,17 +1,30 @@
-use numpy::ndarray::Array1;
-use numpy::{IntoPyArray, PyArray1, PyReadonlyArray1};
+use numpy::ndarray::{Array1, ArrayView1, ArrayView2};
+use numpy::{IntoPyArray, PyArray1, PyReadonlyArray1, PyReadonlyArray2};
 use pyo3::prelude::*;
 
+/// Default per-tick decay for `compute_decayed`.
+const DEFAULT_OFI_DECAY: f64 = 0.9;
+
+/// Order Flow Imbalance over arrays of book snapshots.
+///
+/// Sign convention (shared with `feature.rs` `ofi_l1_*`): OFI = bid_flow -
+/// ask_flow, so positive values mean net buying pressure.
+///
+/// With `normalized`, each tick's OFI is divided by that tick's total L1
+/// depth (best bid qty + best ask qty), giving a scale-free value; ticks
+/// with no L1 depth read 0.
 #[pyclass]
 pub struct AlphaOFI {
-    // No internal state needed for basic OFI, but struct required for class
+    #[pyo3(get)]
+    normalized: bool,
 }
 
 #[pymethods]
 impl AlphaOFI {
     #[new]
-    pub fn new() -> Self {
-        AlphaOFI {}
+    #[pyo3(signature = (normalized=false))]
+    pub fn new(normalized: bool) -> Self {
+        AlphaOFI { normalized }
     }
 
     /// Compute Order Flow Imbalance (OFI)
@@ -26,52 +39,286 @@ impl AlphaOFI {
         bid_v: PyReadonlyArray1<'py, f64>,
         ask_v: PyReadonlyArray1<'py, f64>,
     ) -> PyResult<Py<PyArray1<f64>>> {
-        let bid_p = bid_p.as_array();
-        let ask_p = ask_p.as_array();
-        let bid_v = bid_v.as_array();
-        let ask_v = ask_v.as_array();
-
-        let n = bid_p.len();
-        // Ensure all lengths match
-        if ask_p.len() != n || bid_v.len() != n || ask_v.len() != n {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "Input arrays must have same length",
-            ));
+        let mut ofi = ofi_l1(
+            bid_p.as_array(),
+            ask_p.as_array(),
+            bid_v.as_array(),
+            ask_v.as_array(),
+        )?;
+        if self.normalized {
+            normalize_by_depth(&mut ofi, bid_v.as_array(), ask_v.as_array());
         }
 
-        // Allocate output array once
-        let mut ofi = Array1::<f64>::zeros(n);
-
-        // Loop from 1 to n (skip 0)
-        for t in 1..n {
-            // Bid flow
-            let b_flow = if bid_p[t] > bid_p[t - 1] {
-                bid_v[t]
-            } else if bid_p[t] < bid_p[t - 1] {
-                -bid_v[t - 1]
-            } else {
-                bid_v[t] - bid_v[t - 1]
-            };
-
-            // Ask flow
-            let a_flow = if ask_p[t] < ask_p[t - 1] {
-                ask_v[t]
-            } else if ask_p[t] > ask_p[t - 1] {
-                -ask_v[t - 1]
-            } else {
-                ask_v[t] - ask_v[t - 1]
-            };
-
-            ofi[t] = b_flow - a_flow;
+        // Convert to Python Object (Zero-Copy if possible, but here we transfer ownership of new array)
+        Ok(ofi.into_pyarray_bound(py).unbind())
+    }
+
+    /// Multi-level OFI summed across levels.
+    /// Input: bid_p, ask_p, bid_v, ask_v (2D arrays, shape [n, levels], best level first)
+    /// Output: ofi (1D array of length n)
+    fn compute_multilevel<'py>(
+        &self,
+        py: Python<'py>,
+        bid_p: PyReadonlyArray2<'py, f64>,
+        ask_p: PyReadonlyArray2<'py, f64>,
+        bid_v: PyReadonlyArray2<'py, f64>,
+        ask_v: PyReadonlyArray2<'py, f64>,
+    ) -> PyResult<Py<PyArray1<f64>>> {
+        let mut ofi = ofi_multilevel(
+            bid_p.as_array(),
+            ask_p.as_array(),
+            bid_v.as_array(),
+            ask_v.as_array(),
+        )?;
+        let (bid_v, ask_v) = (bid_v.as_array(), ask_v.as_array());
+        // Shapes match, so column 0 exists on both sides if it does on one
+        if self.normalized && bid_v.ncols() > 0 {
+            normalize_by_depth(&mut ofi, bid_v.column(0), ask_v.column(0));
         }
+        Ok(ofi.into_pyarray_bound(py).unbind())
+    }
 
-        // Convert to Python Object (Zero-Copy if possible, but here we transfer ownership of new array)
+    /// Decayed cumulative L1 OFI: `out[t] = ofi[t] + decay * out[t-1]`.
+    /// Input: bid_p, ask_p, bid_v, ask_v (1D arrays), decay in [0, 1)
+    /// Output: decayed ofi (1D array)
+    #[pyo3(signature = (bid_p, ask_p, bid_v, ask_v, decay=DEFAULT_OFI_DECAY))]
+    fn compute_decayed<'py>(
+        &self,
+        py: Python<'py>,
+        bid_p: PyReadonlyArray1<'py, f64>,
+        ask_p: PyReadonlyArray1<'py, f64>,
+        bid_v: PyReadonlyArray1<'py, f64>,
+        ask_v: PyReadonlyArray1<'py, f64>,
+        decay: f64,
+    ) -> PyResult<Py<PyArray1<f64>>> {
+        if !(0.0..1.0).contains(&decay) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "decay must be in [0, 1), got {decay}"
+            )));
+        }
+        let mut ofi = ofi_l1(
+            bid_p.as_array(),
+            ask_p.as_array(),
+            bid_v.as_array(),
+            ask_v.as_array(),
+        )?;
+        if self.normalized {
+            normalize_by_depth(&mut ofi, bid_v.as_array(), ask_v.as_array());
+        }
+        decay_in_place(&mut ofi, decay);
         Ok(ofi.into_pyarray_bound(py).unbind())
     }
 }
 
 impl Default for AlphaOFI {
     fn default() -> Self {
-        Self::new()
+        Self::new(false)
+    }
+}
+
+/// Cont-style flow at one level between consecutive snapshots.
+#[allow(clippy::too_many_arguments)]
+#[inline(always)]
+fn level_flow(
+    bp: f64,
+    bp_prev: f64,
+    bv: f64,
+    bv_prev: f64,
+    ap: f64,
+    ap_prev: f64,
+    av: f64,
+    av_prev: f64,
+) -> f64 {
+    // Bid flow
+    let b_flow = if bp > bp_prev {
+        bv
+    } else if bp < bp_prev {
+        -bv_prev
+    } else {
+        bv - bv_prev
+    };
+
+    // Ask flow
+    let a_flow = if ap < ap_prev {
+        av
+    } else if ap > ap_prev {
+        -av_prev
+    } else {
+        av - av_prev
+    };
+
+    b_flow - a_flow
+}
+
+fn ofi_l1(
+    bid_p: ArrayView1<f64>,
+    ask_p: ArrayView1<f64>,
+    bid_v: ArrayView1<f64>,
+    ask_v: ArrayView1<f64>,
+) -> PyResult<Array1<f64>> {
+    let n = bid_p.len();
+    // Ensure all lengths match
+    if ask_p.len() != n || bid_v.len() != n || ask_v.len() != n {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Input arrays must have same length",
+        ));
+    }
+
+    // Allocate output array once
+    let mut ofi = Array1::<f64>::zeros(n);
+
+    // Loop from 1 to n (skip 0)
+    for t in 1..n {
+        ofi[t] = level_flow(
+            bid_p[t],
+            bid_p[t - 1],
+            bid_v[t],
+            bid_v[t - 1],
+            ask_p[t],
+            ask_p[t - 1],
+            ask_v[t],
+            ask_v[t - 1],
+        );
+    }
+    Ok(ofi)
+}
+
+fn ofi_multilevel(
+    bid_p: ArrayView2<f64>,
+    ask_p: ArrayView2<f64>,
+    bid_v: ArrayView2<f64>,
+    ask_v: ArrayView2<f64>,
+) -> PyResult<Array1<f64>> {
+    let shape = bid_p.dim();
+    if ask_p.dim() != shape || bid_v.dim() != shape || ask_v.dim() != shape {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Input arrays must have same shape [n, levels]",
+        ));
+    }
+    let (n, levels) = shape;
+
+    let mut ofi = Array1::<f64>::zeros(n);
+    for t in 1..n {
+        let mut sum = 0.0;
+        for l in 0..levels {
+            sum += level_flow(
+                bid_p[[t, l]],
+                bid_p[[t - 1, l]],
+                bid_v[[t, l]],
+                bid_v[[t - 1, l]],
+                ask_p[[t, l]],
+                ask_p[[t - 1, l]],
+                ask_v[[t, l]],
+                ask_v[[t - 1, l]],
+            );
+        }
+        ofi[t] = sum;
+    }
+    Ok(ofi)
+}
+
+/// `ofi[t] /= bid_v[t] + ask_v[t]`, or 0 where that depth is not positive.
+fn normalize_by_depth(ofi: &mut Array1<f64>, bid_v: ArrayView1<f64>, ask_v: ArrayView1<f64>) {
+    for (t, x) in ofi.iter_mut().enumerate() {
+        let depth = bid_v[t] + ask_v[t];
+        *x = if depth > 0.0 { *x / depth } else { 0.0 };
+    }
+}
+
+/// `x[t] += decay * x[t-1]`, i.e. the geometric recursion `ofi = raw + decay * prev`.
+fn decay_in_place(ofi: &mut Array1<f64>, decay: f64) {
+    for t in 1..ofi.len() {
+        ofi[t] += decay * ofi[t - 1];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use numpy::ndarray::{array, Array2};
+
+    #[test]
+    fn test_ofi_sign_positive_for_bid_pressure() {
+        // Bid queue grows at the same price: buying pressure → positive OFI
+        let bp = array![100.0, 100.0];
+        let ap = array![101.0, 101.0];
+        let bv = array![10.0, 15.0];
+        let av = array![10.0, 10.0];
+        let ofi = ofi_l1(bp.view(), ap.view(), bv.view(), av.view()).unwrap();
+        assert_eq!(ofi.to_vec(), vec![0.0, 5.0]);
+    }
+
+    #[test]
+    fn test_ofi_sign_positive_for_rising_bid() {
+        // Bid steps up a tick: new buying interest → positive OFI, and the
+        // mirror move on the ask (ask steps down) is negative
+        let ofi = ofi_l1(
+            array![100.0, 101.0].view(),
+            array![102.0, 102.0].view(),
+            array![10.0, 4.0].view(),
+            array![10.0, 10.0].view(),
+        )
+        .unwrap();
+        assert_eq!(ofi[1], 4.0);
+        let ofi = ofi_l1(
+            array![100.0, 100.0].view(),
+            array![102.0, 101.0].view(),
+            array![10.0, 10.0].view(),
+            array![10.0, 4.0].view(),
+        )
+        .unwrap();
+        assert_eq!(ofi[1], -4.0);
+    }
+
+    #[test]
+    fn test_normalize_by_depth() {
+        let bv = array![10.0, 15.0, 0.0];
+        let av = array![10.0, 5.0, 0.0];
+        let mut ofi = array![0.0, 5.0, 3.0];
+        normalize_by_depth(&mut ofi, bv.view(), av.view());
+        // 5 / (15 + 5); empty book reads 0
+        assert_eq!(ofi.to_vec(), vec![0.0, 0.25, 0.0]);
+    }
+
+    #[test]
+    fn test_multilevel_single_level_matches_l1() {
+        let bp = array![100.0, 100.0, 101.0, 100.0];
+        let ap = array![102.0, 101.0, 102.0, 102.0];
+        let bv = array![10.0, 12.0, 5.0, 8.0];
+        let av = array![7.0, 3.0, 9.0, 9.0];
+        let l1 = ofi_l1(bp.view(), ap.view(), bv.view(), av.view()).unwrap();
+
+        let col = |a: &Array1<f64>| a.clone().into_shape((4, 1)).unwrap();
+        let ml = ofi_multilevel(
+            col(&bp).view(),
+            col(&ap).view(),
+            col(&bv).view(),
+            col(&av).view(),
+        )
+        .unwrap();
+        assert_eq!(l1, ml);
+    }
+
+    #[test]
+    fn test_multilevel_sums_levels() {
+        let bp = array![[100.0, 99.0], [100.0, 99.0]];
+        let ap = array![[101.0, 102.0], [101.0, 102.0]];
+        let bv = array![[10.0, 20.0], [12.0, 25.0]];
+        let av = array![[5.0, 5.0], [4.0, 8.0]];
+        // L1: +2 - (-1) = 3; L2: +5 - 3 = 2
+        let ofi = ofi_multilevel(bp.view(), ap.view(), bv.view(), av.view()).unwrap();
+        assert_eq!(ofi.to_vec(), vec![0.0, 5.0]);
+
+        let bad = Array2::<f64>::zeros((2, 3));
+        assert!(ofi_multilevel(bad.view(), ap.view(), bv.view(), av.view()).is_err());
+    }
+
+    #[test]
+    fn test_decay_recursion() {
+        let mut ofi = array![0.0, 10.0, 0.0, 5.0];
+        decay_in_place(&mut ofi, 0.9);
+        assert_eq!(ofi[1], 10.0);
+        assert!((ofi[2] - 9.0).abs() < 1e-12);
+        assert!((ofi[3] - 13.1).abs() < 1e-12);
     }
 }
@@ -1,91 +1,183 @@
-use crate::lob::LimitOrderBook;
+use crate::lob::{BookView, IntLimitOrderBook, LimitOrderBook};
+use crate::vol::VolMonitor;
+use crate::warmup::{WarmupGate, WarmupPolicy, DEFAULT_WARMUP_POLICY};
 use pyo3::prelude::*;
 
+/// L1 queue pressure, gated on EWMA volatility of the mid.
+///
+/// Emits `bid_v - ask_v` while volatility is at or above `vol_threshold`, 0
+/// otherwise. With `normalize=true` the difference is divided by
+/// `bid_v + ask_v`, giving an imbalance in [-1, 1] (0 when both queues are
+/// empty).
 #[pyclass]
 pub struct AlphaRegimePressure {
     // Volatility Monitor
-    vol_alpha: f64,
-    ewma_variance: f64,
-    prev_mid: f64,
+    vol: VolMonitor,
     vol_threshold: f64,
 
+    normalize: bool,
+
     // State
-    initialized: bool,
+    warmup: WarmupGate,
 }
 
 #[pymethods]
 impl AlphaRegimePressure {
     #[new]
-    pub fn new(vol_window: usize, vol_threshold: f64) -> Self {
-        // EWMA alpha for variance
-        // Center of mass ~ window
-        let vol_alpha = 2.0 / (vol_window as f64 + 1.0);
-
-        AlphaRegimePressure {
-            vol_alpha,
-            ewma_variance: 0.0,
-            prev_mid: f64::NAN,
+    #[pyo3(signature = (vol_window, vol_threshold, warmup_policy=DEFAULT_WARMUP_POLICY, normalize=false))]
+    pub fn new(
+        vol_window: usize,
+        vol_threshold: f64,
+        warmup_policy: &str,
+        normalize: bool,
+    ) -> PyResult<Self> {
+        Ok(AlphaRegimePressure {
+            vol: VolMonitor::new(vol_window)?,
             vol_threshold,
-            initialized: false,
-        }
+            normalize,
+            warmup: WarmupGate::new(WarmupPolicy::parse(warmup_policy)?),
+        })
     }
 
     pub fn calculate(&mut self, lob: &LimitOrderBook) -> f64 {
+        self.calculate_book(lob)
+    }
+
+    /// `calculate` on an integer-lot book.
+    pub fn calculate_int(&mut self, lob: &IntLimitOrderBook) -> f64 {
+        self.calculate_book(lob)
+    }
+
+    /// Clear the volatility state (e.g. at session start).
+    /// The last warm signal is kept so "hold" bridges the new warmup.
+    pub fn reset(&mut self) {
+        self.vol.reset();
+    }
+
+    #[getter]
+    pub fn get_warmup_policy(&self) -> &'static str {
+        self.warmup.policy().name()
+    }
+
+    #[getter]
+    pub fn get_current_vol(&self) -> f64 {
+        self.vol.current_vol()
+    }
+
+    #[getter]
+    pub fn get_normalize(&self) -> bool {
+        self.normalize
+    }
+}
+
+impl AlphaRegimePressure {
+    /// Shared body of `calculate` / `calculate_int`.
+    pub fn calculate_book<B: BookView>(&mut self, lob: &B) -> f64 {
         // 1. Calculate Mid Price
-        let best_bid_opt = lob.bids.iter().next_back();
-        let best_ask_opt = lob.asks.iter().next();
+        let best_bid_opt = lob.best_bid_level();
+        let best_ask_opt = lob.best_ask_level();
 
         let (bid_p, bid_v) = match best_bid_opt {
-            Some((&p, &v)) => (p as f64 / 10000.0, v),
+            Some((p, v)) => (p as f64 / 10000.0, v),
             None => return 0.0,
         };
 
         let (ask_p, ask_v) = match best_ask_opt {
-            Some((&p, &v)) => (p as f64 / 10000.0, v),
+            Some((p, v)) => (p as f64 / 10000.0, v),
             None => return 0.0,
         };
 
         let mid = (bid_p + ask_p) / 2.0;
 
         // 2. Update Volatility (EWMA Variance of Returns)
-        // Return = ln(pt / pt-1) approx (pt - pt-1)/pt-1
-        // Used: (mid - prev) / prev
-
-        let mut current_vol = 0.0;
-
-        if self.initialized {
-            if mid > 0.0 && self.prev_mid > 0.0 {
-                let ret = (mid - self.prev_mid) / self.prev_mid;
-                let ret_sq = ret * ret;
-
-                // Update Variance
-                self.ewma_variance =
-                    self.vol_alpha * ret_sq + (1.0 - self.vol_alpha) * self.ewma_variance;
-                current_vol = self.ewma_variance.sqrt();
-            }
-        } else {
-            self.initialized = true;
-        }
-        self.prev_mid = mid;
+        let current_vol = self.vol.update(mid);
+
+        // Volatility estimate is cold until vol_window returns have been seen
+        let warm = self.vol.is_warm();
 
         // 3. Check Regime
         if current_vol < self.vol_threshold {
-            return 0.0; // Low Volatility -> Gate Closed
+            return self.warmup.emit(warm, 0.0); // Low Volatility -> Gate Closed
         }
 
         // 4. Calculate QueuePressure
         // Formula: BidVol - AskVol at L1
-        // Note: Raw volume difference can be large.
-        // Python code: bid_v - ask_v.
-        // Should we normalize? The factor registry didn't.
-        // But for trading, raw diff is fine if strategy scales it or uses sign.
-        // Let's return raw diff.
+        // Raw diff matches the Python factor registry; normalized form is
+        // comparable across instruments.
+        self.warmup
+            .emit(warm, queue_pressure(bid_v, ask_v, self.normalize))
+    }
+}
 
-        bid_v - ask_v
+#[inline(always)]
+fn queue_pressure(bid_v: f64, ask_v: f64, normalize: bool) -> f64 {
+    if !normalize {
+        return bid_v - ask_v;
+    }
+    let total = bid_v + ask_v;
+    if total > 0.0 {
+        (bid_v - ask_v) / total
+    } else {
+        0.0
     }
+}
 
-    #[getter]
-    pub fn get_current_vol(&self) -> f64 {
-        self.ewma_variance.sqrt()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(bid_v: f64, ask_v: f64) -> LimitOrderBook {
+        book_at(100.0, bid_v, ask_v)
+    }
+
+    fn book_at(bid_p: f64, bid_v: f64, ask_v: f64) -> LimitOrderBook {
+        let mut lob = LimitOrderBook::new("TEST".to_string(), false);
+        lob.update(true, bid_p, bid_v, None);
+        lob.update(false, bid_p + 1.0, ask_v, None);
+        lob
+    }
+
+    #[test]
+    fn test_normalize_bounds_pressure() {
+        let lob = book(30.0, 10.0);
+        // Threshold 0 keeps the gate open once a return has been seen
+        let mut raw = AlphaRegimePressure::new(1, 0.0, "zero", false).unwrap();
+        let mut norm = AlphaRegimePressure::new(1, 0.0, "zero", true).unwrap();
+        raw.calculate(&lob);
+        norm.calculate(&lob);
+        assert_eq!(raw.calculate(&lob), 20.0);
+        assert_eq!(norm.calculate(&lob), 0.5);
+    }
+
+    #[test]
+    fn test_int_book_matches_float_book() {
+        let mut ib = IntLimitOrderBook::new("TEST".to_string(), false);
+        ib.update(true, 100.0, 30);
+        ib.update(false, 101.0, 10);
+        let fb = book(30.0, 10.0);
+        let mut a = AlphaRegimePressure::new(1, 0.0, "zero", false).unwrap();
+        let mut b = AlphaRegimePressure::new(1, 0.0, "zero", false).unwrap();
+        for _ in 0..3 {
+            assert_eq!(a.calculate(&fb), b.calculate_int(&ib));
+        }
+    }
+
+    #[test]
+    fn test_normalize_zero_total() {
+        assert_eq!(queue_pressure(0.0, 0.0, true), 0.0);
+        assert_eq!(queue_pressure(0.0, 5.0, true), -1.0);
+        assert_eq!(queue_pressure(0.0, 5.0, false), -5.0);
+    }
+
+    #[test]
+    fn test_default_policy_emits_once_vol_clears_threshold() {
+        // vol_window=10 stays cold for the whole test; the default still
+        // emits pressure as soon as the single return clears the threshold.
+        let mut legacy = AlphaRegimePressure::new(10, 1e-6, DEFAULT_WARMUP_POLICY, false).unwrap();
+        let mut zero = AlphaRegimePressure::new(10, 1e-6, "zero", false).unwrap();
+        assert_eq!(legacy.calculate(&book_at(100.0, 30.0, 10.0)), 0.0);
+        zero.calculate(&book_at(100.0, 30.0, 10.0));
+        assert_eq!(legacy.calculate(&book_at(102.0, 30.0, 10.0)), 20.0);
+        assert_eq!(zero.calculate(&book_at(102.0, 30.0, 10.0)), 0.0);
     }
 }
@@ -1,12 +1,12 @@
-use crate::lob::LimitOrderBook;
+use crate::lob::{BookView, IntLimitOrderBook, LimitOrderBook};
+use crate::vol::VolMonitor;
+use crate::warmup::{WarmupGate, WarmupPolicy, DEFAULT_WARMUP_POLICY};
 use pyo3::prelude::*;
 
 #[pyclass]
 pub struct AlphaRegimeReversal {
     // Volatility Monitor
-    vol_alpha: f64,
-    ewma_variance: f64,
-    prev_mid: f64,
+    vol: VolMonitor,
     vol_threshold: f64,
 
     // SMA State
@@ -17,20 +17,22 @@ pub struct AlphaRegimeReversal {
     count: usize,
 
     // State
-    initialized: bool,
+    warmup: WarmupGate,
 }
 
 #[pymethods]
 impl AlphaRegimeReversal {
     #[new]
-    pub fn new(vol_window: usize, vol_threshold: f64, sma_window: usize) -> Self {
-        let vol_alpha = 2.0 / (vol_window as f64 + 1.0);
-
-        AlphaRegimeReversal {
+    #[pyo3(signature = (vol_window, vol_threshold, sma_window, warmup_policy=DEFAULT_WARMUP_POLICY))]
+    pub fn new(
+        vol_window: usize,
+        vol_threshold: f64,
+        sma_window: usize,
+        warmup_policy: &str,
+    ) -> PyResult<Self> {
+        Ok(AlphaRegimeReversal {
             // Volatility
-            vol_alpha,
-            ewma_variance: 0.0,
-            prev_mid: f64::NAN,
+            vol: VolMonitor::new(vol_window)?,
             vol_threshold,
 
             // SMA
@@ -40,42 +42,70 @@ impl AlphaRegimeReversal {
             idx: 0,
             count: 0,
 
-            initialized: false,
-        }
+            warmup: WarmupGate::new(WarmupPolicy::parse(warmup_policy)?),
+        })
     }
 
     pub fn calculate(&mut self, lob: &LimitOrderBook) -> f64 {
+        self.calculate_book(lob)
+    }
+
+    /// `calculate` on an integer-lot book.
+    pub fn calculate_int(&mut self, lob: &IntLimitOrderBook) -> f64 {
+        self.calculate_book(lob)
+    }
+
+    /// Clear the volatility and SMA state (e.g. at session start).
+    /// The last warm signal is kept so "hold" bridges the new warmup.
+    pub fn reset(&mut self) {
+        self.vol.reset();
+        self.buffer.iter_mut().for_each(|v| *v = 0.0);
+        self.sum = 0.0;
+        self.idx = 0;
+        self.count = 0;
+    }
+
+    #[getter]
+    pub fn get_warmup_policy(&self) -> &'static str {
+        self.warmup.policy().name()
+    }
+
+    #[getter]
+    pub fn get_current_vol(&self) -> f64 {
+        self.vol.current_vol()
+    }
+
+    #[getter]
+    pub fn get_current_ma(&self) -> f64 {
+        if self.count > 0 {
+            self.sum / (self.count as f64)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl AlphaRegimeReversal {
+    /// Shared body of `calculate` / `calculate_int`.
+    pub fn calculate_book<B: BookView>(&mut self, lob: &B) -> f64 {
         // 1. Calculate Mid Price
-        let best_bid_opt = lob.bids.iter().next_back();
-        let best_ask_opt = lob.asks.iter().next();
+        let best_bid_opt = lob.best_bid_level();
+        let best_ask_opt = lob.best_ask_level();
 
         let (bid_p, _) = match best_bid_opt {
-            Some((&p, &v)) => (p as f64 / 10000.0, v),
+            Some((p, v)) => (p as f64 / 10000.0, v),
             None => return 0.0,
         };
 
         let (ask_p, _) = match best_ask_opt {
-            Some((&p, &v)) => (p as f64 / 10000.0, v),
+            Some((p, v)) => (p as f64 / 10000.0, v),
             None => return 0.0,
         };
 
         let mid = (bid_p + ask_p) / 2.0;
 
-        // 2. Update Volatility (Same logic as AlphaRegimePressure)
-        let mut current_vol = 0.0;
-
-        if self.initialized {
-            if mid > 0.0 && self.prev_mid > 0.0 {
-                let ret = (mid - self.prev_mid) / self.prev_mid;
-                let ret_sq = ret * ret;
-                self.ewma_variance =
-                    self.vol_alpha * ret_sq + (1.0 - self.vol_alpha) * self.ewma_variance;
-                current_vol = self.ewma_variance.sqrt();
-            }
-        } else {
-            self.initialized = true;
-        }
-        self.prev_mid = mid;
+        // 2. Update Volatility (EWMA Variance of Returns)
+        let current_vol = self.vol.update(mid);
 
         // 3. Update SMA
         // O(1) rolling sum
@@ -93,36 +123,52 @@ impl AlphaRegimeReversal {
 
         self.idx = (self.idx + 1) % self.window_size;
 
+        // Cold until both the volatility and SMA windows are filled
+        let warm = self.vol.is_warm() && self.count >= self.window_size;
+
         // 4. Check Regime
         if current_vol < self.vol_threshold {
-            return 0.0; // Gate Closed
+            return self.warmup.emit(warm, 0.0); // Gate Closed
         }
 
         // 5. Calculate Reversal Signal
         // Deviation = (Price - MA) / MA
         // Signal = -Deviation
+        let mut signal = 0.0;
         if self.count > 0 {
             let ma = self.sum / (self.count as f64);
             if ma > 1e-9 {
                 let deviation = (mid - ma) / ma;
-                return -deviation;
+                signal = -deviation;
             }
         }
 
-        0.0
+        self.warmup.emit(warm, signal)
     }
+}
 
-    #[getter]
-    pub fn get_current_vol(&self) -> f64 {
-        self.ewma_variance.sqrt()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book_at(bid_p: f64) -> LimitOrderBook {
+        let mut lob = LimitOrderBook::new("TEST".to_string(), false);
+        lob.update(true, bid_p, 10.0, None);
+        lob.update(false, bid_p + 1.0, 10.0, None);
+        lob
     }
 
-    #[getter]
-    pub fn get_current_ma(&self) -> f64 {
-        if self.count > 0 {
-            self.sum / (self.count as f64)
-        } else {
-            0.0
-        }
+    #[test]
+    fn test_default_policy_emits_while_cold() {
+        // vol_window=10 and sma_window=5 are both cold after two ticks; the
+        // default emits the reversal as soon as volatility clears the gate.
+        let mut legacy = AlphaRegimeReversal::new(10, 1e-6, 5, DEFAULT_WARMUP_POLICY).unwrap();
+        let mut zero = AlphaRegimeReversal::new(10, 1e-6, 5, "zero").unwrap();
+        assert_eq!(legacy.calculate(&book_at(100.0)), 0.0);
+        zero.calculate(&book_at(100.0));
+        // mid 102.5 vs SMA 101.5
+        let expected = -(102.5 - 101.5) / 101.5;
+        assert!((legacy.calculate(&book_at(102.0)) - expected).abs() < 1e-12);
+        assert_eq!(zero.calculate(&book_at(102.0)), 0.0);
     }
 }
@@ -5,13 +5,59 @@ use pyo3::prelude::*;
 #[pyclass]
 pub struct AlphaTransientReprice {
     window_size: usize,
+
+    // Streaming state: ring buffer of the last `window_size` mids
+    buffer: Vec<f64>,
+    idx: usize,
+    count: usize,
 }
 
 #[pymethods]
 impl AlphaTransientReprice {
     #[new]
     pub fn new(window_size: usize) -> Self {
-        AlphaTransientReprice { window_size }
+        AlphaTransientReprice {
+            window_size,
+            buffer: vec![0.0; window_size],
+            idx: 0,
+            count: 0,
+        }
+    }
+
+    /// Streaming variant of `compute` for live use: one (bid, ask) per call.
+    /// Returns `-(mid_now - mid_{t-k}) / mid_{t-k}`, or 0 until k+1 mids
+    /// have been seen.
+    pub fn update(&mut self, bid: f64, ask: f64) -> f64 {
+        let k = self.window_size;
+        if k == 0 {
+            return 0.0;
+        }
+
+        let mid_now = (bid + ask) * 0.5;
+
+        // Slot at idx holds mid_{t-k} once the buffer is full
+        let signal = if self.count >= k {
+            let mid_prev = self.buffer[self.idx];
+            if mid_prev.abs() > 1e-9 {
+                -(mid_now - mid_prev) / mid_prev
+            } else {
+                0.0
+            }
+        } else {
+            self.count += 1;
+            0.0
+        };
+
+        self.buffer[self.idx] = mid_now;
+        self.idx = (self.idx + 1) % k;
+        signal
+    }
+
+    /// Clear the streaming buffer.
+    pub fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.idx = 0;
+        self.count = 0;
     }
 
     /// Compute Transient Reprice (Mean Reversion of Returns)
@@ -58,3 +104,31 @@ impl AlphaTransientReprice {
         Ok(signal.into_pyarray_bound(py).unbind())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_emits_after_k_plus_one_mids() {
+        let mut a = AlphaTransientReprice::new(2);
+        // mid 100
+        assert_eq!(a.update(99.0, 101.0), 0.0);
+        // mid 101
+        assert_eq!(a.update(100.0, 102.0), 0.0);
+        // mid 102 vs mid_{t-2} = 100 → -0.02
+        assert!((a.update(101.0, 103.0) + 0.02).abs() < 1e-12);
+        // mid 99 vs 101
+        assert!((a.update(98.0, 100.0) - 2.0 / 101.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_update_guards_zero_mid_and_resets() {
+        let mut a = AlphaTransientReprice::new(1);
+        a.update(0.0, 0.0);
+        assert_eq!(a.update(100.0, 102.0), 0.0);
+        a.reset();
+        assert_eq!(a.update(100.0, 102.0), 0.0);
+        assert_eq!(AlphaTransientReprice::new(0).update(1.0, 2.0), 0.0);
+    }
+}
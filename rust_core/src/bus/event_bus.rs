@@ -1,10 +1,39 @@
 use pyo3::prelude::*;
 use std::collections::VecDeque;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
 
+/// Tag used by the untagged `push`/`pop` API.
+const DEFAULT_TAG: u8 = 0;
+const NUM_TAGS: usize = u8::MAX as usize + 1;
+
+/// Per-tag FIFOs plus the running total the capacity bound applies to.
+struct TaggedQueues {
+    queues: Vec<VecDeque<String>>, // indexed by tag
+    total: usize,
+}
+
+impl TaggedQueues {
+    fn new() -> Self {
+        Self {
+            queues: vec![VecDeque::new(); NUM_TAGS],
+            total: 0,
+        }
+    }
+
+    fn pop_front(&mut self, tag: usize) -> Option<String> {
+        let event = self.queues[tag].pop_front();
+        self.total -= event.is_some() as usize;
+        event
+    }
+}
+
+/// String event bus with one FIFO per `u8` tag, so a consumer can drain
+/// e.g. control messages without dequeuing market data. A bounded bus caps
+/// the total number of events across all tags.
 #[pyclass]
 pub struct EventBus {
-    queue: Arc<Mutex<VecDeque<String>>>,
+    queue: Arc<Mutex<TaggedQueues>>,
+    capacity: Option<usize>, // across all tags; None = unbounded
 }
 
 #[pymethods]
@@ -12,23 +41,117 @@ impl EventBus {
     #[new]
     pub fn new() -> Self {
         Self {
-            queue: Arc::new(Mutex::new(VecDeque::new())),
+            queue: Arc::new(Mutex::new(TaggedQueues::new())),
+            capacity: None,
         }
     }
 
-    pub fn push(&self, event: String) -> PyResult<()> {
-        let mut q = self.queue.lock().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("lock poisoned: {e}"))
-        })?;
-        q.push_back(event);
-        Ok(())
+    /// Bus that holds at most `capacity` events in total (minimum 1).
+    #[staticmethod]
+    pub fn new_bounded(capacity: usize) -> Self {
+        Self {
+            queue: Arc::new(Mutex::new(TaggedQueues::new())),
+            capacity: Some(capacity.max(1)),
+        }
+    }
+
+    /// Enqueue an event on the default tag.
+    /// Returns false (event dropped) if the bus is full.
+    pub fn push(&self, event: String) -> PyResult<bool> {
+        self.push_tagged(DEFAULT_TAG, event)
+    }
+
+    /// Enqueue an event on `tag`. Returns false (event dropped) if full.
+    pub fn push_tagged(&self, tag: u8, event: String) -> PyResult<bool> {
+        let mut state = self.lock()?;
+        if self.capacity.is_some_and(|cap| state.total >= cap) {
+            return Ok(false);
+        }
+        state.queues[tag as usize].push_back(event);
+        state.total += 1;
+        Ok(true)
+    }
+
+    /// Enqueue an event, evicting the oldest events of the same tag while the
+    /// bus is full. If that tag has nothing queued, the longest other tag's
+    /// oldest event is evicted instead. Returns the number of events dropped.
+    #[pyo3(signature = (event, tag=DEFAULT_TAG))]
+    pub fn push_overwrite(&self, event: String, tag: u8) -> PyResult<usize> {
+        let mut state = self.lock()?;
+        let mut dropped = 0;
+        if let Some(cap) = self.capacity {
+            while state.total >= cap {
+                let victim = if state.queues[tag as usize].is_empty() {
+                    (0..NUM_TAGS)
+                        .max_by_key(|&t| state.queues[t].len())
+                        .unwrap_or(tag as usize)
+                } else {
+                    tag as usize
+                };
+                state.pop_front(victim);
+                dropped += 1;
+            }
+        }
+        state.queues[tag as usize].push_back(event);
+        state.total += 1;
+        Ok(dropped)
     }
 
+    /// Dequeue the oldest event on the default tag.
     pub fn pop(&self) -> PyResult<Option<String>> {
-        let mut q = self.queue.lock().map_err(|e| {
+        self.pop_tag(DEFAULT_TAG)
+    }
+
+    /// Dequeue the oldest event on `tag`, leaving other tags untouched.
+    pub fn pop_tag(&self, tag: u8) -> PyResult<Option<String>> {
+        Ok(self.lock()?.pop_front(tag as usize))
+    }
+
+    /// Dequeue up to `max` oldest events on `tag` under a single lock.
+    /// An empty list means the queue was empty.
+    #[pyo3(signature = (max, tag=DEFAULT_TAG))]
+    pub fn pop_batch(&self, max: usize, tag: u8) -> PyResult<Vec<String>> {
+        let mut state = self.lock()?;
+        let q = &mut state.queues[tag as usize];
+        let n = max.min(q.len());
+        let batch: Vec<String> = q.drain(..n).collect();
+        state.total -= n;
+        Ok(batch)
+    }
+
+    /// Total events queued across all tags.
+    pub fn len(&self) -> PyResult<usize> {
+        Ok(self.lock()?.total)
+    }
+
+    /// Events queued on `tag`.
+    pub fn len_tag(&self, tag: u8) -> PyResult<usize> {
+        Ok(self.lock()?.queues[tag as usize].len())
+    }
+
+    pub fn is_empty(&self) -> PyResult<bool> {
+        Ok(self.lock()?.total == 0)
+    }
+
+    /// True if the bus holds `capacity` events across all tags. Always false
+    /// for an unbounded bus.
+    pub fn is_full(&self) -> PyResult<bool> {
+        let total = self.lock()?.total;
+        Ok(self.capacity.is_some_and(|cap| total >= cap))
+    }
+
+    /// Configured total capacity, or None if unbounded.
+    #[getter]
+    pub fn get_capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+}
+
+impl EventBus {
+    fn lock(&self) -> PyResult<MutexGuard<'_, TaggedQueues>> {
+        self.queue.lock().map_err(|e| {
             PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("lock poisoned: {e}"))
-        })?;
-        Ok(q.pop_front())
+        })
     }
 }
 
@@ -47,4 +170,86 @@ mod tests {
         let bus = EventBus::default();
         assert!(Arc::strong_count(&bus.queue) == 1);
     }
+
+    #[test]
+    fn test_bounded_push_rejects_when_full() {
+        let bus = EventBus::new_bounded(2);
+        assert!(bus.push("a".into()).unwrap());
+        assert!(bus.push("b".into()).unwrap());
+        assert!(bus.is_full().unwrap());
+        assert!(!bus.push("c".into()).unwrap());
+        assert_eq!(bus.len().unwrap(), 2);
+        assert_eq!(bus.pop().unwrap().as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn test_push_overwrite_evicts_oldest() {
+        let bus = EventBus::new_bounded(2);
+        assert_eq!(bus.push_overwrite("a".into(), DEFAULT_TAG).unwrap(), 0);
+        assert_eq!(bus.push_overwrite("b".into(), DEFAULT_TAG).unwrap(), 0);
+        assert_eq!(bus.push_overwrite("c".into(), DEFAULT_TAG).unwrap(), 1);
+        assert_eq!(bus.pop().unwrap().as_deref(), Some("b"));
+        assert_eq!(bus.pop().unwrap().as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn test_unbounded_never_full() {
+        let bus = EventBus::new();
+        for i in 0..100 {
+            assert!(bus.push(i.to_string()).unwrap());
+        }
+        assert!(!bus.is_full().unwrap());
+        assert_eq!(bus.get_capacity(), None);
+    }
+
+    #[test]
+    fn test_pop_tag_is_selective() {
+        let bus = EventBus::new();
+        bus.push("tick1".into()).unwrap();
+        bus.push_tagged(2, "ctrl".into()).unwrap();
+        bus.push("tick2".into()).unwrap();
+
+        assert_eq!(bus.pop_tag(2).unwrap().as_deref(), Some("ctrl"));
+        assert_eq!(bus.pop_tag(2).unwrap(), None);
+        assert_eq!(bus.len().unwrap(), 2);
+        assert_eq!(bus.len_tag(DEFAULT_TAG).unwrap(), 2);
+        assert_eq!(bus.pop().unwrap().as_deref(), Some("tick1"));
+    }
+
+    #[test]
+    fn test_bounded_capacity_is_total() {
+        let bus = EventBus::new_bounded(2);
+        assert!(bus.push("a".into()).unwrap());
+        assert!(bus.push_tagged(1, "b".into()).unwrap());
+        // Full across tags, even though each tag holds only one event
+        assert!(bus.is_full().unwrap());
+        assert!(!bus.push_tagged(2, "c".into()).unwrap());
+        assert_eq!(bus.len().unwrap(), 2);
+        assert_eq!(bus.pop_tag(1).unwrap().as_deref(), Some("b"));
+        assert!(bus.push_tagged(2, "c".into()).unwrap());
+    }
+
+    #[test]
+    fn test_push_overwrite_evicts_from_longest_tag_when_own_is_empty() {
+        let bus = EventBus::new_bounded(3);
+        bus.push("t1".into()).unwrap();
+        bus.push("t2".into()).unwrap();
+        bus.push_tagged(1, "fill".into()).unwrap();
+        assert_eq!(bus.push_overwrite("ctrl".into(), 2).unwrap(), 1);
+        assert_eq!(bus.len().unwrap(), 3);
+        assert_eq!(bus.pop().unwrap().as_deref(), Some("t2"));
+        assert_eq!(bus.len_tag(1).unwrap(), 1);
+        assert_eq!(bus.pop_tag(2).unwrap().as_deref(), Some("ctrl"));
+    }
+
+    #[test]
+    fn test_pop_batch() {
+        let bus = EventBus::new();
+        for i in 0..5 {
+            bus.push(i.to_string()).unwrap();
+        }
+        assert_eq!(bus.pop_batch(3, DEFAULT_TAG).unwrap(), vec!["0", "1", "2"]);
+        assert_eq!(bus.pop_batch(10, DEFAULT_TAG).unwrap(), vec!["3", "4"]);
+        assert!(bus.pop_batch(10, DEFAULT_TAG).unwrap().is_empty());
+    }
 }
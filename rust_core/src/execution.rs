@@ -0,0 +1,218 @@
+use pyo3::prelude::*;
+
+/// How `ExecutionScheduler` spreads the target over its time window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleMode {
+    /// Uniform over time.
+    Twap,
+    /// Follows a supplied intraday volume profile.
+    Vwap,
+}
+
+impl ScheduleMode {
+    /// Parse the Python-facing mode name: "twap" or "vwap".
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "twap" => Ok(Self::Twap),
+            "vwap" => Ok(Self::Vwap),
+            other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown mode '{other}' (expected 'twap' or 'vwap')"
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Twap => "twap",
+            Self::Vwap => "vwap",
+        }
+    }
+}
+
+/// TWAP/VWAP schedule for working `target_qty` between `start_ns` and `end_ns`.
+///
+/// In VWAP mode `volume_profile` gives relative volume for equal-width
+/// buckets spanning the window; the schedule is linear within a bucket.
+/// `next_slice(now_ns, filled_so_far)` returns the quantity needed to be back
+/// on schedule, so a fill shortfall is worked immediately and it never
+/// exceeds what is left of the target.
+#[pyclass]
+pub struct ExecutionScheduler {
+    target_qty: f64,
+    start_ns: i64,
+    end_ns: i64,
+    mode: ScheduleMode,
+    /// Cumulative profile fraction at each bucket end (last = 1.0). VWAP only.
+    cum_profile: Vec<f64>,
+}
+
+#[pymethods]
+impl ExecutionScheduler {
+    #[new]
+    #[pyo3(signature = (target_qty, start_ns, end_ns, mode="twap", volume_profile=None))]
+    pub fn new(
+        target_qty: f64,
+        start_ns: i64,
+        end_ns: i64,
+        mode: &str,
+        volume_profile: Option<Vec<f64>>,
+    ) -> PyResult<Self> {
+        let mode = ScheduleMode::parse(mode)?;
+        if !(target_qty.is_finite() && target_qty > 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "target_qty must be positive, got {target_qty}"
+            )));
+        }
+        if end_ns <= start_ns {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "end_ns must be after start_ns, got {start_ns}..{end_ns}"
+            )));
+        }
+        let cum_profile = match (mode, volume_profile) {
+            (ScheduleMode::Twap, None) => Vec::new(),
+            (ScheduleMode::Twap, Some(_)) => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "volume_profile only applies to mode='vwap'",
+                ))
+            }
+            (ScheduleMode::Vwap, None) => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "mode='vwap' requires a volume_profile",
+                ))
+            }
+            (ScheduleMode::Vwap, Some(profile)) => cumulative_profile(&profile)?,
+        };
+        Ok(Self {
+            target_qty,
+            start_ns,
+            end_ns,
+            mode,
+            cum_profile,
+        })
+    }
+
+    /// Fraction of the target that should be done by `now_ns`, in [0, 1].
+    pub fn scheduled_fraction(&self, now_ns: i64) -> f64 {
+        if now_ns <= self.start_ns {
+            return 0.0;
+        }
+        if now_ns >= self.end_ns {
+            return 1.0;
+        }
+        let t = (now_ns - self.start_ns) as f64 / (self.end_ns - self.start_ns) as f64;
+        match self.mode {
+            ScheduleMode::Twap => t,
+            ScheduleMode::Vwap => {
+                let n = self.cum_profile.len();
+                let pos = t * n as f64;
+                let bucket = (pos as usize).min(n - 1);
+                let before = if bucket == 0 {
+                    0.0
+                } else {
+                    self.cum_profile[bucket - 1]
+                };
+                let within = pos - bucket as f64;
+                before + (self.cum_profile[bucket] - before) * within
+            }
+        }
+    }
+
+    /// Quantity that should be filled by `now_ns`.
+    pub fn scheduled_qty(&self, now_ns: i64) -> f64 {
+        self.target_qty * self.scheduled_fraction(now_ns)
+    }
+
+    /// Quantity to work now: the shortfall against the schedule, clamped to
+    /// [0, target_qty - filled_so_far]. 0 when ahead of schedule.
+    pub fn next_slice(&self, now_ns: i64, filled_so_far: f64) -> f64 {
+        let remaining = (self.target_qty - filled_so_far).max(0.0);
+        (self.scheduled_qty(now_ns) - filled_so_far).clamp(0.0, remaining)
+    }
+
+    #[getter]
+    pub fn get_target_qty(&self) -> f64 {
+        self.target_qty
+    }
+
+    #[getter]
+    pub fn get_mode(&self) -> &'static str {
+        self.mode.name()
+    }
+
+    #[getter]
+    pub fn get_window(&self) -> (i64, i64) {
+        (self.start_ns, self.end_ns)
+    }
+}
+
+/// Normalized running sum of `profile`; rejects negative, non-finite or
+/// all-zero weights.
+fn cumulative_profile(profile: &[f64]) -> PyResult<Vec<f64>> {
+    if profile.iter().any(|w| !(w.is_finite() && *w >= 0.0)) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "volume_profile weights must be finite and non-negative",
+        ));
+    }
+    let total: f64 = profile.iter().sum();
+    if total <= 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "volume_profile must have a positive total",
+        ));
+    }
+    let mut acc = 0.0;
+    let mut cum: Vec<f64> = profile
+        .iter()
+        .map(|w| {
+            acc += w;
+            acc / total
+        })
+        .collect();
+    // Pin the end so the schedule reaches the full target exactly
+    if let Some(last) = cum.last_mut() {
+        *last = 1.0;
+    }
+    Ok(cum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_twap_schedule_and_catch_up() {
+        let s = ExecutionScheduler::new(100.0, 1_000, 2_000, "twap", None).unwrap();
+        assert_eq!(s.next_slice(500, 0.0), 0.0); // before start
+        assert_eq!(s.scheduled_qty(1_250), 25.0);
+        assert_eq!(s.next_slice(1_250, 20.0), 5.0);
+        // Ahead of schedule: nothing to do
+        assert_eq!(s.next_slice(1_250, 40.0), 0.0);
+        // Behind: the whole shortfall at once
+        assert_eq!(s.next_slice(1_750, 10.0), 65.0);
+        // Past the end: everything left, never more
+        assert_eq!(s.next_slice(5_000, 90.0), 10.0);
+        assert_eq!(s.next_slice(5_000, 120.0), 0.0);
+    }
+
+    #[test]
+    fn test_vwap_follows_profile() {
+        // Two buckets: 75% of volume in the first half
+        let s = ExecutionScheduler::new(100.0, 0, 1_000, "vwap", Some(vec![3.0, 1.0])).unwrap();
+        assert_eq!(s.scheduled_qty(250), 37.5);
+        assert_eq!(s.scheduled_qty(500), 75.0);
+        assert_eq!(s.scheduled_qty(750), 87.5);
+        assert!(s.scheduled_qty(999) <= 100.0);
+        assert_eq!(s.scheduled_qty(1_000), 100.0);
+        assert_eq!(s.next_slice(500, 70.0), 5.0);
+    }
+
+    #[test]
+    fn test_invalid_configs() {
+        assert!(ExecutionScheduler::new(0.0, 0, 10, "twap", None).is_err());
+        assert!(ExecutionScheduler::new(10.0, 10, 10, "twap", None).is_err());
+        assert!(ExecutionScheduler::new(10.0, 0, 10, "pov", None).is_err());
+        assert!(ExecutionScheduler::new(10.0, 0, 10, "vwap", None).is_err());
+        assert!(ExecutionScheduler::new(10.0, 0, 10, "vwap", Some(vec![])).is_err());
+        assert!(ExecutionScheduler::new(10.0, 0, 10, "vwap", Some(vec![1.0, -1.0])).is_err());
+        assert!(ExecutionScheduler::new(10.0, 0, 10, "twap", Some(vec![1.0])).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+use ndarray::ArrayView2;
+use numpy::{PyArray2, PyArrayMethods, PyReadonlyArray2};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyTuple};
+
+/// Layout version written as the first byte of every record.
+const CODEC_VERSION: u8 = 1;
+/// Flag bits in the second byte.
+const FLAG_HAS_EXTRA: u8 = 0b01;
+const FLAG_EXTRA_SET: u8 = 0b10;
+
+/// Decoded bidask tuple, minus the constant "bidask" tag.
+#[derive(Debug, Clone, PartialEq)]
+struct BidAskRecord {
+    symbol: String,
+    exch_ts: i64,
+    flag: bool,
+    bids: Vec<[i64; 2]>,
+    asks: Vec<[i64; 2]>,
+    depth_stats: [i64; 4],
+    l1_stats: [f64; 3],
+    /// Optional 14th element (`crossed` / `synthesized`).
+    extra: Option<bool>,
+}
+
+impl BidAskRecord {
+    /// Little-endian layout:
+    /// `u8 version, u8 flags, u16 symbol_len, symbol, i64 exch_ts, u8 flag,
+    ///  u32 n_bids, n_bids * (i64 price, i64 vol), u32 n_asks, ...,
+    ///  4 * i64 (best_bid, best_ask, bid_depth, ask_depth),
+    ///  3 * f64 (mid, spread, imbalance)`.
+    fn encode(&self) -> Result<Vec<u8>, String> {
+        let symbol_len = u16::try_from(self.symbol.len())
+            .map_err(|_| format!("symbol too long: {} bytes", self.symbol.len()))?;
+        let mut out = Vec::with_capacity(
+            4 + self.symbol.len() + 9 + 8 + 16 * (self.bids.len() + self.asks.len()) + 56,
+        );
+        let mut flags = 0;
+        if let Some(extra) = self.extra {
+            flags |= FLAG_HAS_EXTRA;
+            if extra {
+                flags |= FLAG_EXTRA_SET;
+            }
+        }
+        out.push(CODEC_VERSION);
+        out.push(flags);
+        out.extend_from_slice(&symbol_len.to_le_bytes());
+        out.extend_from_slice(self.symbol.as_bytes());
+        out.extend_from_slice(&self.exch_ts.to_le_bytes());
+        out.push(self.flag as u8);
+        for side in [&self.bids, &self.asks] {
+            let n = u32::try_from(side.len()).map_err(|_| "too many levels".to_string())?;
+            out.extend_from_slice(&n.to_le_bytes());
+            for [price, vol] in side.iter() {
+                out.extend_from_slice(&price.to_le_bytes());
+                out.extend_from_slice(&vol.to_le_bytes());
+            }
+        }
+        for v in self.depth_stats {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        for v in self.l1_stats {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Ok(out)
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self, String> {
+        let mut r = Reader { buf, pos: 0 };
+        let version = r.take::<1>()?[0];
+        if version != CODEC_VERSION {
+            return Err(format!("unsupported bidask codec version {version}"));
+        }
+        let flags = r.take::<1>()?[0];
+        let symbol_len = u16::from_le_bytes(r.take()?) as usize;
+        let symbol = std::str::from_utf8(r.slice(symbol_len)?)
+            .map_err(|e| format!("symbol is not utf-8: {e}"))?
+            .to_string();
+        let exch_ts = i64::from_le_bytes(r.take()?);
+        let flag = r.take::<1>()?[0] != 0;
+        let bids = r.levels()?;
+        let asks = r.levels()?;
+        let mut depth_stats = [0i64; 4];
+        for v in depth_stats.iter_mut() {
+            *v = i64::from_le_bytes(r.take()?);
+        }
+        let mut l1_stats = [0f64; 3];
+        for v in l1_stats.iter_mut() {
+            *v = f64::from_le_bytes(r.take()?);
+        }
+        if r.pos != buf.len() {
+            return Err(format!("{} trailing bytes", buf.len() - r.pos));
+        }
+        let extra = (flags & FLAG_HAS_EXTRA != 0).then_some(flags & FLAG_EXTRA_SET != 0);
+        Ok(Self {
+            symbol,
+            exch_ts,
+            flag,
+            bids,
+            asks,
+            depth_stats,
+            l1_stats,
+            extra,
+        })
+    }
+}
+
+/// Bounds-checked cursor over an encoded record.
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn slice(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.buf.len())
+            .ok_or_else(|| format!("truncated bidask record at byte {}", self.pos))?;
+        let out = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.slice(N)?);
+        Ok(out)
+    }
+
+    fn levels(&mut self) -> Result<Vec<[i64; 2]>, String> {
+        let n = u32::from_le_bytes(self.take()?) as usize;
+        // Reject counts the remaining bytes cannot hold before allocating
+        if n > (self.buf.len() - self.pos) / 16 {
+            return Err(format!("truncated bidask record: {n} levels declared"));
+        }
+        (0..n)
+            .map(|_| {
+                Ok([
+                    i64::from_le_bytes(self.take()?),
+                    i64::from_le_bytes(self.take()?),
+                ])
+            })
+            .collect()
+    }
+}
+
+fn side_rows(view: ArrayView2<i64>) -> PyResult<Vec<[i64; 2]>> {
+    if view.ncols() != 2 {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "book side must have shape (n, 2), got {:?}",
+            view.shape()
+        )));
+    }
+    Ok(view.rows().into_iter().map(|r| [r[0], r[1]]).collect())
+}
+
+fn side_array(py: Python<'_>, rows: &[[i64; 2]]) -> PyObject {
+    let arr = PyArray2::<i64>::zeros_bound(py, [rows.len(), 2], false);
+    {
+        let mut view = unsafe { arr.as_array_mut() };
+        for (i, [price, vol]) in rows.iter().enumerate() {
+            view[(i, 0)] = *price;
+            view[(i, 1)] = *vol;
+        }
+    }
+    arr.into_py(py)
+}
+
+/// Pack a `normalize_bidask_tuple*` result (13 or 14 elements) into compact
+/// little-endian bytes, e.g. for `ShmRingBuffer`. Inverse of `decode_bidask`.
+#[pyfunction]
+pub fn encode_bidask<'py>(
+    py: Python<'py>,
+    tuple: &Bound<'py, PyTuple>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let n = tuple.len();
+    if n != 13 && n != 14 {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "expected a 13 or 14 element bidask tuple, got {n}"
+        )));
+    }
+    let bids: PyReadonlyArray2<i64> = tuple.get_item(2)?.extract()?;
+    let asks: PyReadonlyArray2<i64> = tuple.get_item(3)?.extract()?;
+    let record = BidAskRecord {
+        symbol: tuple.get_item(1)?.extract()?,
+        bids: side_rows(bids.as_array())?,
+        asks: side_rows(asks.as_array())?,
+        exch_ts: tuple.get_item(4)?.extract()?,
+        flag: tuple.get_item(5)?.extract()?,
+        depth_stats: [
+            tuple.get_item(6)?.extract()?,
+            tuple.get_item(7)?.extract()?,
+            tuple.get_item(8)?.extract()?,
+            tuple.get_item(9)?.extract()?,
+        ],
+        l1_stats: [
+            tuple.get_item(10)?.extract()?,
+            tuple.get_item(11)?.extract()?,
+            tuple.get_item(12)?.extract()?,
+        ],
+        extra: if n == 14 {
+            Some(tuple.get_item(13)?.extract()?)
+        } else {
+            None
+        },
+    };
+    let bytes = record
+        .encode()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(PyBytes::new_bound(py, &bytes))
+}
+
+/// Rebuild the tuple packed by `encode_bidask`, with `[n, 2]` int64 arrays.
+#[pyfunction]
+pub fn decode_bidask(py: Python<'_>, data: &[u8]) -> PyResult<PyObject> {
+    let rec = BidAskRecord::decode(data).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let mut items = vec![
+        "bidask".into_py(py),
+        rec.symbol.into_py(py),
+        side_array(py, &rec.bids),
+        side_array(py, &rec.asks),
+        rec.exch_ts.into_py(py),
+        rec.flag.into_py(py),
+    ];
+    items.extend(rec.depth_stats.iter().map(|v| v.into_py(py)));
+    items.extend(rec.l1_stats.iter().map(|v| v.into_py(py)));
+    if let Some(extra) = rec.extra {
+        items.push(extra.into_py(py));
+    }
+    Ok(PyTuple::new_bound(py, items).into_py(py))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(extra: Option<bool>) -> BidAskRecord {
+        BidAskRecord {
+            symbol: "2330".to_string(),
+            exch_ts: 1_700_000_000_123_456_789,
+            flag: false,
+            bids: vec![[5_950_000, 12], [5_940_000, 3]],
+            asks: vec![[5_960_000, 7]],
+            depth_stats: [5_950_000, 5_960_000, 15, 7],
+            l1_stats: [5_955_000.0, 10_000.0, 0.263_157_894_736_842_1],
+            extra,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for extra in [None, Some(false), Some(true)] {
+            let rec = sample(extra);
+            let bytes = rec.encode().unwrap();
+            assert_eq!(BidAskRecord::decode(&bytes).unwrap(), rec);
+        }
+        let mut empty = sample(None);
+        empty.bids.clear();
+        empty.asks.clear();
+        let bytes = empty.encode().unwrap();
+        assert_eq!(BidAskRecord::decode(&bytes).unwrap(), empty);
+    }
+
+    #[test]
+    fn test_rejects_corrupt_input() {
+        let bytes = sample(Some(true)).encode().unwrap();
+        for cut in [0, 1, 5, bytes.len() - 1] {
+            assert!(BidAskRecord::decode(&bytes[..cut]).is_err());
+        }
+        let mut bad_version = bytes.clone();
+        bad_version[0] = 99;
+        assert!(BidAskRecord::decode(&bad_version).is_err());
+        let mut trailing = bytes;
+        trailing.push(0);
+        assert!(BidAskRecord::decode(&trailing).is_err());
+    }
+}
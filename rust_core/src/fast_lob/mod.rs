@@ -1,10 +1,14 @@
+mod codec;
 mod normalize_bidask;
 mod normalize_tick;
 mod scale;
 mod stats;
+mod tick;
 
 // Re-export all public items transparently
+pub use codec::*;
 pub use normalize_bidask::*;
 pub use normalize_tick::*;
 pub use scale::*;
 pub use stats::*;
+pub use tick::*;
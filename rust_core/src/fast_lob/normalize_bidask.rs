@@ -5,7 +5,9 @@ use pyo3::prelude::*;
 use pyo3::types::PyList;
 use pyo3::types::PyTuple;
 
-use super::scale::{compute_l1_stats, scale_book_pair_stats, scale_side_with_stats};
+use super::scale::{
+    compute_l1_stats, scale_book_pair_stats_inner, scale_side_with_stats, PriceRounding,
+};
 use super::stats::{extract_ts, get_optional};
 
 #[pyfunction]
@@ -31,13 +33,14 @@ pub fn normalize_bidask_tuple(
     let ask_vols_obj = get_optional(payload, &["ask_volume", "askVolume"])
         .unwrap_or_else(|| PyList::empty_bound(py).into_py(py));
 
-    let (bids, asks, stats) = scale_book_pair_stats(
+    let (bids, asks, stats) = scale_book_pair_stats_inner(
         py,
         bid_prices_obj.bind(py),
         bid_vols_obj.bind(py),
         ask_prices_obj.bind(py),
         ask_vols_obj.bind(py),
         scale,
+        PriceRounding::Bankers,
     )?;
 
     let result = PyTuple::new_bound(
@@ -62,7 +65,13 @@ pub fn normalize_bidask_tuple(
     Ok(result.into_py(py))
 }
 
+/// Numpy-input bidask normalization returning a 13-element tuple.
+///
+/// With `flag_crossed=true` a 14th `crossed: bool` element is appended; it is
+/// set when both sides are present and `best_ask <= best_bid` (crossed or
+/// locked), and mid/spread/imbalance are zeroed for such ticks.
 #[pyfunction]
+#[pyo3(signature = (symbol, exch_ts, bid_prices, bid_vols, ask_prices, ask_vols, scale, flag_crossed=false))]
 pub fn normalize_bidask_tuple_np(
     py: Python<'_>,
     symbol: &str,
@@ -72,39 +81,50 @@ pub fn normalize_bidask_tuple_np(
     ask_prices: PyReadonlyArray1<f64>,
     ask_vols: PyReadonlyArray1<i64>,
     scale: i64,
+    flag_crossed: bool,
 ) -> PyResult<PyObject> {
     if symbol.is_empty() {
         return Ok(py.None());
     }
 
     let (bids, best_bid, bid_top_vol, bid_depth_total) =
-        scale_side_with_stats(py, bid_prices, bid_vols, scale)?;
+        scale_side_with_stats(py, bid_prices, bid_vols, scale, PriceRounding::Bankers)?;
     let (asks, best_ask, ask_top_vol, ask_depth_total) =
-        scale_side_with_stats(py, ask_prices, ask_vols, scale)?;
+        scale_side_with_stats(py, ask_prices, ask_vols, scale, PriceRounding::Bankers)?;
 
-    let (mid_price, spread, imbalance) =
-        compute_l1_stats(best_bid, best_ask, bid_top_vol, ask_top_vol);
+    let crossed = is_crossed(best_bid, best_ask);
+    let (mid_price, spread, imbalance) = if flag_crossed && crossed {
+        (0.0, 0.0, 0.0)
+    } else {
+        compute_l1_stats(best_bid, best_ask, bid_top_vol, ask_top_vol)
+    };
 
-    let result = PyTuple::new_bound(
-        py,
-        [
-            "bidask".into_py(py),
-            symbol.into_py(py),
-            bids.into_py(py),
-            asks.into_py(py),
-            exch_ts.into_py(py),
-            false.into_py(py),
-            best_bid.into_py(py),
-            best_ask.into_py(py),
-            bid_depth_total.into_py(py),
-            ask_depth_total.into_py(py),
-            mid_price.into_py(py),
-            spread.into_py(py),
-            imbalance.into_py(py),
-        ],
-    );
+    let mut items = vec![
+        "bidask".into_py(py),
+        symbol.into_py(py),
+        bids.into_py(py),
+        asks.into_py(py),
+        exch_ts.into_py(py),
+        false.into_py(py),
+        best_bid.into_py(py),
+        best_ask.into_py(py),
+        bid_depth_total.into_py(py),
+        ask_depth_total.into_py(py),
+        mid_price.into_py(py),
+        spread.into_py(py),
+        imbalance.into_py(py),
+    ];
+    if flag_crossed {
+        items.push(crossed.into_py(py));
+    }
 
-    Ok(result.into_py(py))
+    Ok(PyTuple::new_bound(py, items).into_py(py))
+}
+
+/// Both sides present and the ask at or through the bid.
+#[inline(always)]
+pub(super) fn is_crossed(best_bid: i64, best_ask: i64) -> bool {
+    best_bid > 0 && best_ask > 0 && best_ask <= best_bid
 }
 
 /// Like `normalize_bidask_tuple_np` but with built-in synthetic side synthesis.
@@ -132,9 +152,9 @@ pub fn normalize_bidask_tuple_with_synth(
     }
 
     let (bids, best_bid, bid_top_vol, bid_depth_total) =
-        scale_side_with_stats(py, bid_prices, bid_vols, scale)?;
+        scale_side_with_stats(py, bid_prices, bid_vols, scale, PriceRounding::Bankers)?;
     let (asks, best_ask, ask_top_vol, ask_depth_total) =
-        scale_side_with_stats(py, ask_prices, ask_vols, scale)?;
+        scale_side_with_stats(py, ask_prices, ask_vols, scale, PriceRounding::Bankers)?;
 
     let has_bids = bid_depth_total > 0;
     let has_asks = ask_depth_total > 0;
@@ -195,3 +215,18 @@ pub fn normalize_bidask_tuple_with_synth(
 
     Ok(result.into_py(py))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_crossed() {
+        assert!(!is_crossed(1000, 1010));
+        assert!(is_crossed(1010, 1010)); // locked
+        assert!(is_crossed(1020, 1010));
+        // One-sided books are not crossed
+        assert!(!is_crossed(0, 1010));
+        assert!(!is_crossed(1010, 0));
+    }
+}
@@ -1,5 +1,7 @@
 use pyo3::prelude::*;
+use pyo3::types::{PyFloat, PyString};
 
+use super::scale::PriceRounding;
 use super::stats::{extract_ts, get_optional};
 
 #[pyfunction]
@@ -23,26 +25,19 @@ pub fn normalize_tick_tuple(
     let oddlot_obj = get_optional(payload, &["intraday_odd", "odd_lot"]);
 
     let price = if let Some(obj) = close_obj {
-        let obj = obj.bind(py);
-        if let Ok(p) = obj.extract::<f64>() {
-            (p * scale as f64) as i64
-        } else if let Ok(p) = obj.extract::<i64>() {
-            p * scale
-        } else {
-            0
-        }
+        scale_price_obj(obj.bind(py), scale)
     } else {
         0
     };
 
     let volume = if let Some(obj) = volume_obj {
-        obj.bind(py).extract::<i64>().unwrap_or(0)
+        lenient_i64(obj.bind(py)).unwrap_or(0)
     } else {
         0
     };
 
     let total_volume = if let Some(obj) = total_volume_obj {
-        obj.bind(py).extract::<i64>().unwrap_or(0)
+        lenient_i64(obj.bind(py)).unwrap_or(0)
     } else {
         0
     };
@@ -89,3 +84,88 @@ pub fn normalize_tick_tuple(
     );
     Ok(result.into_py(py))
 }
+
+/// Scaled close price. Floats keep the historical truncation; strings and
+/// `__float__` objects (e.g. `Decimal`) are rounded half to even, so a
+/// decimal string such as "0.29" is not truncated to one tick below.
+fn scale_price_obj(obj: &Bound<'_, PyAny>, scale: i64) -> i64 {
+    if obj.is_instance_of::<PyFloat>() {
+        obj.extract::<f64>()
+            .map_or(0, |p| (p * scale as f64) as i64)
+    } else if let Ok(p) = obj.extract::<i64>() {
+        p.saturating_mul(scale)
+    } else if let Some(p) = lenient_f64(obj) {
+        PriceRounding::Bankers.scale(p, scale)
+    } else {
+        0
+    }
+}
+
+/// Numeric value of a string ("123.45") or `__float__` object (e.g. `Decimal`).
+fn lenient_f64(obj: &Bound<'_, PyAny>) -> Option<f64> {
+    if let Ok(s) = obj.downcast::<PyString>() {
+        return parse_num_str(s.to_str().ok()?);
+    }
+    if obj.hasattr("__float__").ok()? {
+        let v: f64 = obj.call_method0("__float__").ok()?.extract().ok()?;
+        return v.is_finite().then_some(v);
+    }
+    None
+}
+
+/// Integer volume from an int, or a whole-valued string / `__float__` object.
+fn lenient_i64(obj: &Bound<'_, PyAny>) -> Option<i64> {
+    if let Ok(v) = obj.extract::<i64>() {
+        return Some(v);
+    }
+    if let Ok(s) = obj.downcast::<PyString>() {
+        let s = s.to_str().ok()?;
+        if let Ok(v) = s.trim().parse::<i64>() {
+            return Some(v);
+        }
+    }
+    let v = obj.extract::<f64>().ok().or_else(|| lenient_f64(obj))?;
+    (v.fract() == 0.0).then_some(v as i64)
+}
+
+/// Parse a trimmed decimal string; rejects NaN/inf.
+fn parse_num_str(s: &str) -> Option<f64> {
+    let v = s.trim().parse::<f64>().ok()?;
+    v.is_finite().then_some(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_num_str() {
+        assert_eq!(parse_num_str("123.45"), Some(123.45));
+        assert_eq!(parse_num_str(" 100 "), Some(100.0));
+        assert_eq!(parse_num_str("1e2"), Some(100.0));
+        assert_eq!(parse_num_str(""), None);
+        assert_eq!(parse_num_str("abc"), None);
+        assert_eq!(parse_num_str("NaN"), None);
+        assert_eq!(parse_num_str("inf"), None);
+    }
+
+    #[test]
+    fn test_string_and_decimal_prices_round() {
+        Python::with_gil(|py| {
+            // 0.29 * 100 is 28.999999999999996 in f64
+            let s = PyString::new_bound(py, "0.29");
+            assert_eq!(scale_price_obj(s.as_any(), 100), 29);
+            let decimal = py
+                .import_bound("decimal")
+                .unwrap()
+                .getattr("Decimal")
+                .unwrap()
+                .call1(("0.29",))
+                .unwrap();
+            assert_eq!(scale_price_obj(&decimal, 100), 29);
+            assert_eq!(scale_price_obj(&7i64.into_py(py).into_bound(py), 100), 700);
+            let junk = PyString::new_bound(py, "n/a");
+            assert_eq!(scale_price_obj(junk.as_any(), 100), 0);
+        });
+    }
+}
@@ -1,8 +1,44 @@
 #![allow(clippy::too_many_arguments)]
 
+use numpy::ndarray::ArrayView1;
 use numpy::{PyArray2, PyArrayMethods, PyReadonlyArray1};
 use pyo3::prelude::*;
 
+/// How `price * scale` is converted to integer ticks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum PriceRounding {
+    /// Toward zero (`as i64`).
+    Trunc,
+    /// Half away from zero.
+    Nearest,
+    /// Half to even, matching Python `round()` (the default).
+    Bankers,
+}
+
+impl PriceRounding {
+    /// Parse the Python-facing mode name: "trunc", "nearest" or "bankers".
+    pub(super) fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "trunc" => Ok(Self::Trunc),
+            "nearest" => Ok(Self::Nearest),
+            "bankers" => Ok(Self::Bankers),
+            other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown rounding '{other}' (expected 'trunc', 'nearest' or 'bankers')"
+            ))),
+        }
+    }
+
+    #[inline(always)]
+    pub(super) fn scale(self, p: f64, scale: i64) -> i64 {
+        let x = p * scale as f64;
+        match self {
+            Self::Trunc => x as i64,
+            Self::Nearest => x.round() as i64,
+            Self::Bankers => x.round_ties_even() as i64,
+        }
+    }
+}
+
 /// Pure-Rust L1 stats computation (no Python dependency).
 pub(super) fn compute_l1_stats(
     best_bid: i64,
@@ -27,12 +63,15 @@ pub(super) fn compute_l1_stats(
 }
 
 #[pyfunction]
+#[pyo3(signature = (prices, vols, scale, rounding="bankers"))]
 pub fn scale_book(
     py: Python<'_>,
     prices: PyReadonlyArray1<f64>,
     vols: PyReadonlyArray1<i64>,
     scale: i64,
+    rounding: &str,
 ) -> PyResult<Py<PyArray2<i64>>> {
+    let rounding = PriceRounding::parse(rounding)?;
     let prices = prices.as_array();
     let vols = vols.as_array();
 
@@ -54,7 +93,7 @@ pub fn scale_book(
     let mut idx = 0usize;
     for (&p, &v) in prices.iter().zip(vols.iter()) {
         if p > 0.0 {
-            out_view[(idx, 0)] = (p * scale as f64).round_ties_even() as i64;
+            out_view[(idx, 0)] = rounding.scale(p, scale);
             out_view[(idx, 1)] = v;
             idx += 1;
         }
@@ -63,17 +102,78 @@ pub fn scale_book(
     Ok(out.into())
 }
 
+/// Like `scale_book`, for prices already carried as integer ticks.
+///
+/// Prices are copied through unchanged (no `f64` round-trip) and optionally
+/// multiplied by an integer `passthrough_scale`; non-positive prices are
+/// dropped as in `scale_book`.
+#[pyfunction]
+#[pyo3(signature = (prices, vols, passthrough_scale=1))]
+pub fn scale_book_int(
+    py: Python<'_>,
+    prices: PyReadonlyArray1<i64>,
+    vols: PyReadonlyArray1<i64>,
+    passthrough_scale: i64,
+) -> PyResult<Py<PyArray2<i64>>> {
+    let flat = scale_int_flat(prices.as_array(), vols.as_array(), passthrough_scale)?;
+
+    let rows = flat.len() / 2;
+    let out = PyArray2::<i64>::zeros_bound(py, [rows, 2], false);
+    let mut out_view = unsafe { out.as_array_mut() };
+    for i in 0..rows {
+        out_view[(i, 0)] = flat[i * 2];
+        out_view[(i, 1)] = flat[i * 2 + 1];
+    }
+
+    Ok(out.into())
+}
+
+/// Flattened `[price, vol]` rows for `scale_book_int`.
+pub(super) fn scale_int_flat(
+    prices: ArrayView1<i64>,
+    vols: ArrayView1<i64>,
+    factor: i64,
+) -> PyResult<Vec<i64>> {
+    if prices.len() != vols.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "prices/vols length mismatch",
+        ));
+    }
+    if factor < 1 {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "passthrough_scale must be >= 1, got {factor}"
+        )));
+    }
+
+    let mut flat: Vec<i64> = Vec::with_capacity(prices.len() * 2);
+    for (&p, &v) in prices.iter().zip(vols.iter()) {
+        if p > 0 {
+            let scaled = p.checked_mul(factor).ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "price {p} * passthrough_scale {factor} overflows i64"
+                ))
+            })?;
+            flat.push(scaled);
+            flat.push(v);
+        }
+    }
+    Ok(flat)
+}
+
 #[pyfunction]
+#[pyo3(signature = (prices, vols, scale, rounding="bankers"))]
 pub fn scale_book_seq(
     py: Python<'_>,
     prices: &Bound<'_, PyAny>,
     vols: &Bound<'_, PyAny>,
     scale: i64,
+    rounding: &str,
 ) -> PyResult<Py<PyArray2<i64>>> {
-    scale_book_seq_inner(py, prices, vols, scale)
+    scale_book_seq_inner(py, prices, vols, scale, PriceRounding::parse(rounding)?)
 }
 
 #[pyfunction]
+#[pyo3(signature = (bid_prices, bid_vols, ask_prices, ask_vols, scale, rounding="bankers"))]
 #[allow(clippy::type_complexity)]
 pub fn scale_book_pair(
     py: Python<'_>,
@@ -82,13 +182,16 @@ pub fn scale_book_pair(
     ask_prices: &Bound<'_, PyAny>,
     ask_vols: &Bound<'_, PyAny>,
     scale: i64,
+    rounding: &str,
 ) -> PyResult<(Py<PyArray2<i64>>, Py<PyArray2<i64>>)> {
-    let bids = scale_book_seq_inner(py, bid_prices, bid_vols, scale)?;
-    let asks = scale_book_seq_inner(py, ask_prices, ask_vols, scale)?;
+    let rounding = PriceRounding::parse(rounding)?;
+    let bids = scale_book_seq_inner(py, bid_prices, bid_vols, scale, rounding)?;
+    let asks = scale_book_seq_inner(py, ask_prices, ask_vols, scale, rounding)?;
     Ok((bids, asks))
 }
 
 #[pyfunction]
+#[pyo3(signature = (bid_prices, bid_vols, ask_prices, ask_vols, scale, rounding="bankers"))]
 #[allow(clippy::type_complexity)]
 pub fn scale_book_pair_stats(
     py: Python<'_>,
@@ -97,13 +200,39 @@ pub fn scale_book_pair_stats(
     ask_prices: &Bound<'_, PyAny>,
     ask_vols: &Bound<'_, PyAny>,
     scale: i64,
+    rounding: &str,
+) -> PyResult<(
+    Py<PyArray2<i64>>,
+    Py<PyArray2<i64>>,
+    (i64, i64, i64, i64, f64, f64, f64),
+)> {
+    scale_book_pair_stats_inner(
+        py,
+        bid_prices,
+        bid_vols,
+        ask_prices,
+        ask_vols,
+        scale,
+        PriceRounding::parse(rounding)?,
+    )
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn scale_book_pair_stats_inner(
+    py: Python<'_>,
+    bid_prices: &Bound<'_, PyAny>,
+    bid_vols: &Bound<'_, PyAny>,
+    ask_prices: &Bound<'_, PyAny>,
+    ask_vols: &Bound<'_, PyAny>,
+    scale: i64,
+    rounding: PriceRounding,
 ) -> PyResult<(
     Py<PyArray2<i64>>,
     Py<PyArray2<i64>>,
     (i64, i64, i64, i64, f64, f64, f64),
 )> {
-    let bids = scale_book_seq_inner(py, bid_prices, bid_vols, scale)?;
-    let asks = scale_book_seq_inner(py, ask_prices, ask_vols, scale)?;
+    let bids = scale_book_seq_inner(py, bid_prices, bid_vols, scale, rounding)?;
+    let asks = scale_book_seq_inner(py, ask_prices, ask_vols, scale, rounding)?;
 
     let bids_view = bids.bind(py).readonly();
     let asks_view = asks.bind(py).readonly();
@@ -113,6 +242,7 @@ pub fn scale_book_pair_stats(
 }
 
 #[pyfunction]
+#[pyo3(signature = (bid_prices, bid_vols, ask_prices, ask_vols, scale, rounding="bankers"))]
 #[allow(clippy::type_complexity)]
 pub fn scale_book_pair_stats_np(
     py: Python<'_>,
@@ -121,15 +251,17 @@ pub fn scale_book_pair_stats_np(
     ask_prices: PyReadonlyArray1<f64>,
     ask_vols: PyReadonlyArray1<i64>,
     scale: i64,
+    rounding: &str,
 ) -> PyResult<(
     Py<PyArray2<i64>>,
     Py<PyArray2<i64>>,
     (i64, i64, i64, i64, f64, f64, f64),
 )> {
+    let rounding = PriceRounding::parse(rounding)?;
     let (bids, best_bid, bid_top_vol, bid_depth_total) =
-        scale_side_with_stats(py, bid_prices, bid_vols, scale)?;
+        scale_side_with_stats(py, bid_prices, bid_vols, scale, rounding)?;
     let (asks, best_ask, ask_top_vol, ask_depth_total) =
-        scale_side_with_stats(py, ask_prices, ask_vols, scale)?;
+        scale_side_with_stats(py, ask_prices, ask_vols, scale, rounding)?;
 
     let (mid_price, spread, imbalance) =
         compute_l1_stats(best_bid, best_ask, bid_top_vol, ask_top_vol);
@@ -154,6 +286,7 @@ pub(super) fn scale_side_with_stats(
     prices: PyReadonlyArray1<f64>,
     vols: PyReadonlyArray1<i64>,
     scale: i64,
+    rounding: PriceRounding,
 ) -> PyResult<(Py<PyArray2<i64>>, i64, i64, i64)> {
     let prices = prices.as_array();
     let vols = vols.as_array();
@@ -181,7 +314,7 @@ pub(super) fn scale_side_with_stats(
 
     for (&p, &v) in prices.iter().zip(vols.iter()) {
         if p > 0.0 {
-            let scaled = (p * scale as f64).round_ties_even() as i64;
+            let scaled = rounding.scale(p, scale);
             out_view[(idx, 0)] = scaled;
             out_view[(idx, 1)] = v;
             if idx == 0 {
@@ -201,6 +334,7 @@ pub(super) fn scale_book_seq_inner(
     prices: &Bound<'_, PyAny>,
     vols: &Bound<'_, PyAny>,
     scale: i64,
+    rounding: PriceRounding,
 ) -> PyResult<Py<PyArray2<i64>>> {
     let mut price_iter = prices.iter()?;
     let mut vol_iter = vols.iter()?;
@@ -215,7 +349,7 @@ pub(super) fn scale_book_seq_inner(
                 let p: f64 = p_obj.extract()?;
                 let v: i64 = v_obj.extract()?;
                 if p > 0.0 {
-                    flat.push((p * scale as f64).round_ties_even() as i64);
+                    flat.push(rounding.scale(p, scale));
                     flat.push(v);
                 }
             }
@@ -289,4 +423,44 @@ mod tests {
         // imbalance = (20 - 80) / 100 = -0.6
         assert!((imbalance - (-0.6)).abs() < 1e-12);
     }
+
+    #[test]
+    fn test_price_rounding_modes() {
+        let trunc = PriceRounding::parse("trunc").unwrap();
+        let nearest = PriceRounding::parse("Nearest").unwrap();
+        let bankers = PriceRounding::parse("bankers").unwrap();
+        // 100.25 * 10 = 1002.5 exactly: the three modes disagree on the tie
+        assert_eq!(trunc.scale(100.25, 10), 1002);
+        assert_eq!(nearest.scale(100.25, 10), 1003);
+        assert_eq!(bankers.scale(100.25, 10), 1002);
+        assert_eq!(bankers.scale(100.75, 10), 1008);
+        // Non-tie above .5 rounds up except under trunc
+        assert_eq!(trunc.scale(1.07, 10), 10);
+        assert_eq!(nearest.scale(1.07, 10), 11);
+        assert!(PriceRounding::parse("floor").is_err());
+    }
+
+    #[test]
+    fn test_scale_int_passthrough_is_exact() {
+        use numpy::ndarray::array;
+        // Beyond f64's 53-bit mantissa: a float round-trip would lose the low bit
+        let big = (1i64 << 53) + 1;
+        let prices = array![big, 0, 1_000_500];
+        let vols = array![3, 9, 7];
+        let flat = scale_int_flat(prices.view(), vols.view(), 1).unwrap();
+        assert_eq!(flat, vec![big, 3, 1_000_500, 7]);
+
+        let flat = scale_int_flat(array![1_000_500].view(), array![7].view(), 10).unwrap();
+        assert_eq!(flat, vec![10_005_000, 7]);
+    }
+
+    #[test]
+    fn test_scale_int_rejects_bad_input() {
+        use numpy::ndarray::array;
+        let prices = array![i64::MAX / 2];
+        let vols = array![1];
+        assert!(scale_int_flat(prices.view(), vols.view(), 3).is_err());
+        assert!(scale_int_flat(prices.view(), vols.view(), 0).is_err());
+        assert!(scale_int_flat(prices.view(), array![1, 2].view(), 1).is_err());
+    }
 }
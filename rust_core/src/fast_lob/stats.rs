@@ -1,6 +1,7 @@
+use numpy::ndarray::ArrayView2;
 use numpy::PyReadonlyArray2;
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyDict, PyString};
 
 #[pyfunction]
 pub fn compute_book_stats(
@@ -58,35 +59,117 @@ pub fn compute_book_stats(
     ))
 }
 
+/// Depth-weighted VWAP per side over all levels: `sum(price*vol)/sum(vol)`.
+///
+/// Takes the same `[price, vol]` books as `compute_book_stats` and returns
+/// `(bid_vwap, ask_vwap)` in the books' scaled price units; a side with no
+/// volume yields 0.0. Kept separate so `compute_book_stats` stays a 7-tuple.
+#[pyfunction]
+pub fn compute_book_vwap(
+    bids: PyReadonlyArray2<i64>,
+    asks: PyReadonlyArray2<i64>,
+) -> PyResult<(f64, f64)> {
+    Ok((side_vwap(bids.as_array()), side_vwap(asks.as_array())))
+}
+
+pub(super) fn side_vwap(book: ArrayView2<i64>) -> f64 {
+    // i128 so price * vol cannot overflow on deep, finely scaled books
+    let mut notional = 0i128;
+    let mut volume = 0i128;
+    for row in book.rows() {
+        if row.len() >= 2 {
+            notional += row[0] as i128 * row[1] as i128;
+            volume += row[1] as i128;
+        }
+    }
+    if volume > 0 {
+        notional as f64 / volume as f64
+    } else {
+        0.0
+    }
+}
+
+/// Scalar mid price that tolerates a one-sided book.
+///
+/// With both sides present this is the plain mid. When only one side has a
+/// price (> 0), the missing side is implied at `best +/- tick_size_scaled *
+/// synthetic_ticks` — the same offset `normalize_bidask_tuple_with_synth`
+/// uses — without building any arrays. Returns 0.0 if both sides are empty.
+#[pyfunction]
+#[pyo3(signature = (best_bid, best_ask, tick_size_scaled, synthetic_ticks=1))]
+pub fn implied_mid(
+    best_bid: i64,
+    best_ask: i64,
+    tick_size_scaled: i64,
+    synthetic_ticks: i64,
+) -> f64 {
+    let tick_offset = tick_size_scaled.max(1) * synthetic_ticks.max(1);
+    match (best_bid > 0, best_ask > 0) {
+        (true, true) => (best_bid + best_ask) as f64 / 2.0,
+        (false, true) => ((best_ask - tick_offset).max(1) + best_ask) as f64 / 2.0,
+        (true, false) => (best_bid + (best_bid + tick_offset).max(1)) as f64 / 2.0,
+        (false, false) => 0.0,
+    }
+}
+
+/// First non-None value among `keys`, from dict items or object attributes.
+///
+/// A dotted string key (`"data.quote.bid_price"`) that isn't present as-is
+/// descends through nested dicts/objects one segment at a time; any missing
+/// or None segment makes that key a miss.
 #[pyfunction]
 pub fn get_field(payload: &Bound<'_, PyAny>, keys: &Bound<'_, PyAny>) -> PyResult<PyObject> {
     let py = payload.py();
-    let mut key_iter = keys.iter()?;
 
-    if let Ok(dict) = payload.downcast::<PyDict>() {
-        for item in key_iter.by_ref() {
-            let key = item?;
-            if let Ok(Some(value)) = dict.get_item(&key) {
-                if !value.is_none() {
-                    return Ok(value.into_py(py));
+    for item in keys.iter()? {
+        let key = item?;
+        if let Some(value) = lookup_key(payload, &key)? {
+            return Ok(value.unbind());
+        }
+        if let Ok(path) = key.downcast::<PyString>() {
+            let path = path.to_str()?;
+            if path.contains('.') {
+                if let Some(value) = lookup_path(payload, path) {
+                    return Ok(value.unbind());
                 }
             }
         }
-        return Ok(py.None());
     }
 
-    for item in key_iter {
-        let key = item?;
-        let key_str = key.str()?;
-        let name = key_str.to_str()?;
-        if let Ok(value) = payload.getattr(name) {
+    Ok(py.None())
+}
+
+/// Flat lookup of one key: dict item, or attribute named `str(key)`.
+fn lookup_key<'py>(
+    payload: &Bound<'py, PyAny>,
+    key: &Bound<'py, PyAny>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    if let Ok(dict) = payload.downcast::<PyDict>() {
+        if let Ok(Some(value)) = dict.get_item(key) {
             if !value.is_none() {
-                return Ok(value.into_py(py));
+                return Ok(Some(value));
             }
         }
+        return Ok(None);
     }
 
-    Ok(py.None())
+    let key_str = key.str()?;
+    let name = key_str.to_str()?;
+    Ok(payload.getattr(name).ok().filter(|v| !v.is_none()))
+}
+
+/// Walk a dotted path through nested dicts/objects.
+fn lookup_path<'py>(payload: &Bound<'py, PyAny>, path: &str) -> Option<Bound<'py, PyAny>> {
+    let mut cur = payload.clone();
+    for segment in path.split('.') {
+        let next = if let Ok(dict) = cur.downcast::<PyDict>() {
+            dict.get_item(segment).ok().flatten()
+        } else {
+            cur.getattr(segment).ok()
+        };
+        cur = next.filter(|v| !v.is_none())?;
+    }
+    Some(cur)
 }
 
 pub(super) fn get_optional(payload: &Bound<'_, PyAny>, keys: &[&str]) -> Option<PyObject> {
@@ -130,3 +213,46 @@ pub(super) fn extract_ts(value: Option<PyObject>, py: Python<'_>) -> PyResult<i6
     }
     Ok(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_side_vwap() {
+        use numpy::ndarray::{array, Array2};
+        let bids = array![[1000, 10], [990, 30]];
+        // (1000*10 + 990*30) / 40 = 992.5
+        assert_eq!(side_vwap(bids.view()), 992.5);
+        assert_eq!(side_vwap(Array2::<i64>::zeros((0, 2)).view()), 0.0);
+        assert_eq!(side_vwap(array![[1000, 0]].view()), 0.0);
+    }
+
+    #[test]
+    fn test_implied_mid_two_sided() {
+        assert_eq!(implied_mid(1000, 1010, 5, 1), 1005.0);
+    }
+
+    #[test]
+    fn test_implied_mid_bid_only() {
+        // synthetic ask = 1000 + 5*2 = 1010
+        assert_eq!(implied_mid(1000, 0, 5, 2), 1005.0);
+    }
+
+    #[test]
+    fn test_implied_mid_ask_only() {
+        // synthetic bid = 1010 - 5 = 1005
+        assert_eq!(implied_mid(0, 1010, 5, 1), 1007.5);
+    }
+
+    #[test]
+    fn test_implied_mid_ask_only_floor_at_one() {
+        // synthetic bid floored at 1
+        assert_eq!(implied_mid(0, 3, 10, 1), 2.0);
+    }
+
+    #[test]
+    fn test_implied_mid_empty() {
+        assert_eq!(implied_mid(0, 0, 5, 1), 0.0);
+    }
+}
@@ -0,0 +1,102 @@
+use pyo3::prelude::*;
+
+/// Ratios within this distance of an integer are treated as on-grid, so
+/// `0.3 / 0.1 = 2.9999999999999996` floors to 3 rather than 2.
+const GRID_EPS: f64 = 1e-9;
+
+/// Direction used by `round_to_tick`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum TickRounding {
+    /// Nearest tick, half away from zero (`f64::round`).
+    Round,
+    /// Largest tick <= price.
+    Floor,
+    /// Smallest tick >= price.
+    Ceil,
+}
+
+impl TickRounding {
+    /// Parse the Python-facing mode name: "round", "floor" or "ceil".
+    pub(super) fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "round" => Ok(Self::Round),
+            "floor" => Ok(Self::Floor),
+            "ceil" => Ok(Self::Ceil),
+            other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown mode '{other}' (expected 'round', 'floor' or 'ceil')"
+            ))),
+        }
+    }
+
+    /// Number of ticks for `price`.
+    pub(super) fn ticks(self, price: f64, tick_size: f64) -> f64 {
+        let raw = price / tick_size;
+        let nearest = raw.round();
+        if (raw - nearest).abs() < GRID_EPS {
+            return nearest;
+        }
+        match self {
+            Self::Round => nearest,
+            Self::Floor => raw.floor(),
+            Self::Ceil => raw.ceil(),
+        }
+    }
+}
+
+/// Snap `price` to a multiple of `tick_size`. `mode` is "round" (nearest,
+/// half away from zero), "floor" or "ceil".
+#[pyfunction]
+#[pyo3(signature = (price, tick_size, mode="round"))]
+pub fn round_to_tick(price: f64, tick_size: f64, mode: &str) -> PyResult<f64> {
+    let mode = TickRounding::parse(mode)?;
+    if !(tick_size.is_finite() && tick_size > 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "tick_size must be positive, got {tick_size}"
+        )));
+    }
+    if !price.is_finite() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "price must be finite, got {price}"
+        )));
+    }
+    Ok(mode.ticks(price, tick_size) * tick_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_half_way_cases() {
+        // Exactly half a tick: away from zero for "round"
+        assert_eq!(round_to_tick(10.5, 1.0, "round").unwrap(), 11.0);
+        assert_eq!(round_to_tick(11.5, 1.0, "round").unwrap(), 12.0);
+        assert_eq!(round_to_tick(-10.5, 1.0, "round").unwrap(), -11.0);
+        assert_eq!(round_to_tick(10.5, 1.0, "floor").unwrap(), 10.0);
+        assert_eq!(round_to_tick(10.5, 1.0, "ceil").unwrap(), 11.0);
+        assert_eq!(round_to_tick(102.5, 5.0, "round").unwrap(), 105.0);
+        assert_eq!(round_to_tick(97.5, 5.0, "round").unwrap(), 100.0);
+    }
+
+    #[test]
+    fn test_on_grid_prices_are_stable() {
+        // 0.3 / 0.1 is just under 3 in binary; floor must not drop a tick
+        assert_eq!(TickRounding::Floor.ticks(0.3, 0.1), 3.0);
+        assert_eq!(TickRounding::Ceil.ticks(0.7, 0.1), 7.0);
+        for mode in ["round", "floor", "ceil"] {
+            assert_eq!(round_to_tick(600.0, 1.0, mode).unwrap(), 600.0);
+            assert_eq!(round_to_tick(12.25, 0.05, mode).unwrap(), 245.0 * 0.05);
+        }
+        assert_eq!(round_to_tick(12.26, 0.05, "floor").unwrap(), 245.0 * 0.05);
+        assert_eq!(round_to_tick(12.26, 0.05, "ceil").unwrap(), 246.0 * 0.05);
+        assert_eq!(round_to_tick(12.26, 0.05, "round").unwrap(), 245.0 * 0.05);
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        assert!(round_to_tick(1.0, 0.0, "round").is_err());
+        assert!(round_to_tick(1.0, -0.5, "round").is_err());
+        assert!(round_to_tick(f64::NAN, 0.5, "round").is_err());
+        assert!(round_to_tick(1.0, 0.5, "nearest").is_err());
+    }
+}
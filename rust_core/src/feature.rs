@@ -1,4 +1,105 @@
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+/// Output layout of `LobFeatureKernelV1::update` (matches the
+/// `lob_shared_v1` feature set in the Python registry).
+pub const FEATURE_NAMES_V1: [&str; 16] = [
+    "best_bid",
+    "best_ask",
+    "mid_price_x2",
+    "spread_scaled",
+    "bid_depth",
+    "ask_depth",
+    "depth_imbalance_ppm",
+    "microprice_x2",
+    "l1_bid_qty",
+    "l1_ask_qty",
+    "l1_imbalance_ppm",
+    "ofi_l1_raw",
+    "ofi_l1_cum",
+    "ofi_l1_ema8",
+    "spread_ema8_scaled",
+    "depth_imbalance_ema8_ppm",
+];
+
+/// Extra slots appended by `LobFeatureKernelV2::update`.
+pub const FEATURE_NAMES_V2_EXTRA: [&str; 2] = ["ofi_ml_raw", "ofi_ml_cum"];
+
+/// Default EMA window for the smoothed OFI slot.
+const DEFAULT_OFI_EMA_WINDOW: f64 = 8.0;
+/// Default per-tick decay for the geometric OFI recursion.
+const DEFAULT_OFI_GEOMETRIC_DECAY: f64 = 0.9;
+/// Default span for the spread and imbalance EMAs.
+const DEFAULT_EMA_SPAN: f64 = 8.0;
+
+/// EMA weight for a span: `2 / (span + 1)`. Span must be >= 1.
+fn span_alpha(name: &str, span: f64) -> PyResult<f64> {
+    if span.is_nan() || span < 1.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{name} must be >= 1, got {span}"
+        )));
+    }
+    Ok(2.0 / (span + 1.0))
+}
+
+/// Smoothing applied to L1 OFI for the `ofi_l1_ema8` output slot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OfiDecay {
+    /// `ema = (1 - a) * ema + a * raw` with `a = 2 / (window + 1)`.
+    Ema { alpha: f64 },
+    /// `ofi = raw + decay * prev` (the recursion used by RL-style live strategies).
+    Geometric { decay: f64 },
+}
+
+impl Default for OfiDecay {
+    fn default() -> Self {
+        Self::Ema {
+            alpha: 2.0 / (DEFAULT_OFI_EMA_WINDOW + 1.0),
+        }
+    }
+}
+
+impl OfiDecay {
+    /// Parse the Python-facing mode ("ema" or "geometric") and its parameter
+    /// (EMA window, or per-tick decay). `None` selects the mode default.
+    pub fn parse(mode: &str, param: Option<f64>) -> PyResult<Self> {
+        match mode.to_ascii_lowercase().as_str() {
+            "ema" => {
+                let window = param.unwrap_or(DEFAULT_OFI_EMA_WINDOW);
+                if window.is_nan() || window < 1.0 {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "ema window must be >= 1, got {window}"
+                    )));
+                }
+                Ok(Self::Ema {
+                    alpha: 2.0 / (window + 1.0),
+                })
+            }
+            "geometric" => {
+                let decay = param.unwrap_or(DEFAULT_OFI_GEOMETRIC_DECAY);
+                if !(0.0..1.0).contains(&decay) {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "geometric decay must be in [0, 1), got {decay}"
+                    )));
+                }
+                Ok(Self::Geometric { decay })
+            }
+            other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown decay_mode '{other}' (expected 'ema' or 'geometric')"
+            ))),
+        }
+    }
+
+    #[inline(always)]
+    fn step(&self, prev: f64, raw: f64) -> f64 {
+        match *self {
+            Self::Ema { alpha } => (1.0 - alpha) * prev + alpha * raw,
+            Self::Geometric { decay } => raw + decay * prev,
+        }
+    }
+}
 
 #[pyclass]
 pub struct LobFeatureKernelV1 {
@@ -11,20 +112,27 @@ pub struct LobFeatureKernelV1 {
     spread_ema8: f64,
     imbalance_ema8_ppm: f64,
     initialized: bool,
+    ofi_decay: OfiDecay,
+    spread_alpha: f64,
+    imbalance_alpha: f64,
+    max_gap_ns: Option<i64>,
+    last_ts_ns: Option<i64>,
+    last_update_reset: bool,
 }
 
 impl Default for LobFeatureKernelV1 {
     fn default() -> Self {
-        Self::new()
+        Self::with_ofi_decay(OfiDecay::default())
     }
 }
 
-unsafe impl Send for LobFeatureKernelV1 {}
-
-#[pymethods]
 impl LobFeatureKernelV1 {
-    #[new]
-    pub fn new() -> Self {
+    pub fn with_ofi_decay(ofi_decay: OfiDecay) -> Self {
+        let alpha = 2.0 / (DEFAULT_EMA_SPAN + 1.0);
+        Self::with_params(ofi_decay, alpha, alpha)
+    }
+
+    pub fn with_params(ofi_decay: OfiDecay, spread_alpha: f64, imbalance_alpha: f64) -> Self {
         Self {
             prev_best_bid: 0,
             prev_best_ask: 0,
@@ -35,23 +143,31 @@ impl LobFeatureKernelV1 {
             spread_ema8: 0.0,
             imbalance_ema8_ppm: 0.0,
             initialized: false,
+            ofi_decay,
+            spread_alpha,
+            imbalance_alpha,
+            max_gap_ns: None,
+            last_ts_ns: None,
+            last_update_reset: false,
         }
     }
 
-    pub fn reset(&mut self) {
-        self.prev_best_bid = 0;
-        self.prev_best_ask = 0;
-        self.prev_l1_bid_qty = 0;
-        self.prev_l1_ask_qty = 0;
-        self.ofi_l1_cum = 0;
-        self.ofi_l1_ema8 = 0.0;
-        self.spread_ema8 = 0.0;
-        self.imbalance_ema8_ppm = 0.0;
-        self.initialized = false;
+    /// Reset if `ts_ns` is more than `max_gap_ns` after the previous
+    /// timestamped update. Returns whether the reset happened.
+    fn check_session_gap(&mut self, ts_ns: i64) -> bool {
+        let gap = match (self.max_gap_ns, self.last_ts_ns) {
+            (Some(max_gap), Some(last)) => ts_ns.saturating_sub(last) > max_gap,
+            _ => false,
+        };
+        if gap {
+            self.reset();
+        }
+        self.last_ts_ns = Some(ts_ns);
+        gap
     }
 
     #[allow(clippy::too_many_arguments)]
-    pub fn update(
+    pub fn compute(
         &mut self,
         best_bid: i64,
         best_ask: i64,
@@ -99,29 +215,26 @@ impl LobFeatureKernelV1 {
                     py_round_i64(self.imbalance_ema8_ppm),
                 )
             } else {
-                let b_flow = if best_bid > self.prev_best_bid {
-                    l1_bid_qty
-                } else if best_bid == self.prev_best_bid {
-                    l1_bid_qty - self.prev_l1_bid_qty
-                } else {
-                    -self.prev_l1_bid_qty
-                };
-
-                let a_flow = if best_ask > self.prev_best_ask {
-                    -self.prev_l1_ask_qty
-                } else if best_ask == self.prev_best_ask {
-                    l1_ask_qty - self.prev_l1_ask_qty
-                } else {
-                    l1_ask_qty
-                };
+                let b_flow = bid_flow(
+                    best_bid,
+                    l1_bid_qty,
+                    self.prev_best_bid,
+                    self.prev_l1_bid_qty,
+                );
+                let a_flow = ask_flow(
+                    best_ask,
+                    l1_ask_qty,
+                    self.prev_best_ask,
+                    self.prev_l1_ask_qty,
+                );
 
                 let ofi_raw = b_flow - a_flow;
                 self.ofi_l1_cum += ofi_raw;
-                let alpha = 2.0 / 9.0;
-                self.ofi_l1_ema8 = (1.0 - alpha) * self.ofi_l1_ema8 + alpha * ofi_raw as f64;
-                self.spread_ema8 = (1.0 - alpha) * self.spread_ema8 + alpha * spread_scaled as f64;
+                let (sa, ia) = (self.spread_alpha, self.imbalance_alpha);
+                self.ofi_l1_ema8 = self.ofi_decay.step(self.ofi_l1_ema8, ofi_raw as f64);
+                self.spread_ema8 = (1.0 - sa) * self.spread_ema8 + sa * spread_scaled as f64;
                 self.imbalance_ema8_ppm =
-                    (1.0 - alpha) * self.imbalance_ema8_ppm + alpha * l1_imbalance_ppm as f64;
+                    (1.0 - ia) * self.imbalance_ema8_ppm + ia * l1_imbalance_ppm as f64;
 
                 (
                     ofi_raw,
@@ -158,6 +271,397 @@ impl LobFeatureKernelV1 {
     }
 }
 
+unsafe impl Send for LobFeatureKernelV1 {}
+
+#[pymethods]
+impl LobFeatureKernelV1 {
+    /// `decay_mode` selects how the `ofi_l1_ema8` slot smooths raw OFI:
+    ///   "ema"       – EMA over `decay` ticks (default window 8)
+    ///   "geometric" – `ofi = raw + decay * prev` (default decay 0.9)
+    /// `ofi_span` is an alias for `decay` in "ema" mode.
+    ///
+    /// `spread_span` / `imbalance_span` set the spread and imbalance EMAs
+    /// (`alpha = 2 / (span + 1)`). Output slot names keep the `ema8` suffix
+    /// regardless of span so the vector layout never changes.
+    #[new]
+    #[pyo3(signature = (
+        decay_mode="ema",
+        decay=None,
+        ofi_span=None,
+        spread_span=DEFAULT_EMA_SPAN,
+        imbalance_span=DEFAULT_EMA_SPAN,
+        max_gap_ns=None
+    ))]
+    pub fn new(
+        decay_mode: &str,
+        decay: Option<f64>,
+        ofi_span: Option<f64>,
+        spread_span: f64,
+        imbalance_span: f64,
+        max_gap_ns: Option<i64>,
+    ) -> PyResult<Self> {
+        let decay =
+            match (ofi_span, decay) {
+                (None, d) => d,
+                (Some(span), None) if decay_mode.eq_ignore_ascii_case("ema") => Some(span),
+                (Some(_), _) => return Err(pyo3::exceptions::PyValueError::new_err(
+                    "ofi_span applies to decay_mode='ema' only and cannot be combined with decay",
+                )),
+            };
+        if matches!(max_gap_ns, Some(gap) if gap <= 0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "max_gap_ns must be > 0",
+            ));
+        }
+        let mut kernel = Self::with_params(
+            OfiDecay::parse(decay_mode, decay)?,
+            span_alpha("spread_span", spread_span)?,
+            span_alpha("imbalance_span", imbalance_span)?,
+        );
+        kernel.max_gap_ns = max_gap_ns;
+        Ok(kernel)
+    }
+
+    /// Names of the `update` output slots, in order.
+    #[staticmethod]
+    pub fn feature_names() -> Vec<String> {
+        FEATURE_NAMES_V1.iter().map(|n| n.to_string()).collect()
+    }
+
+    /// Same as `update`, but returns a dict keyed by `feature_names()`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_dict<'py>(
+        &mut self,
+        py: Python<'py>,
+        best_bid: i64,
+        best_ask: i64,
+        mid_price_x2: i64,
+        spread_scaled: i64,
+        bid_depth: i64,
+        ask_depth: i64,
+        l1_bid_qty: i64,
+        l1_ask_qty: i64,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let values = self.update(
+            best_bid,
+            best_ask,
+            mid_price_x2,
+            spread_scaled,
+            bid_depth,
+            ask_depth,
+            l1_bid_qty,
+            l1_ask_qty,
+            None,
+        );
+        let dict = PyDict::new_bound(py);
+        for (name, value) in FEATURE_NAMES_V1.iter().zip(values) {
+            dict.set_item(*name, value)?;
+        }
+        Ok(dict)
+    }
+
+    pub fn reset(&mut self) {
+        self.prev_best_bid = 0;
+        self.prev_best_ask = 0;
+        self.prev_l1_bid_qty = 0;
+        self.prev_l1_ask_qty = 0;
+        self.ofi_l1_cum = 0;
+        self.ofi_l1_ema8 = 0.0;
+        self.spread_ema8 = 0.0;
+        self.imbalance_ema8_ppm = 0.0;
+        self.initialized = false;
+        self.last_ts_ns = None;
+        self.last_update_reset = false;
+    }
+
+    /// Compute the 16 features for one book update. When `max_gap_ns` is set
+    /// and `ts_ns` is more than that past the previous update, the kernel is
+    /// reset first (session boundary) so the tick seeds state and emits zero
+    /// OFI instead of a spurious flow; `last_update_reset` reports whether
+    /// that happened on this call.
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (
+        best_bid,
+        best_ask,
+        mid_price_x2,
+        spread_scaled,
+        bid_depth,
+        ask_depth,
+        l1_bid_qty,
+        l1_ask_qty,
+        ts_ns=None
+    ))]
+    pub fn update(
+        &mut self,
+        best_bid: i64,
+        best_ask: i64,
+        mid_price_x2: i64,
+        spread_scaled: i64,
+        bid_depth: i64,
+        ask_depth: i64,
+        l1_bid_qty: i64,
+        l1_ask_qty: i64,
+        ts_ns: Option<i64>,
+    ) -> Vec<i64> {
+        self.last_update_reset = ts_ns.is_some_and(|ts| self.check_session_gap(ts));
+        self.compute(
+            best_bid,
+            best_ask,
+            mid_price_x2,
+            spread_scaled,
+            bid_depth,
+            ask_depth,
+            l1_bid_qty,
+            l1_ask_qty,
+        )
+    }
+
+    #[getter]
+    pub fn get_max_gap_ns(&self) -> Option<i64> {
+        self.max_gap_ns
+    }
+
+    /// True if the last `update` reset the kernel on a session gap.
+    #[getter]
+    pub fn get_last_update_reset(&self) -> bool {
+        self.last_update_reset
+    }
+}
+
+/// Multi-level OFI kernel: the 16 `LobFeatureKernelV1` values computed from
+/// the top of the book, followed by `ofi_ml_raw` and `ofi_ml_cum` — Cont-style
+/// order flow summed over the top `depth` levels. Per-level flows use the
+/// same rules as L1 and are available via `level_ofi()`.
+///
+/// Missing levels are padded as empty (bid price 0, ask price `i64::MAX`), so
+/// a level that disappears counts as its whole previous queue leaving.
+#[pyclass]
+pub struct LobFeatureKernelV2 {
+    l1: LobFeatureKernelV1,
+    depth: usize,
+    prev_bid_px: Vec<i64>,
+    prev_bid_qty: Vec<i64>,
+    prev_ask_px: Vec<i64>,
+    prev_ask_qty: Vec<i64>,
+    level_ofi: Vec<i64>,
+    ofi_ml_cum: i64,
+    initialized: bool,
+}
+
+unsafe impl Send for LobFeatureKernelV2 {}
+
+#[pymethods]
+impl LobFeatureKernelV2 {
+    /// `depth` is the number of levels (K) aggregated into the multi-level
+    /// OFI. The remaining arguments configure the L1 kernel as in V1.
+    #[new]
+    #[pyo3(signature = (
+        depth,
+        decay_mode="ema",
+        decay=None,
+        ofi_span=None,
+        spread_span=DEFAULT_EMA_SPAN,
+        imbalance_span=DEFAULT_EMA_SPAN
+    ))]
+    pub fn new(
+        depth: usize,
+        decay_mode: &str,
+        decay: Option<f64>,
+        ofi_span: Option<f64>,
+        spread_span: f64,
+        imbalance_span: f64,
+    ) -> PyResult<Self> {
+        if depth == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "depth must be >= 1",
+            ));
+        }
+        Ok(Self {
+            l1: LobFeatureKernelV1::new(
+                decay_mode,
+                decay,
+                ofi_span,
+                spread_span,
+                imbalance_span,
+                None,
+            )?,
+            depth,
+            prev_bid_px: vec![0; depth],
+            prev_bid_qty: vec![0; depth],
+            prev_ask_px: vec![i64::MAX; depth],
+            prev_ask_qty: vec![0; depth],
+            level_ofi: vec![0; depth],
+            ofi_ml_cum: 0,
+            initialized: false,
+        })
+    }
+
+    pub fn reset(&mut self) {
+        self.l1.reset();
+        self.prev_bid_px.fill(0);
+        self.prev_bid_qty.fill(0);
+        self.prev_ask_px.fill(i64::MAX);
+        self.prev_ask_qty.fill(0);
+        self.level_ofi.fill(0);
+        self.ofi_ml_cum = 0;
+        self.initialized = false;
+    }
+
+    /// Update from the top-K book (best first). Each side may hold fewer
+    /// than `depth` levels; prices and qtys of a side must have equal length.
+    /// Returns the 16 V1 values followed by `ofi_ml_raw`, `ofi_ml_cum`.
+    pub fn update(
+        &mut self,
+        bid_prices: Vec<i64>,
+        bid_qtys: Vec<i64>,
+        ask_prices: Vec<i64>,
+        ask_qtys: Vec<i64>,
+    ) -> PyResult<Vec<i64>> {
+        if bid_prices.len() != bid_qtys.len() || ask_prices.len() != ask_qtys.len() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "price and qty arrays must have equal length per side",
+            ));
+        }
+        if bid_prices.len() > self.depth || ask_prices.len() > self.depth {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "at most {} levels per side, got {} bids / {} asks",
+                self.depth,
+                bid_prices.len(),
+                ask_prices.len()
+            )));
+        }
+
+        let best_bid = bid_prices.first().copied().unwrap_or(0);
+        let best_ask = ask_prices.first().copied().unwrap_or(0);
+        let (mid_price_x2, spread_scaled) = if !bid_prices.is_empty() && !ask_prices.is_empty() {
+            (best_bid + best_ask, best_ask - best_bid)
+        } else {
+            (0, 0)
+        };
+        let bid_depth: i64 = bid_qtys.iter().map(|q| (*q).max(0)).sum();
+        let ask_depth: i64 = ask_qtys.iter().map(|q| (*q).max(0)).sum();
+        let l1_bid_qty = bid_qtys.first().copied().unwrap_or(0);
+        let l1_ask_qty = ask_qtys.first().copied().unwrap_or(0);
+
+        let mut values = self.l1.compute(
+            best_bid,
+            best_ask,
+            mid_price_x2,
+            spread_scaled,
+            bid_depth,
+            ask_depth,
+            l1_bid_qty,
+            l1_ask_qty,
+        );
+
+        let mut ofi_ml_raw = 0_i64;
+        for lvl in 0..self.depth {
+            let (bp, bq) = match bid_prices.get(lvl) {
+                Some(&p) => (p, bid_qtys[lvl].max(0)),
+                None => (0, 0),
+            };
+            let (ap, aq) = match ask_prices.get(lvl) {
+                Some(&p) => (p, ask_qtys[lvl].max(0)),
+                None => (i64::MAX, 0),
+            };
+
+            let flow = if self.initialized {
+                bid_flow(bp, bq, self.prev_bid_px[lvl], self.prev_bid_qty[lvl])
+                    - ask_flow(ap, aq, self.prev_ask_px[lvl], self.prev_ask_qty[lvl])
+            } else {
+                0
+            };
+            self.level_ofi[lvl] = flow;
+            ofi_ml_raw += flow;
+
+            self.prev_bid_px[lvl] = bp;
+            self.prev_bid_qty[lvl] = bq;
+            self.prev_ask_px[lvl] = ap;
+            self.prev_ask_qty[lvl] = aq;
+        }
+        self.initialized = true;
+        self.ofi_ml_cum += ofi_ml_raw;
+
+        values.push(ofi_ml_raw);
+        values.push(self.ofi_ml_cum);
+        Ok(values)
+    }
+
+    /// Names of the `update` output slots, in order.
+    #[staticmethod]
+    pub fn feature_names() -> Vec<String> {
+        FEATURE_NAMES_V1
+            .iter()
+            .chain(FEATURE_NAMES_V2_EXTRA.iter())
+            .map(|n| n.to_string())
+            .collect()
+    }
+
+    /// Per-level OFI from the last `update`, best level first.
+    pub fn level_ofi(&self) -> Vec<i64> {
+        self.level_ofi.clone()
+    }
+
+    #[getter]
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+}
+
+/// Spread-only slice of `LobFeatureKernelV1`: the scaled spread and its EMA,
+/// without the OFI and imbalance state.
+///
+/// `update(best_bid, best_ask)` returns `(spread_scaled, spread_ema_scaled)`,
+/// the EMA rounded half to even like the kernel's `spread_ema8_scaled`
+/// slot. The first valid tick seeds the EMA. Ticks missing either side
+/// (price <= 0) return a spread of 0 and leave the EMA untouched.
+#[pyclass]
+pub struct SpreadKernel {
+    alpha: f64,
+    span: f64,
+    spread_ema: f64,
+    initialized: bool,
+}
+
+#[pymethods]
+impl SpreadKernel {
+    #[new]
+    #[pyo3(signature = (span=DEFAULT_EMA_SPAN))]
+    pub fn new(span: f64) -> PyResult<Self> {
+        Ok(Self {
+            alpha: span_alpha("span", span)?,
+            span,
+            spread_ema: 0.0,
+            initialized: false,
+        })
+    }
+
+    #[inline(always)]
+    pub fn update(&mut self, best_bid: i64, best_ask: i64) -> (i64, i64) {
+        if best_bid <= 0 || best_ask <= 0 {
+            return (0, py_round_i64(self.spread_ema));
+        }
+        let spread = best_ask - best_bid;
+        if self.initialized {
+            self.spread_ema = (1.0 - self.alpha) * self.spread_ema + self.alpha * spread as f64;
+        } else {
+            self.spread_ema = spread as f64;
+            self.initialized = true;
+        }
+        (spread, py_round_i64(self.spread_ema))
+    }
+
+    pub fn reset(&mut self) {
+        self.spread_ema = 0.0;
+        self.initialized = false;
+    }
+
+    #[getter]
+    pub fn get_span(&self) -> f64 {
+        self.span
+    }
+}
+
 /// Fused feature pipeline: computes all 16 feature values + changed_mask +
 /// warmup_ready_mask in a single Rust call.  Replaces three separate Python
 /// methods (`_compute_values`, `_compute_changed_mask`,
@@ -252,21 +756,18 @@ impl LobFeatureKernelV1Inner {
                     py_round_i64(self.ema_state[2]),
                 )
             } else {
-                let b_flow = if best_bid > self.prev_best_bid {
-                    l1_bid_qty
-                } else if best_bid == self.prev_best_bid {
-                    l1_bid_qty - self.prev_l1_bid_qty
-                } else {
-                    -self.prev_l1_bid_qty
-                };
-
-                let a_flow = if best_ask > self.prev_best_ask {
-                    -self.prev_l1_ask_qty
-                } else if best_ask == self.prev_best_ask {
-                    l1_ask_qty - self.prev_l1_ask_qty
-                } else {
-                    l1_ask_qty
-                };
+                let b_flow = bid_flow(
+                    best_bid,
+                    l1_bid_qty,
+                    self.prev_best_bid,
+                    self.prev_l1_bid_qty,
+                );
+                let a_flow = ask_flow(
+                    best_ask,
+                    l1_ask_qty,
+                    self.prev_best_ask,
+                    self.prev_l1_ask_qty,
+                );
 
                 let ofi_raw = b_flow - a_flow;
                 self.ofi_l1_cum += ofi_raw;
@@ -389,6 +890,30 @@ impl RustFeaturePipelineV1 {
     }
 }
 
+/// Cont-style bid flow: price up → new qty, equal → delta, down → -prev qty.
+#[inline(always)]
+fn bid_flow(price: i64, qty: i64, prev_price: i64, prev_qty: i64) -> i64 {
+    if price > prev_price {
+        qty
+    } else if price == prev_price {
+        qty - prev_qty
+    } else {
+        -prev_qty
+    }
+}
+
+/// Cont-style ask flow: price up → -prev qty, equal → delta, down → new qty.
+#[inline(always)]
+fn ask_flow(price: i64, qty: i64, prev_price: i64, prev_qty: i64) -> i64 {
+    if price > prev_price {
+        -prev_qty
+    } else if price == prev_price {
+        qty - prev_qty
+    } else {
+        qty
+    }
+}
+
 #[inline]
 fn py_round_i64(x: f64) -> i64 {
     // Python round() uses bankers rounding (ties to even).
@@ -396,6 +921,159 @@ fn py_round_i64(x: f64) -> i64 {
     x.round_ties_even() as i64
 }
 
+/// One `LobFeatureKernelV1` per symbol, created on first `update` with the
+/// constructor's kernel settings (same arguments as `LobFeatureKernelV1`).
+#[pyclass]
+pub struct FeatureKernelManager {
+    kernels: HashMap<String, LobFeatureKernelV1>,
+    settings: KernelSettings,
+}
+
+/// `LobFeatureKernelV1` constructor arguments shared by a manager's kernels.
+struct KernelSettings {
+    decay_mode: String,
+    decay: Option<f64>,
+    ofi_span: Option<f64>,
+    spread_span: f64,
+    imbalance_span: f64,
+    max_gap_ns: Option<i64>,
+}
+
+impl KernelSettings {
+    fn new_kernel(&self) -> PyResult<LobFeatureKernelV1> {
+        LobFeatureKernelV1::new(
+            &self.decay_mode,
+            self.decay,
+            self.ofi_span,
+            self.spread_span,
+            self.imbalance_span,
+            self.max_gap_ns,
+        )
+    }
+}
+
+#[pymethods]
+impl FeatureKernelManager {
+    #[new]
+    #[pyo3(signature = (
+        decay_mode="ema",
+        decay=None,
+        ofi_span=None,
+        spread_span=DEFAULT_EMA_SPAN,
+        imbalance_span=DEFAULT_EMA_SPAN,
+        max_gap_ns=None
+    ))]
+    pub fn new(
+        decay_mode: &str,
+        decay: Option<f64>,
+        ofi_span: Option<f64>,
+        spread_span: f64,
+        imbalance_span: f64,
+        max_gap_ns: Option<i64>,
+    ) -> PyResult<Self> {
+        let settings = KernelSettings {
+            decay_mode: decay_mode.to_string(),
+            decay,
+            ofi_span,
+            spread_span,
+            imbalance_span,
+            max_gap_ns,
+        };
+        // Reject bad settings now rather than on the first update
+        settings.new_kernel()?;
+        Ok(Self {
+            kernels: HashMap::new(),
+            settings,
+        })
+    }
+
+    /// `LobFeatureKernelV1.update` on `symbol`'s kernel.
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (
+        symbol,
+        best_bid,
+        best_ask,
+        mid_price_x2,
+        spread_scaled,
+        bid_depth,
+        ask_depth,
+        l1_bid_qty,
+        l1_ask_qty,
+        ts_ns=None
+    ))]
+    pub fn update(
+        &mut self,
+        symbol: &str,
+        best_bid: i64,
+        best_ask: i64,
+        mid_price_x2: i64,
+        spread_scaled: i64,
+        bid_depth: i64,
+        ask_depth: i64,
+        l1_bid_qty: i64,
+        l1_ask_qty: i64,
+        ts_ns: Option<i64>,
+    ) -> PyResult<Vec<i64>> {
+        let kernel = match self.kernels.entry(symbol.to_string()) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(self.settings.new_kernel()?),
+        };
+        Ok(kernel.update(
+            best_bid,
+            best_ask,
+            mid_price_x2,
+            spread_scaled,
+            bid_depth,
+            ask_depth,
+            l1_bid_qty,
+            l1_ask_qty,
+            ts_ns,
+        ))
+    }
+
+    /// `last_update_reset` of `symbol`'s kernel; false if the symbol is
+    /// unknown.
+    pub fn last_update_reset(&self, symbol: &str) -> bool {
+        self.kernels
+            .get(symbol)
+            .is_some_and(|kernel| kernel.last_update_reset)
+    }
+
+    /// Reset one symbol's kernel. Returns false if the symbol is unknown.
+    pub fn reset(&mut self, symbol: &str) -> bool {
+        match self.kernels.get_mut(symbol) {
+            Some(kernel) => {
+                kernel.reset();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reset every kernel, e.g. at a session boundary.
+    pub fn reset_all(&mut self) {
+        self.kernels
+            .values_mut()
+            .for_each(LobFeatureKernelV1::reset);
+    }
+
+    /// Drop a symbol's kernel. Returns false if the symbol is unknown.
+    pub fn remove(&mut self, symbol: &str) -> bool {
+        self.kernels.remove(symbol).is_some()
+    }
+
+    /// Symbols with a kernel, sorted.
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.kernels.keys().cloned().collect();
+        symbols.sort_unstable();
+        symbols
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.kernels.len()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -469,6 +1147,217 @@ mod tests {
         assert!(changed < (1_i64 << 16) - 1); // not all changed
     }
 
+    #[test]
+    fn test_kernel_default_ema_matches_pipeline() {
+        let mut kernel = LobFeatureKernelV1::default();
+        let mut inner = LobFeatureKernelV1Inner::new();
+        let ticks = [(100, 80), (150, 80), (150, 20), (90, 40)];
+        for (bq, aq) in ticks {
+            let a = kernel.update(100, 102, 202, 2, 500, 400, bq, aq, None);
+            let b = inner.compute(100, 102, 202, 2, 500, 400, bq, aq);
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn test_kernel_geometric_decay_recursion() {
+        let mut k = LobFeatureKernelV1::new("geometric", Some(0.9), None, 8.0, 8.0, None).unwrap();
+        k.update(100, 102, 202, 2, 500, 400, 100, 80, None);
+        // raw = +50 → ofi = 50
+        let v = k.update(100, 102, 202, 2, 500, 400, 150, 80, None);
+        assert_eq!(v[11], 50);
+        assert_eq!(v[13], 50);
+        // raw = 0 → ofi = 0 + 0.9 * 50 = 45
+        let v = k.update(100, 102, 202, 2, 500, 400, 150, 80, None);
+        assert_eq!(v[13], 45);
+        // raw = +10 → ofi = 10 + 0.9 * 45 = 50.5 → bankers round 50
+        let v = k.update(100, 102, 202, 2, 500, 400, 160, 80, None);
+        assert_eq!(v[13], 50);
+    }
+
+    #[test]
+    fn test_kernel_ema_window_param() {
+        let mut k = LobFeatureKernelV1::new("ema", Some(1.0), None, 8.0, 8.0, None).unwrap();
+        k.update(100, 102, 202, 2, 500, 400, 100, 80, None);
+        // window=1 → alpha=1 → ema tracks raw exactly
+        let v = k.update(100, 102, 202, 2, 500, 400, 130, 80, None);
+        assert_eq!(v[13], v[11]);
+    }
+
+    #[test]
+    fn test_kernel_decay_mode_validation() {
+        assert!(LobFeatureKernelV1::new("geometric", Some(1.5), None, 8.0, 8.0, None).is_err());
+        assert!(LobFeatureKernelV1::new("ema", Some(0.0), None, 8.0, 8.0, None).is_err());
+        assert!(LobFeatureKernelV1::new("linear", None, None, 8.0, 8.0, None).is_err());
+    }
+
+    #[test]
+    fn test_v2_l1_values_match_v1() {
+        let mut v1 = LobFeatureKernelV1::default();
+        let mut v2 = LobFeatureKernelV2::new(3, "ema", None, None, 8.0, 8.0).unwrap();
+        let ticks = [(100, 80), (150, 80), (150, 20), (90, 40)];
+        for (bq, aq) in ticks {
+            let a = v1.update(100, 102, 202, 2, bq + 30, aq + 70, bq, aq, None);
+            let b = v2
+                .update(vec![100, 99], vec![bq, 30], vec![102, 103], vec![aq, 70])
+                .unwrap();
+            assert_eq!(b.len(), 18);
+            assert_eq!(&b[..16], &a[..]);
+        }
+    }
+
+    #[test]
+    fn test_v2_multi_level_ofi() {
+        let mut k = LobFeatureKernelV2::new(2, "ema", None, None, 8.0, 8.0).unwrap();
+        let v = k
+            .update(vec![100, 99], vec![10, 20], vec![101, 102], vec![5, 7])
+            .unwrap();
+        assert_eq!((v[16], v[17]), (0, 0)); // first tick seeds state
+
+        // L1: bid same px +4, ask same px -1 → 4 - (-1) = 5
+        // L2: bid same px +5; ask px up 102→103 → -(-7) = +7
+        let v = k
+            .update(vec![100, 99], vec![14, 25], vec![101, 103], vec![4, 9])
+            .unwrap();
+        assert_eq!(k.level_ofi(), vec![5, 12]);
+        assert_eq!(v[11], 5); // L1 OFI unchanged by V2
+        assert_eq!((v[16], v[17]), (17, 17));
+
+        // L2 ask vanishes → whole previous queue leaves: -(-9) = +9
+        let v = k
+            .update(vec![100, 99], vec![14, 25], vec![101], vec![4])
+            .unwrap();
+        assert_eq!(k.level_ofi(), vec![0, 9]);
+        assert_eq!((v[16], v[17]), (9, 26));
+    }
+
+    #[test]
+    fn test_v2_validates_inputs() {
+        assert!(LobFeatureKernelV2::new(0, "ema", None, None, 8.0, 8.0).is_err());
+        let mut k = LobFeatureKernelV2::new(1, "ema", None, None, 8.0, 8.0).unwrap();
+        assert!(k.update(vec![100], vec![], vec![101], vec![1]).is_err());
+        assert!(k
+            .update(vec![100, 99], vec![1, 1], vec![101], vec![1])
+            .is_err());
+    }
+
+    #[test]
+    fn test_kernel_default_spans_match_legacy() {
+        let mut k = LobFeatureKernelV1::new("ema", None, Some(8.0), 8.0, 8.0, None).unwrap();
+        let mut inner = LobFeatureKernelV1Inner::new();
+        for (bq, aq, spread) in [(100, 80, 2), (150, 80, 4), (150, 20, 2), (90, 40, 6)] {
+            let a = k.update(
+                100,
+                100 + spread,
+                200 + spread,
+                spread,
+                500,
+                400,
+                bq,
+                aq,
+                None,
+            );
+            let b = inner.compute(100, 100 + spread, 200 + spread, spread, 500, 400, bq, aq);
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn test_kernel_independent_spans() {
+        // span 1 → alpha 1: spread EMA tracks input, imbalance stays smoothed
+        let mut k = LobFeatureKernelV1::new("ema", None, None, 1.0, 8.0, None).unwrap();
+        k.update(100, 102, 202, 2, 500, 400, 100, 100, None);
+        let v = k.update(100, 110, 210, 10, 500, 400, 300, 100, None);
+        assert_eq!(v[14], 10);
+        assert_ne!(v[15], v[10]);
+
+        let mut k = LobFeatureKernelV1::new("ema", None, Some(1.0), 8.0, 8.0, None).unwrap();
+        k.update(100, 102, 202, 2, 500, 400, 100, 80, None);
+        let v = k.update(100, 102, 202, 2, 500, 400, 130, 80, None);
+        assert_eq!(v[13], v[11]);
+
+        assert!(LobFeatureKernelV1::new("ema", None, None, 0.5, 8.0, None).is_err());
+        assert!(LobFeatureKernelV1::new("geometric", None, Some(4.0), 8.0, 8.0, None).is_err());
+        assert!(LobFeatureKernelV1::new("ema", Some(4.0), Some(4.0), 8.0, 8.0, None).is_err());
+    }
+
+    #[test]
+    fn test_feature_names_schema() {
+        let names = LobFeatureKernelV1::feature_names();
+        let mut k = LobFeatureKernelV1::default();
+        assert_eq!(
+            names.len(),
+            k.update(100, 102, 202, 2, 5, 4, 3, 2, None).len()
+        );
+        let idx = |n: &str| names.iter().position(|x| x == n).unwrap();
+        assert_eq!(idx("microprice_x2"), 7);
+        assert_eq!(idx("l1_imbalance_ppm"), 10);
+        assert_eq!(idx("ofi_l1_raw"), 11);
+        assert_eq!(idx("ofi_l1_ema8"), 13);
+        assert_eq!(idx("spread_ema8_scaled"), 14);
+
+        let mut unique = names.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), names.len());
+
+        let v2_names = LobFeatureKernelV2::feature_names();
+        assert_eq!(&v2_names[..16], &names[..]);
+        assert_eq!(&v2_names[16..], &["ofi_ml_raw", "ofi_ml_cum"]);
+    }
+
+    #[test]
+    fn test_session_gap_resets_kernel() {
+        let mut k = LobFeatureKernelV1::new("ema", None, None, 8.0, 8.0, Some(1_000)).unwrap();
+        k.update(100, 102, 202, 2, 500, 400, 100, 80, Some(0));
+        let v = k.update(100, 102, 202, 2, 500, 400, 150, 80, Some(500));
+        assert_eq!(v[11], 50);
+
+        // Next session opens at a different price with a new queue
+        let v = k.update(120, 122, 242, 2, 500, 400, 10, 900, Some(10_000));
+        assert_eq!((v[11], v[12], v[13]), (0, 0, 0));
+        // Spread EMA re-seeded
+        assert_eq!(v[14], 2);
+
+        let v = k.update(120, 122, 242, 2, 500, 400, 20, 900, Some(10_100));
+        assert_eq!((v[11], v[12]), (10, 10));
+
+        // reset() forgets the last timestamp: no gap is measured against
+        // the pre-reset tick, so state seeded since is kept
+        k.reset();
+        k.update(120, 122, 242, 2, 500, 400, 20, 900, None);
+        let v = k.update(120, 122, 242, 2, 500, 400, 30, 900, Some(1_000_000));
+        assert_eq!(v[11], 10);
+    }
+
+    #[test]
+    fn test_last_update_reset_flags_gap_tick_only() {
+        let mut k = LobFeatureKernelV1::new("ema", None, None, 8.0, 8.0, Some(1_000)).unwrap();
+        k.update(100, 102, 202, 2, 500, 400, 100, 80, Some(0));
+        assert!(!k.get_last_update_reset());
+        k.update(120, 122, 242, 2, 500, 400, 10, 900, Some(10_000));
+        assert!(k.get_last_update_reset());
+        k.update(120, 122, 242, 2, 500, 400, 20, 900, Some(10_100));
+        assert!(!k.get_last_update_reset());
+
+        let mut m = FeatureKernelManager::new("ema", None, None, 8.0, 8.0, Some(1_000)).unwrap();
+        assert!(!m.last_update_reset("2330"));
+        for (ts, want) in [(0, false), (10_000, true), (10_100, false)] {
+            m.update("2330", 100, 102, 202, 2, 500, 400, 100, 80, Some(ts))
+                .unwrap();
+            assert_eq!(m.last_update_reset("2330"), want);
+        }
+    }
+
+    #[test]
+    fn test_session_gap_disabled_by_default() {
+        let mut k = LobFeatureKernelV1::default();
+        k.update(100, 102, 202, 2, 500, 400, 100, 80, Some(0));
+        let v = k.update(100, 102, 202, 2, 500, 400, 150, 80, Some(i64::MAX / 2));
+        assert_eq!(v[11], 50);
+        assert!(LobFeatureKernelV1::new("ema", None, None, 8.0, 8.0, Some(0)).is_err());
+    }
+
     #[test]
     fn test_py_round_i64_bankers() {
         assert_eq!(py_round_i64(2.5), 2);
@@ -501,4 +1390,69 @@ mod tests {
         assert_eq!(v[4], 0); // bid_depth clamped to 0
         assert_eq!(v[5], 0); // ask_depth clamped to 0
     }
+
+    #[test]
+    fn test_spread_kernel_matches_v1_spread_slots() {
+        let ticks = [
+            (100_0000, 101_0000),
+            (100_5000, 101_0000),
+            (100_0000, 102_0000),
+            (99_0000, 102_5000),
+            (100_0000, 100_5000),
+        ];
+        let mut k = LobFeatureKernelV1::with_params(
+            OfiDecay::default(),
+            span_alpha("spread_span", 4.0).unwrap(),
+            span_alpha("imbalance_span", 8.0).unwrap(),
+        );
+        let mut s = SpreadKernel::new(4.0).unwrap();
+        for (bid, ask) in ticks {
+            let v = k.compute(bid, ask, bid + ask, ask - bid, 10, 10, 5, 5);
+            assert_eq!(s.update(bid, ask), (v[3], v[14]));
+        }
+    }
+
+    #[test]
+    fn test_spread_kernel_skips_one_sided_ticks() {
+        let mut s = SpreadKernel::new(8.0).unwrap();
+        assert_eq!(s.update(0, 101_0000), (0, 0));
+        assert_eq!(s.update(100_0000, 101_0000), (1_0000, 1_0000)); // seeds
+        assert_eq!(s.update(100_0000, 0), (0, 1_0000));
+        s.reset();
+        assert_eq!(s.update(100_0000, 100_5000), (5000, 5000));
+        assert!(SpreadKernel::new(0.5).is_err());
+    }
+
+    #[test]
+    fn test_feature_kernel_manager_routes_by_symbol() {
+        let mut m = FeatureKernelManager::new("ema", None, None, 8.0, 8.0, None).unwrap();
+        let mut solo = LobFeatureKernelV1::default();
+        let ticks = [(100_0000, 101_0000, 5, 3), (100_0000, 101_0000, 8, 3)];
+        for (bid, ask, bq, aq) in ticks {
+            let want = solo.compute(bid, ask, bid + ask, ask - bid, bq, aq, bq, aq);
+            let got = m
+                .update("2330", bid, ask, bid + ask, ask - bid, bq, aq, bq, aq, None)
+                .unwrap();
+            assert_eq!(got, want);
+        }
+        // A new symbol starts from a fresh kernel: no OFI on its first tick
+        let other = m
+            .update("2317", 50_0000, 51_0000, 101_0000, 1_0000, 4, 4, 4, 4, None)
+            .unwrap();
+        assert_eq!(other[11], 0);
+        assert_eq!(m.symbols(), vec!["2317".to_string(), "2330".to_string()]);
+
+        assert!(m.reset("2330"));
+        assert!(!m.reset("0050"));
+        let after_reset = m
+            .update(
+                "2330", 100_0000, 101_0000, 201_0000, 1_0000, 1, 9, 1, 9, None,
+            )
+            .unwrap();
+        assert_eq!(after_reset[12], 0); // cumulative OFI restarted
+        m.reset_all();
+        assert!(m.remove("2317"));
+        assert_eq!(m.__len__(), 1);
+        assert!(FeatureKernelManager::new("linear", None, None, 8.0, 8.0, None).is_err());
+    }
 }
@@ -0,0 +1,380 @@
+use crate::lob::{scale_price, LimitOrderBook};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// One of our resting quotes and the visible volume queued ahead of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RestingOrder {
+    price: i64,
+    qty: i64,
+    ahead: i64,
+}
+
+/// Maker-fill simulator for one bid and one ask quote, integer-priced.
+///
+/// A quote placed at a price joins the back of its queue: `queue_ahead` is
+/// the visible quantity already resting there. Trades use price-time
+/// priority:
+/// - a trade through our price (below our bid / above our ask) means the
+///   level was swept, so the whole quote fills;
+/// - a trade at our price first consumes the volume ahead of us, and only
+///   the excess fills us;
+/// - anything else leaves the quote untouched.
+///
+/// `aggressor_side` is +1 for a buy aggressor (lifts asks) and -1 for a sell
+/// aggressor (hits bids).
+#[pyclass]
+#[derive(Default)]
+pub struct FillSimulator {
+    bid: Option<RestingOrder>,
+    ask: Option<RestingOrder>,
+    filled_bid: i64,
+    filled_ask: i64,
+}
+
+#[pymethods]
+impl FillSimulator {
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rest a quote, replacing any existing one on that side.
+    #[pyo3(signature = (is_bid, price, qty, queue_ahead=0))]
+    pub fn place(&mut self, is_bid: bool, price: i64, qty: i64, queue_ahead: i64) -> PyResult<()> {
+        if price <= 0 || qty <= 0 || queue_ahead < 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "need price > 0, qty > 0, queue_ahead >= 0, got \
+                 price={price} qty={qty} queue_ahead={queue_ahead}"
+            )));
+        }
+        *self.side_mut(is_bid) = Some(RestingOrder {
+            price,
+            qty,
+            ahead: queue_ahead,
+        });
+        Ok(())
+    }
+
+    /// Pull the quote on one side. Returns the quantity that was still resting.
+    pub fn cancel(&mut self, is_bid: bool) -> i64 {
+        self.side_mut(is_bid).take().map_or(0, |o| o.qty)
+    }
+
+    /// Match an incoming trade against the quote on the passive side.
+    /// Returns (filled, remaining) for that quote; (0, 0) if none rests.
+    pub fn on_trade(&mut self, price: i64, qty: i64, aggressor_side: i32) -> PyResult<(i64, i64)> {
+        if qty <= 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "trade qty must be positive, got {qty}"
+            )));
+        }
+        let is_bid = match aggressor_side {
+            1 => false,
+            -1 => true,
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "aggressor_side must be +1 or -1, got {other}"
+                )))
+            }
+        };
+        let Some(order) = self.side_mut(is_bid) else {
+            return Ok((0, 0));
+        };
+        let through = if is_bid {
+            price < order.price
+        } else {
+            price > order.price
+        };
+        let filled = if through {
+            order.ahead = 0;
+            order.qty
+        } else if price == order.price {
+            let past_queue = qty - order.ahead;
+            order.ahead = (-past_queue).max(0);
+            past_queue.clamp(0, order.qty)
+        } else {
+            0
+        };
+        order.qty -= filled;
+        let remaining = order.qty;
+        if remaining == 0 {
+            *self.side_mut(is_bid) = None;
+        }
+        if is_bid {
+            self.filled_bid += filled;
+        } else {
+            self.filled_ask += filled;
+        }
+        Ok((filled, remaining))
+    }
+
+    /// (price, qty, queue_ahead) of the quote on one side, if any.
+    pub fn resting(&self, is_bid: bool) -> Option<(i64, i64, i64)> {
+        let order = if is_bid { self.bid } else { self.ask };
+        order.map(|o| (o.price, o.qty, o.ahead))
+    }
+
+    /// Cumulative (bid, ask) quantity filled since construction or `reset`.
+    pub fn filled(&self) -> (i64, i64) {
+        (self.filled_bid, self.filled_ask)
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl FillSimulator {
+    fn side_mut(&mut self, is_bid: bool) -> &mut Option<RestingOrder> {
+        if is_bid {
+            &mut self.bid
+        } else {
+            &mut self.ask
+        }
+    }
+}
+
+/// Estimated queue state for one of our resting orders.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct QueueEntry {
+    /// Visible volume estimated to be ahead of us.
+    ahead: f64,
+    /// Visible level quantity as last seen (excluding our order).
+    level_qty: f64,
+    /// `LimitOrderBook::traded_at` as of the last `sync`.
+    traded_seen: f64,
+}
+
+/// Queue-position estimates for our resting orders, keyed by (side, price).
+///
+/// An order joins behind the visible level quantity. Trades at the price
+/// consume the volume ahead first. A level decrease not explained by a trade
+/// is a cancel, assumed to be spread evenly through the queue, so `ahead`
+/// shrinks by the same fraction as the level. Increases join behind us.
+///
+/// Feed it directly via `on_trade` / `on_level`, or call `sync(book)` after
+/// applying updates and `on_trade` to a `LimitOrderBook`.
+#[pyclass]
+#[derive(Default)]
+pub struct QueueTracker {
+    orders: HashMap<(bool, u64), QueueEntry>,
+}
+
+#[pymethods]
+impl QueueTracker {
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking an order placed behind `level_qty` of visible volume.
+    /// Use `join_book` instead when the order will be fed via `sync`.
+    /// Re-joining an already tracked price resets its position.
+    pub fn join(&mut self, is_bid: bool, price: f64, level_qty: f64) -> PyResult<()> {
+        if !(level_qty.is_finite() && level_qty >= 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "level_qty must be finite and >= 0, got {level_qty}"
+            )));
+        }
+        self.orders.insert(
+            (is_bid, scale_price(price)),
+            QueueEntry {
+                ahead: level_qty,
+                level_qty,
+                traded_seen: 0.0,
+            },
+        );
+        Ok(())
+    }
+
+    /// `join` behind the level currently resting in `book`, using its traded
+    /// counter as the baseline for later `sync` calls.
+    pub fn join_book(&mut self, is_bid: bool, price: f64, book: &LimitOrderBook) {
+        let key = scale_price(price);
+        let side = if is_bid { &book.bids } else { &book.asks };
+        let level_qty = side.get(&key).copied().unwrap_or(0.0);
+        self.orders.insert(
+            (is_bid, key),
+            QueueEntry {
+                ahead: level_qty,
+                level_qty,
+                traded_seen: book.traded.get(&key).copied().unwrap_or(0.0),
+            },
+        );
+    }
+
+    /// Stop tracking. Returns false if the order was not tracked.
+    pub fn leave(&mut self, is_bid: bool, price: f64) -> bool {
+        self.orders.remove(&(is_bid, scale_price(price))).is_some()
+    }
+
+    /// Volume traded at a tracked price: it comes off the front of the queue.
+    /// Returns false if no order is tracked there.
+    pub fn on_trade(&mut self, is_bid: bool, price: f64, qty: f64) -> bool {
+        match self.orders.get_mut(&(is_bid, scale_price(price))) {
+            Some(entry) => {
+                entry.consume(qty);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// New visible quantity at a tracked price (0 if the level is gone).
+    /// Returns false if no order is tracked there.
+    pub fn on_level(&mut self, is_bid: bool, price: f64, qty: f64) -> bool {
+        match self.orders.get_mut(&(is_bid, scale_price(price))) {
+            Some(entry) => {
+                entry.resize(qty);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pull trades and level changes for every tracked order from `book`.
+    pub fn sync(&mut self, book: &LimitOrderBook) {
+        for (&(is_bid, key), entry) in self.orders.iter_mut() {
+            let side = if is_bid { &book.bids } else { &book.asks };
+            let traded = book.traded.get(&key).copied().unwrap_or(0.0);
+            // The counter restarts when the level is removed and re-created
+            let new_trades = if traded >= entry.traded_seen {
+                traded - entry.traded_seen
+            } else {
+                traded
+            };
+            entry.traded_seen = traded;
+            entry.consume(new_trades);
+            entry.resize(side.get(&key).copied().unwrap_or(0.0));
+        }
+    }
+
+    /// Estimated visible volume ahead of our order at (side, price).
+    pub fn ahead_of_me(&self, is_bid: bool, price: f64) -> PyResult<f64> {
+        self.orders
+            .get(&(is_bid, scale_price(price)))
+            .map(|e| e.ahead)
+            .ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "no order tracked at {} {price}",
+                    if is_bid { "bid" } else { "ask" }
+                ))
+            })
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.orders.len()
+    }
+}
+
+impl QueueEntry {
+    fn consume(&mut self, qty: f64) {
+        if qty > 0.0 {
+            self.ahead = (self.ahead - qty).max(0.0);
+            // Expected shrink, so the matching level update is not a cancel
+            self.level_qty = (self.level_qty - qty).max(0.0);
+        }
+    }
+
+    fn resize(&mut self, qty: f64) {
+        let qty = qty.max(0.0);
+        if qty < self.level_qty {
+            self.ahead *= qty / self.level_qty;
+        }
+        self.ahead = self.ahead.min(qty);
+        self.level_qty = qty;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_ahead_trades_first() {
+        let mut sim = FillSimulator::new();
+        sim.place(true, 100, 5, 10).unwrap();
+        // Sell aggressor at our price eats the queue ahead first
+        assert_eq!(sim.on_trade(100, 8, -1).unwrap(), (0, 5));
+        assert_eq!(sim.resting(true), Some((100, 5, 2)));
+        assert_eq!(sim.on_trade(100, 4, -1).unwrap(), (2, 3));
+        assert_eq!(sim.resting(true), Some((100, 3, 0)));
+        // Buy aggressor never touches the bid
+        assert_eq!(sim.on_trade(100, 50, 1).unwrap(), (0, 0));
+        assert_eq!(sim.on_trade(100, 50, -1).unwrap(), (3, 0));
+        assert_eq!(sim.resting(true), None);
+        assert_eq!(sim.filled(), (5, 0));
+    }
+
+    #[test]
+    fn test_trade_through_fills_whole_quote() {
+        let mut sim = FillSimulator::new();
+        sim.place(false, 101, 7, 100).unwrap();
+        // Above our ask: our level was swept regardless of the queue
+        assert_eq!(sim.on_trade(102, 1, 1).unwrap(), (7, 0));
+        sim.place(false, 101, 7, 0).unwrap();
+        // Below our ask: no fill
+        assert_eq!(sim.on_trade(100, 10, 1).unwrap(), (0, 7));
+        assert_eq!(sim.cancel(false), 7);
+        assert_eq!(sim.cancel(false), 0);
+        assert_eq!(sim.filled(), (0, 7));
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        let mut sim = FillSimulator::new();
+        assert!(sim.place(true, 0, 1, 0).is_err());
+        assert!(sim.place(true, 100, 0, 0).is_err());
+        assert!(sim.place(true, 100, 1, -1).is_err());
+        assert!(sim.on_trade(100, 0, 1).is_err());
+        assert!(sim.on_trade(100, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_queue_advances_on_trades_and_cancels() {
+        let mut q = QueueTracker::new();
+        q.join(true, 100.0, 40.0).unwrap();
+        assert_eq!(q.ahead_of_me(true, 100.0).unwrap(), 40.0);
+        // Trade then the matching level update: only the trade counts
+        assert!(q.on_trade(true, 100.0, 10.0));
+        assert!(q.on_level(true, 100.0, 30.0));
+        assert_eq!(q.ahead_of_me(true, 100.0).unwrap(), 30.0);
+        // Half the level cancels: half the queue ahead goes with it
+        q.on_level(true, 100.0, 15.0);
+        assert_eq!(q.ahead_of_me(true, 100.0).unwrap(), 15.0);
+        // Adds join behind us
+        q.on_level(true, 100.0, 50.0);
+        assert_eq!(q.ahead_of_me(true, 100.0).unwrap(), 15.0);
+        q.on_level(true, 100.0, 0.0);
+        assert_eq!(q.ahead_of_me(true, 100.0).unwrap(), 0.0);
+
+        assert!(!q.on_trade(false, 100.0, 1.0));
+        assert!(q.ahead_of_me(false, 100.0).is_err());
+        assert!(q.leave(true, 100.0));
+        assert_eq!(q.__len__(), 0);
+    }
+
+    #[test]
+    fn test_queue_sync_from_book() {
+        let mut book = LimitOrderBook::new("TEST".to_string(), false);
+        book.update(false, 101.0, 20.0, None);
+        // Earlier trades at the level are not ahead of us
+        book.on_trade(101.0, 7.0);
+        let mut q = QueueTracker::new();
+        q.join_book(false, 101.0, &book);
+
+        book.on_trade(101.0, 5.0);
+        book.update(false, 101.0, 15.0, None);
+        q.sync(&book);
+        assert_eq!(q.ahead_of_me(false, 101.0).unwrap(), 15.0);
+
+        // Cancel from 15 to 12 scales the queue ahead by 12/15
+        book.update(false, 101.0, 12.0, None);
+        q.sync(&book);
+        assert!((q.ahead_of_me(false, 101.0).unwrap() - 12.0).abs() < 1e-12);
+        // Syncing again without changes is a no-op
+        q.sync(&book);
+        assert!((q.ahead_of_me(false, 101.0).unwrap() - 12.0).abs() < 1e-12);
+    }
+}
@@ -1,15 +1,75 @@
+use crate::checksum::crc32;
 use memmap2::MmapMut;
 use pyo3::prelude::*;
 use std::fs::OpenOptions;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
-const HEADER_SIZE: usize = 128; // 64B WriteCursor + 64B ReadCursor (padded)
-const SLOT_SIZE: usize = 64;
+const HEADER_SIZE: usize = 128; // 64B WriteCursor/geometry + 64B reader cursors (padded)
+const DEFAULT_SLOT_SIZE: usize = 64;
+/// Each slot starts with a little-endian u32 payload length.
+const LEN_PREFIX: usize = 4;
+/// With CRC enabled, a little-endian u32 CRC-32 of the payload follows.
+const CRC_LEN: usize = 4;
 
+/// Header word indices (u64).
+const HDR_WRITE: usize = 0;
+const HDR_READ: usize = 1;
+const HDR_CAPACITY: usize = 2;
+const HDR_SLOT_SIZE: usize = 3;
+/// Number of readers parked in `read_blocking`; the writer only issues a
+/// wake syscall when this is non-zero.
+const HDR_WAITERS: usize = 5;
+/// Option bits fixed by the creator; attachers must match.
+const HDR_FLAGS: usize = 6;
+const FLAG_CRC: u64 = 1;
+/// Bitmask of registered broadcast readers.
+const HDR_READER_MASK: usize = 4;
+/// First per-reader cursor; reader `i` uses word `HDR_READER_BASE + i`.
+const HDR_READER_BASE: usize = 8;
+/// Broadcast reader slots that fit in the header.
+const MAX_READERS: usize = HEADER_SIZE / 8 - HDR_READER_BASE;
+
+/// Yields before `read_blocking` parks on the futex.
+const SPIN_YIELDS: u32 = 64;
+/// Sleep between polls where futex wake is unavailable.
+#[cfg(not(all(target_os = "linux", target_endian = "little")))]
+const POLL_SLEEP: Duration = Duration::from_micros(50);
+
+/// `write_checked` status codes exposed to Python.
+const WRITE_OK: u8 = 0;
+const WRITE_FULL: u8 = 1;
+const WRITE_TOO_LARGE: u8 = 2;
+
+/// Single-producer/single-consumer ring of length-prefixed records in shared memory.
+///
+/// Slot layout: `[u32 len][payload; slot_size]`, or `[u32 len][u32 crc32]
+/// [payload; slot_size]` with `crc=True`. The creator stores capacity, slot
+/// size and the CRC flag in the header; attaching with different values is
+/// an error.
+///
+/// With CRC enabled, a record whose payload no longer matches its checksum
+/// raises ValueError on read instead of returning the bytes. `read()` and
+/// `read_as()` skip past it so the next call continues with the following
+/// record; `peek()` leaves it in place until `commit()`.
+///
+/// Cursors are atomics in the shared header: the writer publishes a record
+/// with a Release store of the write cursor after copying the payload, and
+/// the reader copies the payload out before Release-storing the read cursor,
+/// so neither side can observe or overwrite a half-written slot.
+///
+/// Broadcast mode: readers that `register_reader()` get their own cursor in
+/// the header and consume with `read_as(reader_id)`, each seeing every
+/// record written after it registered. While any reader is registered the
+/// writer is bounded by the slowest registered reader instead of the
+/// single `read()` cursor, so don't mix `read()` with registered readers.
 #[pyclass]
 pub struct ShmRingBuffer {
     #[allow(dead_code)]
     mmap: MmapMut,
     capacity: usize,
+    slot_size: usize,
+    crc: bool,
     header_ptr: *mut u64,
     buffer_ptr: *mut u8,
 }
@@ -19,8 +79,25 @@ unsafe impl Send for ShmRingBuffer {}
 #[pymethods]
 impl ShmRingBuffer {
     #[new]
-    pub fn new(name: String, capacity: usize, create: bool) -> PyResult<Self> {
-        let size = HEADER_SIZE + (capacity * SLOT_SIZE);
+    #[pyo3(signature = (name, capacity, create, slot_size=DEFAULT_SLOT_SIZE, crc=false))]
+    pub fn new(
+        name: String,
+        capacity: usize,
+        create: bool,
+        slot_size: usize,
+        crc: bool,
+    ) -> PyResult<Self> {
+        if capacity == 0 || slot_size == 0 || slot_size > u32::MAX as usize {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "capacity and slot_size must be > 0 (slot_size <= u32::MAX), got {capacity}, {slot_size}"
+            )));
+        }
+        let prefix = if crc {
+            LEN_PREFIX + CRC_LEN
+        } else {
+            LEN_PREFIX
+        };
+        let size = HEADER_SIZE + capacity * (prefix + slot_size);
 
         let path = if name.starts_with('/') {
             name
@@ -39,76 +116,782 @@ impl ShmRingBuffer {
         }
 
         let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        if mmap.len() < size {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "shm segment {path} is {} bytes, expected at least {size}",
+                mmap.len()
+            )));
+        }
 
         // Pointers
         let header_ptr = mmap.as_mut_ptr() as *mut u64;
         let buffer_ptr = unsafe { mmap.as_mut_ptr().add(HEADER_SIZE) };
 
-        // Zero header if create
-        if create {
-            unsafe {
-                std::ptr::write_volatile(header_ptr.add(0), 0); // write
-                std::ptr::write_volatile(header_ptr.add(1), 0); // read
+        unsafe {
+            let word = |idx: usize| &*(header_ptr.add(idx) as *const AtomicU64);
+            if create {
+                // Zero cursors and record geometry for attachers
+                word(HDR_CAPACITY).store(capacity as u64, Ordering::Relaxed);
+                word(HDR_SLOT_SIZE).store(slot_size as u64, Ordering::Relaxed);
+                word(HDR_READ).store(0, Ordering::Relaxed);
+                word(HDR_READER_MASK).store(0, Ordering::Relaxed);
+                word(HDR_WAITERS).store(0, Ordering::Relaxed);
+                word(HDR_FLAGS).store(if crc { FLAG_CRC } else { 0 }, Ordering::Relaxed);
+                for i in 0..MAX_READERS {
+                    word(HDR_READER_BASE + i).store(0, Ordering::Relaxed);
+                }
+                word(HDR_WRITE).store(0, Ordering::Release);
+            } else {
+                let hdr_capacity = word(HDR_CAPACITY).load(Ordering::Acquire);
+                let hdr_slot_size = word(HDR_SLOT_SIZE).load(Ordering::Acquire);
+                if hdr_capacity != capacity as u64 || hdr_slot_size != slot_size as u64 {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "shm segment {path} has capacity={hdr_capacity} slot_size={hdr_slot_size}, \
+                         requested capacity={capacity} slot_size={slot_size}"
+                    )));
+                }
+                let hdr_crc = word(HDR_FLAGS).load(Ordering::Acquire) & FLAG_CRC != 0;
+                if hdr_crc != crc {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "shm segment {path} has crc={hdr_crc}, requested crc={crc}"
+                    )));
+                }
             }
         }
 
         Ok(ShmRingBuffer {
             mmap,
             capacity,
+            slot_size,
+            crc,
             header_ptr,
             buffer_ptr,
         })
     }
 
+    /// Write one record. Returns false if the record was not written
+    /// (buffer full or larger than a slot); see `write_checked` for the reason.
     pub fn write(&mut self, data: &[u8]) -> PyResult<bool> {
-        unsafe {
-            let write_cursor = std::ptr::read_volatile(self.header_ptr.add(0));
-            let read_cursor = std::ptr::read_volatile(self.header_ptr.add(1));
+        let (status, _) = self.write_checked(data)?;
+        Ok(status == WRITE_OK)
+    }
 
-            if write_cursor - read_cursor >= self.capacity as u64 {
-                return Ok(false);
-            }
+    /// Write one record and report why it was rejected, if it was.
+    ///
+    /// Returns (status, max_len):
+    ///   status: 0=WRITE_OK, 1=WRITE_FULL, 2=WRITE_TOO_LARGE
+    ///   max_len: largest record a slot accepts (slot_size)
+    ///
+    /// Oversized records are rejected rather than truncated.
+    pub fn write_checked(&mut self, data: &[u8]) -> PyResult<(u8, usize)> {
+        if data.len() > self.slot_size {
+            return Ok((WRITE_TOO_LARGE, self.slot_size));
+        }
 
-            let slot_idx = (write_cursor as usize) % self.capacity;
-            let offset = slot_idx * SLOT_SIZE;
+        // Only this (single) producer moves the write cursor
+        let write_cursor = self.header(HDR_WRITE).load(Ordering::Relaxed);
+        // Acquire: the reader(s) are done with every slot before read_cursor
+        let read_cursor = self.slowest_read_cursor();
 
-            let dest = self.buffer_ptr.add(offset);
+        if write_cursor - read_cursor >= self.capacity as u64 {
+            return Ok((WRITE_FULL, self.slot_size));
+        }
 
-            // Fast copy
-            let len = data.len().min(SLOT_SIZE);
-            std::ptr::copy_nonoverlapping(data.as_ptr(), dest, len);
+        unsafe {
+            let dest = self.slot_ptr(write_cursor);
 
-            // Bump cursor
-            std::ptr::write_volatile(self.header_ptr.add(0), write_cursor + 1);
-            Ok(true)
+            // Length prefix, optional CRC, then payload
+            let len_bytes = (data.len() as u32).to_le_bytes();
+            std::ptr::copy_nonoverlapping(len_bytes.as_ptr(), dest, LEN_PREFIX);
+            if self.crc {
+                let crc_bytes = crc32(data).to_le_bytes();
+                std::ptr::copy_nonoverlapping(crc_bytes.as_ptr(), dest.add(LEN_PREFIX), CRC_LEN);
+            }
+            std::ptr::copy_nonoverlapping(data.as_ptr(), dest.add(self.prefix_len()), data.len());
         }
+
+        // Release: payload is visible before the reader sees the new cursor
+        self.header(HDR_WRITE)
+            .store(write_cursor + 1, Ordering::Release);
+        // Pairs with the fence in `wait_for_write`: either we see the waiter
+        // or it sees the new cursor before sleeping.
+        fence(Ordering::SeqCst);
+        if self.header(HDR_WAITERS).load(Ordering::Relaxed) != 0 {
+            self.wake_readers();
+        }
+        Ok((WRITE_OK, self.slot_size))
     }
 
+    /// Pop the oldest record, returning exactly the bytes that were written.
+    /// Equivalent to `peek()` followed by `commit()`.
     pub fn read<'py>(
         &mut self,
         py: Python<'py>,
     ) -> PyResult<Option<Bound<'py, pyo3::types::PyBytes>>> {
-        unsafe {
-            let write_cursor = std::ptr::read_volatile(self.header_ptr.add(0));
-            let read_cursor = std::ptr::read_volatile(self.header_ptr.add(1));
+        Ok(self
+            .pop_record()?
+            .map(|bytes| pyo3::types::PyBytes::new_bound(py, &bytes)))
+    }
+
+    /// Like `read()`, but waits up to `timeout_ms` for a record to arrive.
+    ///
+    /// Yields briefly, then sleeps until the writer signals (a futex on
+    /// Linux; short polling sleeps elsewhere). The GIL is released while
+    /// waiting. Returns None on timeout.
+    pub fn read_blocking<'py>(
+        &mut self,
+        timeout_ms: u64,
+        py: Python<'py>,
+    ) -> PyResult<Option<Bound<'py, pyo3::types::PyBytes>>> {
+        let timeout = Duration::from_millis(timeout_ms);
+        let record = py.allow_threads(|| self.pop_record_blocking(timeout))?;
+        Ok(record.map(|bytes| pyo3::types::PyBytes::new_bound(py, &bytes)))
+    }
+
+    /// `for record in buf:` yields the records currently available and stops
+    /// when the ring is empty. See `iter_blocking` to wait for more.
+    pub fn __iter__(slf: Bound<'_, Self>) -> ShmRingIter {
+        ShmRingIter {
+            ring: slf.unbind(),
+            timeout: None,
+        }
+    }
+
+    /// Iterator that waits up to `timeout_ms` for each record, as
+    /// `read_blocking` does, and stops at the first timeout.
+    pub fn iter_blocking(slf: Bound<'_, Self>, timeout_ms: u64) -> ShmRingIter {
+        ShmRingIter {
+            ring: slf.unbind(),
+            timeout: Some(Duration::from_millis(timeout_ms)),
+        }
+    }
+
+    /// Return the oldest record without consuming it. Repeated calls return
+    /// the same record until `commit()` is called.
+    pub fn peek<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, pyo3::types::PyBytes>>> {
+        Ok(self
+            .peek_record()?
+            .map(|bytes| pyo3::types::PyBytes::new_bound(py, &bytes)))
+    }
+
+    /// Consume the oldest record (after it has been durably handled).
+    /// Returns false if there was nothing to commit.
+    pub fn commit(&mut self) -> bool {
+        let write_cursor = self.header(HDR_WRITE).load(Ordering::Acquire);
+        let read_cursor = self.header(HDR_READ).load(Ordering::Relaxed);
+        if read_cursor >= write_cursor {
+            return false;
+        }
+        // Release: any prior copy of the slot completes before the writer may reuse it
+        self.header(HDR_READ)
+            .store(read_cursor + 1, Ordering::Release);
+        true
+    }
+
+    /// Register a broadcast reader starting at the current write position.
+    /// Returns its reader id; fails when all reader slots are taken.
+    pub fn register_reader(&self) -> PyResult<usize> {
+        let mask = self.header(HDR_READER_MASK);
+        let mut current = mask.load(Ordering::Acquire);
+        loop {
+            let free = (!current).trailing_zeros() as usize;
+            if free >= MAX_READERS {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "all {MAX_READERS} reader slots are registered"
+                )));
+            }
+            match mask.compare_exchange_weak(
+                current,
+                current | (1 << free),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    // The slot may hold a stale (lower) cursor until this
+                    // store; the writer only ever treats that as less room.
+                    let write_cursor = self.header(HDR_WRITE).load(Ordering::Acquire);
+                    self.header(HDR_READER_BASE + free)
+                        .store(write_cursor, Ordering::Release);
+                    return Ok(free);
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Release a broadcast reader slot so it no longer holds back the writer.
+    pub fn unregister_reader(&self, reader_id: usize) -> PyResult<()> {
+        self.check_reader(reader_id)?;
+        self.header(HDR_READER_MASK)
+            .fetch_and(!(1 << reader_id), Ordering::AcqRel);
+        Ok(())
+    }
+
+    /// Pop the next record for broadcast reader `reader_id`.
+    pub fn read_as<'py>(
+        &self,
+        reader_id: usize,
+        py: Python<'py>,
+    ) -> PyResult<Option<Bound<'py, pyo3::types::PyBytes>>> {
+        Ok(self
+            .pop_record_as(reader_id)?
+            .map(|bytes| pyo3::types::PyBytes::new_bound(py, &bytes)))
+    }
+
+    /// Records written but not yet consumed by broadcast reader `reader_id`.
+    pub fn pending_as(&self, reader_id: usize) -> PyResult<usize> {
+        self.check_reader(reader_id)?;
+        let read_cursor = self
+            .header(HDR_READER_BASE + reader_id)
+            .load(Ordering::Acquire);
+        let write_cursor = self.header(HDR_WRITE).load(Ordering::Acquire);
+        Ok(write_cursor.saturating_sub(read_cursor) as usize)
+    }
+
+    /// Records written but not yet consumed (consumer lag). In broadcast
+    /// mode this is the lag of the slowest registered reader.
+    pub fn pending(&self) -> usize {
+        let read_cursor = self.slowest_read_cursor();
+        let write_cursor = self.header(HDR_WRITE).load(Ordering::Acquire);
+        write_cursor.saturating_sub(read_cursor) as usize
+    }
+
+    /// Total records ever written (raw write cursor).
+    pub fn write_count(&self) -> u64 {
+        self.header(HDR_WRITE).load(Ordering::Acquire)
+    }
+
+    /// Total records ever consumed (raw read cursor).
+    pub fn read_count(&self) -> u64 {
+        self.header(HDR_READ).load(Ordering::Acquire)
+    }
+
+    /// Fraction of slots occupied, in [0, 1]. Writes fail at 1.0.
+    pub fn utilization(&self) -> f64 {
+        self.pending() as f64 / self.capacity as f64
+    }
+
+    /// Maximum record size in bytes for this buffer.
+    #[getter]
+    pub fn get_slot_size(&self) -> usize {
+        self.slot_size
+    }
+
+    #[getter]
+    pub fn get_crc(&self) -> bool {
+        self.crc
+    }
+
+    /// Default maximum record size in bytes.
+    #[classattr]
+    pub const DEFAULT_SLOT_SIZE: usize = DEFAULT_SLOT_SIZE;
+    /// Maximum number of registered broadcast readers.
+    #[classattr]
+    pub const MAX_READERS: usize = MAX_READERS;
+    /// Write status constants for Python consumption.
+    #[classattr]
+    pub const WRITE_OK: u8 = WRITE_OK;
+    #[classattr]
+    pub const WRITE_FULL: u8 = WRITE_FULL;
+    #[classattr]
+    pub const WRITE_TOO_LARGE: u8 = WRITE_TOO_LARGE;
+}
+
+/// Iterator returned by `ShmRingBuffer.__iter__` and `iter_blocking`.
+#[pyclass]
+pub struct ShmRingIter {
+    ring: Py<ShmRingBuffer>,
+    /// None: stop as soon as the ring is empty.
+    timeout: Option<Duration>,
+}
+
+#[pymethods]
+impl ShmRingIter {
+    pub fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    pub fn __next__<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> PyResult<Option<Bound<'py, pyo3::types::PyBytes>>> {
+        let mut guard = self.ring.bind(py).try_borrow_mut()?;
+        let ring: &mut ShmRingBuffer = &mut guard;
+        let record = match self.timeout {
+            None => ring.pop_record()?,
+            Some(timeout) => py.allow_threads(|| ring.pop_record_blocking(timeout))?,
+        };
+        Ok(record.map(|bytes| pyo3::types::PyBytes::new_bound(py, &bytes)))
+    }
+}
+
+impl ShmRingBuffer {
+    #[inline(always)]
+    fn header(&self, idx: usize) -> &AtomicU64 {
+        // Header words are 8-byte aligned (mmap is page aligned) and live
+        // as long as `self.mmap`.
+        unsafe { &*(self.header_ptr.add(idx) as *const AtomicU64) }
+    }
+
+    /// Cursor the writer must not lap: the slowest registered broadcast
+    /// reader, or the single `read()` cursor when none is registered.
+    fn slowest_read_cursor(&self) -> u64 {
+        let mask = self.header(HDR_READER_MASK).load(Ordering::Acquire);
+        if mask == 0 {
+            return self.header(HDR_READ).load(Ordering::Acquire);
+        }
+        (0..MAX_READERS)
+            .filter(|i| mask & (1 << i) != 0)
+            .map(|i| self.header(HDR_READER_BASE + i).load(Ordering::Acquire))
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn check_reader(&self, reader_id: usize) -> PyResult<()> {
+        let mask = self.header(HDR_READER_MASK).load(Ordering::Acquire);
+        if reader_id >= MAX_READERS || mask & (1 << reader_id) == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "reader {reader_id} is not registered"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Copy the next record for `reader_id`, then advance its cursor.
+    fn pop_record_as(&self, reader_id: usize) -> PyResult<Option<Vec<u8>>> {
+        self.check_reader(reader_id)?;
+        let cursor = self.header(HDR_READER_BASE + reader_id);
+        // Acquire: payload written before write_cursor is visible
+        let write_cursor = self.header(HDR_WRITE).load(Ordering::Acquire);
+        let read_cursor = cursor.load(Ordering::Relaxed);
+        if read_cursor >= write_cursor {
+            return Ok(None);
+        }
+        let record = unsafe { self.copy_record(read_cursor) };
+        // Release: the copy completes before the writer may reuse the slot.
+        // A corrupt record is skipped too, so the reader is never stuck on it.
+        cursor.store(read_cursor + 1, Ordering::Release);
+        record.map(Some)
+    }
 
-            if read_cursor >= write_cursor {
+    /// Copy the oldest record into an owned buffer without consuming it.
+    fn peek_record(&self) -> PyResult<Option<Vec<u8>>> {
+        // Acquire: payload written before write_cursor is visible
+        let write_cursor = self.header(HDR_WRITE).load(Ordering::Acquire);
+        let read_cursor = self.header(HDR_READ).load(Ordering::Relaxed);
+
+        if read_cursor >= write_cursor {
+            return Ok(None);
+        }
+
+        unsafe { self.copy_record(read_cursor) }.map(Some)
+    }
+
+    /// `pop_record`, waiting up to `timeout` for the writer.
+    fn pop_record_blocking(&mut self, timeout: Duration) -> PyResult<Option<Vec<u8>>> {
+        let deadline = Instant::now() + timeout;
+        let mut spins = 0;
+        loop {
+            if let Some(bytes) = self.pop_record()? {
+                return Ok(Some(bytes));
+            }
+            let now = Instant::now();
+            if now >= deadline {
                 return Ok(None);
             }
+            if spins < SPIN_YIELDS {
+                spins += 1;
+                std::thread::yield_now();
+            } else {
+                self.wait_for_write(deadline - now);
+            }
+        }
+    }
+
+    /// Sleep until the write cursor moves past our read cursor, a wake
+    /// arrives, or `timeout` elapses. Spurious returns are fine.
+    fn wait_for_write(&self, timeout: Duration) {
+        let waiters = self.header(HDR_WAITERS);
+        waiters.fetch_add(1, Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        let write_cursor = self.header(HDR_WRITE).load(Ordering::Relaxed);
+        if write_cursor <= self.header(HDR_READ).load(Ordering::Relaxed) {
+            self.sleep_on_write(write_cursor, timeout);
+        }
+        waiters.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Block on the low 32 bits of the write cursor while it still equals
+    /// `write_cursor`.
+    #[cfg(all(target_os = "linux", target_endian = "little"))]
+    fn sleep_on_write(&self, write_cursor: u64, timeout: Duration) {
+        let ts = libc::timespec {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_nsec: timeout.subsec_nanos() as libc::c_long,
+        };
+        // Shared (non-private) futex: the writer may be another process
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                self.header_ptr.add(HDR_WRITE) as *const u32,
+                libc::FUTEX_WAIT,
+                write_cursor as u32,
+                &ts as *const libc::timespec,
+            );
+        }
+    }
+
+    #[cfg(not(all(target_os = "linux", target_endian = "little")))]
+    fn sleep_on_write(&self, _write_cursor: u64, timeout: Duration) {
+        std::thread::sleep(timeout.min(POLL_SLEEP));
+    }
+
+    #[cfg(all(target_os = "linux", target_endian = "little"))]
+    fn wake_readers(&self) {
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                self.header_ptr.add(HDR_WRITE) as *const u32,
+                libc::FUTEX_WAKE,
+                libc::c_int::MAX,
+            );
+        }
+    }
 
-            let slot_idx = (read_cursor as usize) % self.capacity;
-            let offset = slot_idx * SLOT_SIZE;
+    #[cfg(not(all(target_os = "linux", target_endian = "little")))]
+    fn wake_readers(&self) {}
 
-            let src = self.buffer_ptr.add(offset);
-            let bytes = std::slice::from_raw_parts(src, SLOT_SIZE);
+    /// Copy the oldest record into an owned buffer, then release its slot.
+    /// A record failing its CRC is released as well, and the error returned.
+    fn pop_record(&mut self) -> PyResult<Option<Vec<u8>>> {
+        let record = self.peek_record();
+        if !matches!(record, Ok(None)) {
+            self.commit();
+        }
+        record
+    }
 
-            // Create Python Bytes (overhead here, but this is proof of concept)
-            let pystruct = pyo3::types::PyBytes::new_bound(py, bytes);
+    /// Bytes before the payload in each slot.
+    #[inline(always)]
+    fn prefix_len(&self) -> usize {
+        if self.crc {
+            LEN_PREFIX + CRC_LEN
+        } else {
+            LEN_PREFIX
+        }
+    }
 
-            // Bump cursor
-            std::ptr::write_volatile(self.header_ptr.add(1), read_cursor + 1);
+    /// Start of the slot for `cursor` (length prefix first).
+    unsafe fn slot_ptr(&self, cursor: u64) -> *mut u8 {
+        let slot_idx = (cursor as usize) % self.capacity;
+        self.buffer_ptr
+            .add(slot_idx * (self.prefix_len() + self.slot_size))
+    }
 
-            Ok(Some(pystruct))
+    /// Copy the payload of the record at `cursor`, clamped to the slot size,
+    /// and check it against the stored CRC when enabled.
+    unsafe fn copy_record(&self, cursor: u64) -> PyResult<Vec<u8>> {
+        let src = self.slot_ptr(cursor);
+        let read_u32 = |p: *const u8| {
+            let mut b = [0u8; 4];
+            std::ptr::copy_nonoverlapping(p, b.as_mut_ptr(), 4);
+            u32::from_le_bytes(b)
+        };
+        let len = (read_u32(src) as usize).min(self.slot_size);
+        let bytes = std::slice::from_raw_parts(src.add(self.prefix_len()), len).to_vec();
+        if self.crc {
+            let stored = read_u32(src.add(LEN_PREFIX));
+            let actual = crc32(&bytes);
+            if stored != actual {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "record {cursor} failed CRC check (stored {stored:#010x}, computed {actual:#010x})"
+                )));
+            }
         }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    const SLOT_SIZE: usize = DEFAULT_SLOT_SIZE;
+
+    fn temp_path() -> String {
+        let f = NamedTempFile::new().unwrap();
+        let path = f.path().to_string_lossy().to_string();
+        // Keep the file alive by leaking (test only)
+        std::mem::forget(f);
+        path
+    }
+
+    fn make_ring(capacity: usize) -> ShmRingBuffer {
+        ShmRingBuffer::new(temp_path(), capacity, true, SLOT_SIZE, false).unwrap()
+    }
+
+    #[test]
+    fn test_write_checked_ok() {
+        let mut ring = make_ring(4);
+        assert_eq!(
+            ring.write_checked(&[1u8; 16]).unwrap(),
+            (WRITE_OK, SLOT_SIZE)
+        );
+        assert_eq!(ring.write_checked(&[1u8; SLOT_SIZE]).unwrap().0, WRITE_OK);
+    }
+
+    #[test]
+    fn test_write_checked_full() {
+        let mut ring = make_ring(2);
+        assert!(ring.write(&[1u8; 8]).unwrap());
+        assert!(ring.write(&[2u8; 8]).unwrap());
+        assert_eq!(
+            ring.write_checked(&[3u8; 8]).unwrap(),
+            (WRITE_FULL, SLOT_SIZE)
+        );
+        assert!(!ring.write(&[3u8; 8]).unwrap());
+    }
+
+    #[test]
+    fn test_write_checked_too_large_not_written() {
+        let mut ring = make_ring(2);
+        let (status, max_len) = ring.write_checked(&[9u8; SLOT_SIZE + 1]).unwrap();
+        assert_eq!(status, WRITE_TOO_LARGE);
+        assert_eq!(max_len, SLOT_SIZE);
+        // Cursor must not advance for a rejected record
+        assert_eq!(ring.header(HDR_WRITE).load(Ordering::Acquire), 0);
+    }
+
+    #[test]
+    fn test_record_round_trips_exact_length() {
+        let mut ring = ShmRingBuffer::new(temp_path(), 4, true, 256, false).unwrap();
+        let payload: Vec<u8> = (0..200u8).collect();
+        assert!(ring.write(&payload).unwrap());
+        assert!(ring.write(b"xy").unwrap());
+        assert_eq!(ring.pop_record().unwrap().unwrap(), payload);
+        assert_eq!(ring.pop_record().unwrap().unwrap(), b"xy");
+        assert_eq!(ring.pop_record().unwrap(), None);
+    }
+
+    #[test]
+    fn test_attach_validates_geometry() {
+        let path = temp_path();
+        let _owner = ShmRingBuffer::new(path.clone(), 8, true, 128, false).unwrap();
+        assert!(ShmRingBuffer::new(path.clone(), 8, false, 128, false).is_ok());
+        assert!(ShmRingBuffer::new(path.clone(), 8, false, 64, false).is_err());
+        assert!(ShmRingBuffer::new(path, 16, false, 128, false).is_err());
+    }
+
+    #[test]
+    fn test_zero_capacity_rejected() {
+        assert!(ShmRingBuffer::new(temp_path(), 0, true, 64, false).is_err());
+    }
+
+    #[test]
+    fn test_concurrent_writer_no_torn_reads() {
+        const N: u64 = 20_000;
+        let path = temp_path();
+        let mut reader = ShmRingBuffer::new(path.clone(), 8, true, 64, false).unwrap();
+        let mut writer = ShmRingBuffer::new(path, 8, false, 64, false).unwrap();
+
+        let handle = std::thread::spawn(move || {
+            let mut seq = 0u64;
+            while seq < N {
+                // Record = seq repeated 8 times; any torn copy breaks the pattern
+                let record = seq.to_le_bytes().repeat(8);
+                if writer.write(&record).unwrap() {
+                    seq += 1;
+                } else {
+                    std::thread::yield_now();
+                }
+            }
+        });
+
+        let mut expected = 0u64;
+        while expected < N {
+            match reader.pop_record().unwrap() {
+                Some(bytes) => {
+                    assert_eq!(bytes.len(), 64);
+                    for chunk in bytes.chunks_exact(8) {
+                        let v = u64::from_le_bytes(chunk.try_into().unwrap());
+                        assert_eq!(v, expected);
+                    }
+                    expected += 1;
+                }
+                None => std::thread::yield_now(),
+            }
+        }
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_peek_does_not_consume_until_commit() {
+        let mut ring = make_ring(4);
+        assert!(!ring.commit());
+        ring.write(b"first").unwrap();
+        ring.write(b"second").unwrap();
+
+        assert_eq!(ring.peek_record().unwrap().unwrap(), b"first");
+        assert_eq!(ring.peek_record().unwrap().unwrap(), b"first");
+        assert!(ring.commit());
+        assert_eq!(ring.peek_record().unwrap().unwrap(), b"second");
+        assert!(ring.commit());
+        assert_eq!(ring.peek_record().unwrap(), None);
+        assert!(!ring.commit());
+    }
+
+    #[test]
+    fn test_uncommitted_record_blocks_writer() {
+        let mut ring = make_ring(1);
+        ring.write(b"a").unwrap();
+        assert!(ring.peek_record().unwrap().is_some());
+        // Slot still owned by the reader until commit
+        assert!(!ring.write(b"b").unwrap());
+        ring.commit();
+        assert!(ring.write(b"b").unwrap());
+    }
+
+    #[test]
+    fn test_occupancy_metrics() {
+        let mut ring = make_ring(4);
+        assert_eq!(ring.pending(), 0);
+        assert_eq!(ring.utilization(), 0.0);
+
+        for i in 0..3u8 {
+            ring.write(&[i]).unwrap();
+        }
+        assert!(ring.commit());
+        assert_eq!(ring.write_count(), 3);
+        assert_eq!(ring.read_count(), 1);
+        assert_eq!(ring.pending(), 2);
+        assert!((ring.utilization() - 0.5).abs() < 1e-12);
+
+        ring.write(b"x").unwrap();
+        ring.write(b"y").unwrap();
+        assert_eq!(ring.pending(), 4);
+        assert_eq!(ring.utilization(), 1.0);
+    }
+
+    #[test]
+    fn test_broadcast_readers_each_see_every_record() {
+        let mut ring = make_ring(4);
+        ring.write(b"before").unwrap();
+        let logger = ring.register_reader().unwrap();
+        let strat = ring.register_reader().unwrap();
+        assert_ne!(logger, strat);
+
+        ring.write(b"a").unwrap();
+        ring.write(b"b").unwrap();
+        // Readers start at registration time
+        assert_eq!(ring.pop_record_as(logger).unwrap().unwrap(), b"a");
+        assert_eq!(ring.pop_record_as(strat).unwrap().unwrap(), b"a");
+        assert_eq!(ring.pop_record_as(strat).unwrap().unwrap(), b"b");
+        assert_eq!(ring.pop_record_as(strat).unwrap(), None);
+        assert_eq!(ring.pending_as(logger).unwrap(), 1);
+        assert_eq!(ring.pending_as(strat).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_writer_bounded_by_slowest_reader() {
+        let mut ring = make_ring(2);
+        let slow = ring.register_reader().unwrap();
+        let fast = ring.register_reader().unwrap();
+        assert!(ring.write(b"1").unwrap());
+        assert!(ring.write(b"2").unwrap());
+        ring.pop_record_as(fast).unwrap();
+        ring.pop_record_as(fast).unwrap();
+        // Fast reader is caught up but the slow one still holds both slots
+        assert!(!ring.write(b"3").unwrap());
+        assert_eq!(ring.pending(), 2);
+        ring.pop_record_as(slow).unwrap();
+        assert!(ring.write(b"3").unwrap());
+
+        // Dropping the slow reader frees the writer
+        ring.unregister_reader(slow).unwrap();
+        assert!(ring.pop_record_as(slow).is_err());
+        ring.pop_record_as(fast).unwrap();
+        assert!(ring.write(b"4").unwrap());
+        assert!(ring.write(b"5").unwrap());
+    }
+
+    #[test]
+    fn test_reader_slots_limited_and_shared_across_handles() {
+        let path = temp_path();
+        let owner = ShmRingBuffer::new(path.clone(), 4, true, 64, false).unwrap();
+        let attached = ShmRingBuffer::new(path, 4, false, 64, false).unwrap();
+        let ids: Vec<usize> = (0..MAX_READERS)
+            .map(|i| {
+                let ring = if i % 2 == 0 { &owner } else { &attached };
+                ring.register_reader().unwrap()
+            })
+            .collect();
+        assert_eq!(ids, (0..MAX_READERS).collect::<Vec<_>>());
+        assert!(owner.register_reader().is_err());
+        attached.unregister_reader(3).unwrap();
+        assert_eq!(owner.register_reader().unwrap(), 3);
+        assert!(owner.pending_as(MAX_READERS).is_err());
+    }
+
+    #[test]
+    fn test_read_blocking_times_out_when_empty() {
+        let mut ring = make_ring(4);
+        let start = Instant::now();
+        assert_eq!(
+            ring.pop_record_blocking(Duration::from_millis(20)).unwrap(),
+            None
+        );
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(ring.header(HDR_WAITERS).load(Ordering::Acquire), 0);
+
+        ring.write(b"ready").unwrap();
+        assert_eq!(
+            ring.pop_record_blocking(Duration::from_millis(0))
+                .unwrap()
+                .unwrap(),
+            b"ready"
+        );
+    }
+
+    #[test]
+    fn test_read_blocking_woken_by_writer() {
+        let path = temp_path();
+        let mut reader = ShmRingBuffer::new(path.clone(), 4, true, 64, false).unwrap();
+        let mut writer = ShmRingBuffer::new(path, 4, false, 64, false).unwrap();
+
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(30));
+            assert!(writer.write(b"wake").unwrap());
+        });
+        let start = Instant::now();
+        let got = reader.pop_record_blocking(Duration::from_secs(10));
+        assert_eq!(got.unwrap().unwrap(), b"wake");
+        // Woken promptly rather than running out the timeout
+        assert!(start.elapsed() < Duration::from_secs(5));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_crc_detects_corrupted_record() {
+        let path = temp_path();
+        let mut ring = ShmRingBuffer::new(path.clone(), 4, true, SLOT_SIZE, true).unwrap();
+        ring.write(b"good").unwrap();
+        ring.write(b"flip").unwrap();
+        ring.write(b"next").unwrap();
+        assert_eq!(ring.pop_record().unwrap().unwrap(), b"good");
+
+        // Corrupt one payload byte of the second record in place
+        unsafe {
+            *ring.slot_ptr(1).add(LEN_PREFIX + CRC_LEN) ^= 0xFF;
+        }
+        assert!(ring.peek_record().is_err());
+        assert!(ring.peek_record().is_err()); // peek leaves it in place
+        assert!(ring.pop_record().is_err());
+        // The corrupt record was skipped
+        assert_eq!(ring.pop_record().unwrap().unwrap(), b"next");
+
+        // Attachers must agree on the CRC flag
+        assert!(ShmRingBuffer::new(path.clone(), 4, false, SLOT_SIZE, false).is_err());
+        assert!(ShmRingBuffer::new(path, 4, false, SLOT_SIZE, true).is_ok());
     }
 }
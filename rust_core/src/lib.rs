@@ -2,6 +2,7 @@ use pyo3::prelude::*;
 
 mod alpha;
 mod alpha_flow; // New module
+mod alpha_impact;
 mod alpha_markov; // New module
 mod alpha_meta; // Meta Alpha module
 mod alpha_ofi;
@@ -11,13 +12,16 @@ mod alpha_transient;
 mod backtest_kernels;
 mod book_state;
 mod bus;
+mod checksum;
 mod circuit_breaker;
 mod columnar_buffer;
 mod dedup;
+mod execution;
 mod exposure;
 mod fast_lob;
 mod feature;
 mod feature_engine;
+mod fill_sim;
 pub mod ipc;
 mod lob;
 mod metrics_sampler;
@@ -27,6 +31,7 @@ mod positions;
 mod record_mapper;
 pub mod risk;
 mod risk_validator;
+mod session;
 mod shm_snapshot;
 mod storm_guard;
 mod strategy; // New Strategy
@@ -38,11 +43,16 @@ mod md_event_frame;
 mod normalizer_feature_fused;
 mod normalizer_lob_fused;
 mod typed_ring;
+mod vol;
+mod warmup;
 
 /// The HFT Platform Rust Core Module
 #[pymodule]
 fn rust_core(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<lob::LimitOrderBook>()?;
+    m.add_class::<lob::PriceLevel>()?;
+    m.add_class::<lob::IntLimitOrderBook>()?;
+    m.add_class::<lob::Bbo>()?;
     m.add_class::<alpha::AlphaDepthSlope>()?;
     m.add_class::<alpha_pressure::AlphaRegimePressure>()?;
     m.add_class::<alpha_reversal::AlphaRegimeReversal>()?;
@@ -50,16 +60,26 @@ fn rust_core(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<alpha_transient::AlphaTransientReprice>()?;
     m.add_class::<alpha_markov::AlphaMarkovTransition>()?;
     m.add_class::<alpha_flow::MatchedFilterTradeFlow>()?;
+    m.add_function(wrap_pyfunction!(alpha_flow::classify_trade, m)?)?;
+    m.add_class::<alpha_impact::KyleLambda>()?;
     m.add_class::<alpha_meta::MetaAlpha>()?;
+    m.add_class::<alpha_meta::RollingCorrelation>()?;
+    m.add_class::<vol::VolMonitor>()?;
+    m.add_class::<vol::VolRegime>()?;
+    m.add_class::<vol::RealizedVol>()?;
     m.add_class::<bus::EventBus>()?;
     m.add_class::<bus::FastRingBuffer>()?;
     m.add_class::<bus::FastTickRingBuffer>()?;
     m.add_class::<bus::FastBidAskRingBuffer>()?;
     m.add_class::<bus::FastLOBStatsRingBuffer>()?;
     m.add_class::<feature::LobFeatureKernelV1>()?;
+    m.add_class::<feature::LobFeatureKernelV2>()?;
+    m.add_class::<feature::SpreadKernel>()?;
+    m.add_class::<feature::FeatureKernelManager>()?;
     m.add_class::<ipc::ShmRingBuffer>()?;
     m.add_class::<shm_snapshot::ShmSnapshotTable>()?;
     m.add_class::<risk::FastGate>()?;
+    m.add_class::<session::SessionCalendar>()?;
     m.add_class::<risk_validator::RustRiskValidator>()?;
     m.add_class::<exposure::RustExposureStore>()?;
     m.add_class::<circuit_breaker::RustCircuitBreaker>()?;
@@ -67,11 +87,14 @@ fn rust_core(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(timeutil::coerce_ns_int, m)?)?;
     m.add_function(wrap_pyfunction!(timeutil::coerce_ns_float, m)?)?;
     m.add_function(wrap_pyfunction!(fast_lob::scale_book, m)?)?;
+    m.add_function(wrap_pyfunction!(fast_lob::scale_book_int, m)?)?;
     m.add_function(wrap_pyfunction!(fast_lob::scale_book_seq, m)?)?;
     m.add_function(wrap_pyfunction!(fast_lob::scale_book_pair, m)?)?;
     m.add_function(wrap_pyfunction!(fast_lob::scale_book_pair_stats, m)?)?;
     m.add_function(wrap_pyfunction!(fast_lob::scale_book_pair_stats_np, m)?)?;
     m.add_function(wrap_pyfunction!(fast_lob::compute_book_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(fast_lob::compute_book_vwap, m)?)?;
+    m.add_function(wrap_pyfunction!(fast_lob::implied_mid, m)?)?;
     m.add_function(wrap_pyfunction!(fast_lob::get_field, m)?)?;
     m.add_function(wrap_pyfunction!(fast_lob::normalize_tick_tuple, m)?)?;
     m.add_function(wrap_pyfunction!(fast_lob::normalize_bidask_tuple, m)?)?;
@@ -80,9 +103,18 @@ fn rust_core(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
         fast_lob::normalize_bidask_tuple_with_synth,
         m
     )?)?;
+    m.add_function(wrap_pyfunction!(fast_lob::encode_bidask, m)?)?;
+    m.add_function(wrap_pyfunction!(fast_lob::decode_bidask, m)?)?;
+    m.add_function(wrap_pyfunction!(fast_lob::round_to_tick, m)?)?;
     m.add_class::<strategy::AlphaStrategy>()?;
+    m.add_class::<strategy::BivariateHawkes>()?;
     m.add_class::<positions::RustPositionTracker>()?;
+    m.add_class::<positions::EquityMonitor>()?;
+    m.add_class::<execution::ExecutionScheduler>()?;
+    m.add_class::<fill_sim::FillSimulator>()?;
+    m.add_class::<fill_sim::QueueTracker>()?;
     m.add_class::<storm_guard::RustStormGuardValidator>()?;
+    m.add_class::<storm_guard::MessageRateMonitor>()?;
     m.add_function(wrap_pyfunction!(record_mapper::to_ch_price_scaled, m)?)?;
     m.add_function(wrap_pyfunction!(record_mapper::map_tick_record, m)?)?;
     m.add_function(wrap_pyfunction!(record_mapper::map_bidask_record, m)?)?;
@@ -1,7 +1,15 @@
+use crate::checksum::crc32;
+use numpy::{PyArray2, PyArrayMethods};
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+/// Fixed-point scale used for the BTreeMap price keys.
+pub const PRICE_SCALE: f64 = 10000.0;
+
+/// (price, delta_qty) per changed level, best first.
+pub type LevelDeltas = Vec<(f64, f64)>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass]
 pub struct PriceLevel {
@@ -11,6 +19,15 @@ pub struct PriceLevel {
     pub quantity: f64,
 }
 
+/// Read access to the touch, shared by the float and integer books so the
+/// alpha factors can run on either.
+pub trait BookView {
+    /// Best bid as (scaled price key, quantity).
+    fn best_bid_level(&self) -> Option<(u64, f64)>;
+    /// Best ask as (scaled price key, quantity).
+    fn best_ask_level(&self) -> Option<(u64, f64)>;
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass]
 pub struct LimitOrderBook {
@@ -18,32 +35,178 @@ pub struct LimitOrderBook {
     pub symbol: String,
     pub bids: BTreeMap<u64, f64>, // Price(scaled) -> Qty
     pub asks: BTreeMap<u64, f64>,
+    /// Volume traded at each resting price since the level was created.
+    #[serde(default)]
+    pub traded: BTreeMap<u64, f64>,
+    /// When set, `update` drops opposite-side levels crossed by the new level.
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub enforce_uncrossed: bool,
+    /// Sequence number `update` expects next; None until the first `seq`.
+    #[pyo3(get)]
+    #[serde(default)]
+    pub expected_next_seq: Option<u64>,
+    /// Set when an `update` skips ahead of `expected_next_seq`, i.e. a
+    /// message was dropped; stays set until `reset_seq`.
+    #[pyo3(get)]
+    #[serde(default)]
+    pub seq_gap: bool,
 }
 
 #[pymethods]
 impl LimitOrderBook {
     #[new]
-    pub fn new(symbol: String) -> Self {
+    #[pyo3(signature = (symbol, enforce_uncrossed=false))]
+    pub fn new(symbol: String, enforce_uncrossed: bool) -> Self {
         Self {
             symbol,
             bids: BTreeMap::new(),
             asks: BTreeMap::new(),
+            traded: BTreeMap::new(),
+            enforce_uncrossed,
+            expected_next_seq: None,
+            seq_gap: false,
         }
     }
 
-    pub fn update(&mut self, is_bid: bool, price: f64, quantity: f64) {
-        let scaled_price = (price * 10000.0) as u64; // Simple scaling for key
-        let book = if is_bid {
-            &mut self.bids
+    /// Set or remove (quantity <= 0) one level. Removing a level also
+    /// resets its traded-volume counter.
+    ///
+    /// Returns the number of opposite-side levels removed because the new
+    /// level crossed them; always 0 unless `enforce_uncrossed` is set.
+    /// The incoming level is treated as the fresher information.
+    ///
+    /// With `seq`, an update older than `expected_next_seq` is ignored
+    /// (returns 0) and one beyond it is applied but sets `seq_gap`. After
+    /// `u64::MAX` there is no next number, so tracking re-anchors on the
+    /// following sequenced update.
+    #[pyo3(signature = (is_bid, price, quantity, seq=None))]
+    pub fn update(&mut self, is_bid: bool, price: f64, quantity: f64, seq: Option<u64>) -> usize {
+        if let Some(seq) = seq {
+            match self.expected_next_seq {
+                Some(expected) if seq < expected => return 0,
+                Some(expected) if seq > expected => self.seq_gap = true,
+                _ => {}
+            }
+            self.expected_next_seq = seq.checked_add(1);
+        }
+        let scaled_price = scale_price(price);
+        let (book, opposite) = if is_bid {
+            (&mut self.bids, &mut self.asks)
         } else {
-            &mut self.asks
+            (&mut self.asks, &mut self.bids)
         };
 
         if quantity <= 0.0 {
             book.remove(&scaled_price);
+            self.traded.remove(&scaled_price);
+            return 0;
+        }
+        book.insert(scaled_price, quantity);
+
+        if !self.enforce_uncrossed {
+            return 0;
+        }
+        let crossed = take_crossed(opposite, is_bid, scaled_price);
+        for p in &crossed {
+            self.traded.remove(p);
+        }
+        crossed.len()
+    }
+
+    /// Apply `(is_bid, price, qty)` updates in order, with `update`
+    /// semantics, in a single call. Returns the total number of crossed
+    /// opposite-side levels removed.
+    pub fn apply_batch(&mut self, updates: Vec<(bool, f64, f64)>) -> usize {
+        updates
+            .into_iter()
+            .map(|(is_bid, price, qty)| self.update(is_bid, price, qty, None))
+            .sum()
+    }
+
+    /// Clear `seq_gap` after resyncing (e.g. from a snapshot) and set the
+    /// next expected sequence number; None accepts whatever comes next.
+    #[pyo3(signature = (next_seq=None))]
+    pub fn reset_seq(&mut self, next_seq: Option<u64>) {
+        self.expected_next_seq = next_seq;
+        self.seq_gap = false;
+    }
+
+    /// Replace one whole side with a snapshot of `prices`/`qtys`.
+    ///
+    /// The side is rebuilt from the snapshot in one pass; levels with
+    /// quantity <= 0 are skipped and a repeated price keeps its last
+    /// quantity. Traded counters survive only for prices still resting.
+    /// With `enforce_uncrossed`, opposite levels crossed by the new best are
+    /// dropped and their count returned, as in `update`.
+    pub fn replace_side(
+        &mut self,
+        is_bid: bool,
+        prices: Vec<f64>,
+        qtys: Vec<f64>,
+    ) -> PyResult<usize> {
+        if prices.len() != qtys.len() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "prices/qtys length mismatch: {} vs {}",
+                prices.len(),
+                qtys.len()
+            )));
+        }
+        let side: BTreeMap<u64, f64> = prices
+            .iter()
+            .zip(&qtys)
+            .filter(|(_, q)| **q > 0.0)
+            .map(|(p, q)| (scale_price(*p), *q))
+            .collect();
+        let best = if is_bid {
+            side.keys().next_back().copied()
+        } else {
+            side.keys().next().copied()
+        };
+        let (book, opposite) = if is_bid {
+            (&mut self.bids, &mut self.asks)
         } else {
-            book.insert(scaled_price, quantity);
+            (&mut self.asks, &mut self.bids)
+        };
+        *book = side;
+
+        let mut n_crossed = 0;
+        if let (true, Some(best)) = (self.enforce_uncrossed, best) {
+            n_crossed = take_crossed(opposite, is_bid, best).len();
+        }
+        let (bids, asks) = (&self.bids, &self.asks);
+        self.traded
+            .retain(|p, _| bids.contains_key(p) || asks.contains_key(p));
+        Ok(n_crossed)
+    }
+
+    /// Accrue `qty` of traded volume at `price`.
+    /// Returns false (and records nothing) if no level rests at that price.
+    pub fn on_trade(&mut self, price: f64, qty: f64) -> bool {
+        let scaled_price = scale_price(price);
+        if qty <= 0.0
+            || !(self.bids.contains_key(&scaled_price) || self.asks.contains_key(&scaled_price))
+        {
+            return false;
         }
+        *self.traded.entry(scaled_price).or_insert(0.0) += qty;
+        true
+    }
+
+    /// Cumulative traded volume at `price` since its level was created.
+    pub fn traded_at(&self, price: f64) -> f64 {
+        let scaled_price = scale_price(price);
+        self.traded.get(&scaled_price).copied().unwrap_or(0.0)
+    }
+
+    /// (resting_qty, traded) at `price`, or None if no level rests there.
+    pub fn level_detail(&self, price: f64) -> Option<(f64, f64)> {
+        let scaled_price = scale_price(price);
+        let qty = self
+            .bids
+            .get(&scaled_price)
+            .or_else(|| self.asks.get(&scaled_price))?;
+        Some((*qty, self.traded_at(price)))
     }
 
     pub fn top_bids(&self, depth: usize) -> Vec<PriceLevel> {
@@ -52,7 +215,7 @@ impl LimitOrderBook {
             .rev()
             .take(depth)
             .map(|(p, q)| PriceLevel {
-                price: *p as f64 / 10000.0,
+                price: *p as f64 / PRICE_SCALE,
                 quantity: *q,
             })
             .collect()
@@ -63,9 +226,887 @@ impl LimitOrderBook {
             .iter()
             .take(depth)
             .map(|(p, q)| PriceLevel {
-                price: *p as f64 / 10000.0,
+                price: *p as f64 / PRICE_SCALE,
                 quantity: *q,
             })
             .collect()
     }
+
+    /// Top `depth` levels per side as two `[depth, 2]` float arrays of
+    /// (price, qty), best first, zero-padded past the last resting level.
+    pub fn depth_arrays(
+        &self,
+        py: Python<'_>,
+        depth: usize,
+    ) -> (Py<PyArray2<f64>>, Py<PyArray2<f64>>) {
+        let bids = PyArray2::<f64>::zeros_bound(py, [depth, 2], false);
+        let asks = PyArray2::<f64>::zeros_bound(py, [depth, 2], false);
+        {
+            let mut bid_view = unsafe { bids.as_array_mut() };
+            for (i, (p, q)) in self.bids.iter().rev().take(depth).enumerate() {
+                bid_view[(i, 0)] = *p as f64 / PRICE_SCALE;
+                bid_view[(i, 1)] = *q;
+            }
+            let mut ask_view = unsafe { asks.as_array_mut() };
+            for (i, (p, q)) in self.asks.iter().take(depth).enumerate() {
+                ask_view[(i, 0)] = *p as f64 / PRICE_SCALE;
+                ask_view[(i, 1)] = *q;
+            }
+        }
+        (bids.unbind(), asks.unbind())
+    }
+
+    /// Highest bid price, or None if the bid side is empty.
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids
+            .keys()
+            .next_back()
+            .map(|p| *p as f64 / PRICE_SCALE)
+    }
+
+    /// Lowest ask price, or None if the ask side is empty.
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks.keys().next().map(|p| *p as f64 / PRICE_SCALE)
+    }
+
+    /// (best_bid + best_ask) / 2, or None unless both sides are present.
+    pub fn mid(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(b), Some(a)) => Some((b + a) / 2.0),
+            _ => None,
+        }
+    }
+
+    /// best_ask - best_bid without allocating; None unless both sides are
+    /// present, like `mid()` (negative if the book is crossed).
+    pub fn spread(&self) -> Option<f64> {
+        let (&b, &a) = (self.bids.keys().next_back()?, self.asks.keys().next()?);
+        Some((a as f64 - b as f64) / PRICE_SCALE)
+    }
+
+    /// Total quantity over the best `n` bid levels, without allocating.
+    pub fn bid_depth(&self, n: usize) -> f64 {
+        self.bids.values().rev().take(n).sum()
+    }
+
+    /// Total quantity over the best `n` ask levels, without allocating.
+    pub fn ask_depth(&self, n: usize) -> f64 {
+        self.asks.values().take(n).sum()
+    }
+
+    /// Depth imbalance over the top `levels` per side with level `i` (0 = best)
+    /// weighted by `decay^i`: `(B - A) / (B + A)`, in [-1, 1]. `decay = 1`
+    /// gives flat depth imbalance; 0.0 when both weighted sums are zero.
+    #[pyo3(signature = (decay, levels=5))]
+    pub fn weighted_imbalance(&self, decay: f64, levels: usize) -> PyResult<f64> {
+        check_decay(decay)?;
+        let bid = decayed_sum(self.bids.values().rev().take(levels).copied(), decay);
+        let ask = decayed_sum(self.asks.values().take(levels).copied(), decay);
+        Ok(normalized_imbalance(bid, ask))
+    }
+
+    /// L1 microprice: (best_ask*bid_qty + best_bid*ask_qty) / (bid_qty + ask_qty).
+    /// None unless both sides are present.
+    pub fn microprice(&self) -> Option<f64> {
+        let (&bp, &bq) = self.bids.iter().next_back()?;
+        let (&ap, &aq) = self.asks.iter().next()?;
+        Some(l1_microprice(
+            bp as f64 / PRICE_SCALE,
+            bq,
+            ap as f64 / PRICE_SCALE,
+            aq,
+        ))
+    }
+
+    /// Mid of the size-weighted average prices of the top `levels` on each
+    /// side. Equals `mid()` for `levels == 1`. None unless both sides are present.
+    pub fn weighted_mid(&self, levels: usize) -> Option<f64> {
+        let bid_vwap = side_vwap(self.bids.iter().rev().take(levels.max(1)))?;
+        let ask_vwap = side_vwap(self.asks.iter().take(levels.max(1)))?;
+        Some((bid_vwap + ask_vwap) / 2.0)
+    }
+
+    /// Kraken-style CRC32 over the top 10 asks (best first) followed by the
+    /// top 10 bids (best first). Each level contributes
+    /// `checksum_field(price, price_decimals) + checksum_field(qty, qty_decimals)`.
+    /// Prices are held to 4 decimals (`PRICE_SCALE`), so venues quoting finer
+    /// ticks cannot be matched.
+    #[pyo3(signature = (price_decimals=4, qty_decimals=8))]
+    pub fn crc32_top10(&self, price_decimals: usize, qty_decimals: usize) -> u32 {
+        let asks = self.asks.iter().take(10);
+        let bids = self.bids.iter().rev().take(10);
+        let levels = asks.chain(bids).map(|(p, q)| (*p as f64 / PRICE_SCALE, *q));
+        crc32(checksum_payload(levels, price_decimals, qty_decimals).as_bytes())
+    }
+
+    /// True if `crc32_top10` matches the venue-published checksum.
+    /// A mismatch means the local book has desynced and should be resynced.
+    #[pyo3(signature = (expected, price_decimals=4, qty_decimals=8))]
+    pub fn verify_crc32(&self, expected: u32, price_decimals: usize, qty_decimals: usize) -> bool {
+        self.crc32_top10(price_decimals, qty_decimals) == expected
+    }
+
+    /// Every resting level on one side in price order, best first,
+    /// as (price, quantity). Unlike `top_bids`/`top_asks` there is no depth cap.
+    pub fn levels(&self, is_bid: bool) -> Vec<(f64, f64)> {
+        let to_level = |(p, q): (&u64, &f64)| (*p as f64 / PRICE_SCALE, *q);
+        if is_bid {
+            self.bids.iter().rev().map(to_level).collect()
+        } else {
+            self.asks.iter().map(to_level).collect()
+        }
+    }
+
+    /// Per-level quantity changes from `prev` to `self`.
+    ///
+    /// Returns (bid_deltas, ask_deltas), each a list of (price, delta_qty)
+    /// in best-first order. Added levels report +qty, removed levels -qty,
+    /// resized levels the difference; unchanged levels are omitted.
+    pub fn diff(&self, prev: &LimitOrderBook) -> (LevelDeltas, LevelDeltas) {
+        let mut bid_deltas = diff_side(&self.bids, &prev.bids);
+        bid_deltas.reverse();
+        let ask_deltas = diff_side(&self.asks, &prev.asks);
+        (bid_deltas, ask_deltas)
+    }
+}
+
+impl BookView for LimitOrderBook {
+    fn best_bid_level(&self) -> Option<(u64, f64)> {
+        self.bids.iter().next_back().map(|(p, q)| (*p, *q))
+    }
+
+    fn best_ask_level(&self) -> Option<(u64, f64)> {
+        self.asks.iter().next().map(|(p, q)| (*p, *q))
+    }
+}
+
+/// Order book with integer (lot) quantities, for venues where fractional
+/// size is impossible. Prices use the same scaled `u64` keys as
+/// `LimitOrderBook`; quantities never pass through `f64`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[pyclass]
+pub struct IntLimitOrderBook {
+    #[pyo3(get)]
+    pub symbol: String,
+    pub bids: BTreeMap<u64, i64>, // Price(scaled) -> Lots
+    pub asks: BTreeMap<u64, i64>,
+    /// When set, `update` drops opposite-side levels crossed by the new level.
+    #[pyo3(get, set)]
+    #[serde(default)]
+    pub enforce_uncrossed: bool,
+}
+
+#[pymethods]
+impl IntLimitOrderBook {
+    #[new]
+    #[pyo3(signature = (symbol, enforce_uncrossed=false))]
+    pub fn new(symbol: String, enforce_uncrossed: bool) -> Self {
+        Self {
+            symbol,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            enforce_uncrossed,
+        }
+    }
+
+    /// Set or remove (quantity <= 0) one level. Same crossing semantics
+    /// and return value as `LimitOrderBook.update`.
+    pub fn update(&mut self, is_bid: bool, price: f64, quantity: i64) -> usize {
+        let scaled_price = scale_price(price);
+        let (book, opposite) = if is_bid {
+            (&mut self.bids, &mut self.asks)
+        } else {
+            (&mut self.asks, &mut self.bids)
+        };
+
+        if quantity <= 0 {
+            book.remove(&scaled_price);
+            return 0;
+        }
+        book.insert(scaled_price, quantity);
+
+        if !self.enforce_uncrossed {
+            return 0;
+        }
+        take_crossed(opposite, is_bid, scaled_price).len()
+    }
+
+    /// Top `depth` bids as (price, lots), best first.
+    pub fn top_bids(&self, depth: usize) -> Vec<(f64, i64)> {
+        self.bids
+            .iter()
+            .rev()
+            .take(depth)
+            .map(|(p, q)| (*p as f64 / PRICE_SCALE, *q))
+            .collect()
+    }
+
+    /// Top `depth` asks as (price, lots), best first.
+    pub fn top_asks(&self, depth: usize) -> Vec<(f64, i64)> {
+        self.asks
+            .iter()
+            .take(depth)
+            .map(|(p, q)| (*p as f64 / PRICE_SCALE, *q))
+            .collect()
+    }
+
+    /// Every resting level on one side, best first, as (price, lots).
+    pub fn levels(&self, is_bid: bool) -> Vec<(f64, i64)> {
+        if is_bid {
+            self.top_bids(usize::MAX)
+        } else {
+            self.top_asks(usize::MAX)
+        }
+    }
+
+    /// Highest bid price, or None if the bid side is empty.
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids
+            .keys()
+            .next_back()
+            .map(|p| *p as f64 / PRICE_SCALE)
+    }
+
+    /// Lowest ask price, or None if the ask side is empty.
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks.keys().next().map(|p| *p as f64 / PRICE_SCALE)
+    }
+
+    /// (best_bid + best_ask) / 2, or None unless both sides are present.
+    pub fn mid(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(b), Some(a)) => Some((b + a) / 2.0),
+            _ => None,
+        }
+    }
+
+    /// Level-decayed depth imbalance; see `LimitOrderBook.weighted_imbalance`.
+    #[pyo3(signature = (decay, levels=5))]
+    pub fn weighted_imbalance(&self, decay: f64, levels: usize) -> PyResult<f64> {
+        check_decay(decay)?;
+        let bid = decayed_sum(
+            self.bids.values().rev().take(levels).map(|q| *q as f64),
+            decay,
+        );
+        let ask = decayed_sum(self.asks.values().take(levels).map(|q| *q as f64), decay);
+        Ok(normalized_imbalance(bid, ask))
+    }
+}
+
+impl BookView for IntLimitOrderBook {
+    fn best_bid_level(&self) -> Option<(u64, f64)> {
+        self.bids.iter().next_back().map(|(p, q)| (*p, *q as f64))
+    }
+
+    fn best_ask_level(&self) -> Option<(u64, f64)> {
+        self.asks.iter().next().map(|(p, q)| (*p, *q as f64))
+    }
+}
+
+/// Top of book only: best bid/ask price and quantity, for alphas that never
+/// look past L1 and don't need `LimitOrderBook`'s maps.
+///
+/// A side is present when its price is > 0; the accessors return 0.0 unless
+/// both sides are present, except `spread`, which returns None so a
+/// one-sided book is not mistaken for a locked one.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[pyclass]
+pub struct Bbo {
+    #[pyo3(get)]
+    pub bid_px: f64,
+    #[pyo3(get)]
+    pub bid_qty: f64,
+    #[pyo3(get)]
+    pub ask_px: f64,
+    #[pyo3(get)]
+    pub ask_qty: f64,
+}
+
+#[pymethods]
+impl Bbo {
+    #[new]
+    #[pyo3(signature = (bid_px=0.0, bid_qty=0.0, ask_px=0.0, ask_qty=0.0))]
+    pub fn new(bid_px: f64, bid_qty: f64, ask_px: f64, ask_qty: f64) -> Self {
+        Self {
+            bid_px,
+            bid_qty,
+            ask_px,
+            ask_qty,
+        }
+    }
+
+    /// Replace the whole top of book.
+    #[inline(always)]
+    pub fn update(&mut self, bid_px: f64, bid_qty: f64, ask_px: f64, ask_qty: f64) {
+        self.bid_px = bid_px;
+        self.bid_qty = bid_qty;
+        self.ask_px = ask_px;
+        self.ask_qty = ask_qty;
+    }
+
+    /// True when both sides carry a price.
+    #[inline(always)]
+    pub fn is_valid(&self) -> bool {
+        self.bid_px > 0.0 && self.ask_px > 0.0
+    }
+
+    pub fn mid(&self) -> f64 {
+        if !self.is_valid() {
+            return 0.0;
+        }
+        (self.bid_px + self.ask_px) / 2.0
+    }
+
+    pub fn spread(&self) -> Option<f64> {
+        self.is_valid().then_some(self.ask_px - self.bid_px)
+    }
+
+    /// (bid_qty - ask_qty) / (bid_qty + ask_qty), in [-1, 1].
+    pub fn imbalance(&self) -> f64 {
+        let total = self.bid_qty + self.ask_qty;
+        if !self.is_valid() || total <= 0.0 {
+            return 0.0;
+        }
+        (self.bid_qty - self.ask_qty) / total
+    }
+
+    /// Same formula as `LimitOrderBook.microprice`.
+    pub fn microprice(&self) -> f64 {
+        if !self.is_valid() {
+            return 0.0;
+        }
+        l1_microprice(self.bid_px, self.bid_qty, self.ask_px, self.ask_qty)
+    }
+}
+
+impl BookView for Bbo {
+    fn best_bid_level(&self) -> Option<(u64, f64)> {
+        (self.bid_px > 0.0).then(|| (scale_price(self.bid_px), self.bid_qty))
+    }
+
+    fn best_ask_level(&self) -> Option<(u64, f64)> {
+        (self.ask_px > 0.0).then(|| (scale_price(self.ask_px), self.ask_qty))
+    }
+}
+
+/// L1 microprice `(ask*bid_qty + bid*ask_qty) / (bid_qty + ask_qty)`,
+/// falling back to the mid when both quantities are zero.
+#[inline(always)]
+pub fn l1_microprice(bid: f64, bid_qty: f64, ask: f64, ask_qty: f64) -> f64 {
+    let total = bid_qty + ask_qty;
+    if total <= 0.0 {
+        return (bid + ask) / 2.0;
+    }
+    (ask * bid_qty + bid * ask_qty) / total
+}
+
+/// Remove opposite-side levels crossed by a new level at `scaled_price`:
+/// asks at or below a new bid / bids at or above a new ask are stale.
+/// Returns the removed price keys.
+fn take_crossed<Q>(opposite: &mut BTreeMap<u64, Q>, is_bid: bool, scaled_price: u64) -> Vec<u64> {
+    let crossed: Vec<u64> = if is_bid {
+        opposite.range(..=scaled_price).map(|(p, _)| *p).collect()
+    } else {
+        opposite.range(scaled_price..).map(|(p, _)| *p).collect()
+    };
+    for p in &crossed {
+        opposite.remove(p);
+    }
+    crossed
+}
+
+/// Format one checksum field the way Kraken specifies: render with
+/// `decimals` fraction digits, drop the decimal point, then strip leading
+/// zeros (e.g. `0.05000000` → `5000000`). Adapt here for other venues.
+pub fn checksum_field(value: f64, decimals: usize) -> String {
+    let rendered = format!("{:.*}", decimals, value).replace('.', "");
+    let trimmed = rendered.trim_start_matches('0');
+    if trimmed.is_empty() {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Concatenated checksum fields for `(price, qty)` levels, in the order given.
+fn checksum_payload(
+    levels: impl Iterator<Item = (f64, f64)>,
+    price_decimals: usize,
+    qty_decimals: usize,
+) -> String {
+    let mut payload = String::with_capacity(512);
+    for (p, q) in levels {
+        payload.push_str(&checksum_field(p, price_decimals));
+        payload.push_str(&checksum_field(q, qty_decimals));
+    }
+    payload
+}
+
+/// Size-weighted average real price over the given levels.
+fn side_vwap<'a>(levels: impl Iterator<Item = (&'a u64, &'a f64)>) -> Option<f64> {
+    let mut notional = 0.0;
+    let mut qty = 0.0;
+    for (p, q) in levels {
+        notional += (*p as f64 / PRICE_SCALE) * q;
+        qty += q;
+    }
+    if qty > 0.0 {
+        Some(notional / qty)
+    } else {
+        None
+    }
+}
+
+fn check_decay(decay: f64) -> PyResult<()> {
+    if decay > 0.0 && decay <= 1.0 {
+        Ok(())
+    } else {
+        Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "decay must be in (0, 1], got {decay}"
+        )))
+    }
+}
+
+/// `sum(q_i * decay^i)` over best-first quantities.
+#[inline(always)]
+fn decayed_sum(qtys: impl Iterator<Item = f64>, decay: f64) -> f64 {
+    let mut weight = 1.0;
+    let mut total = 0.0;
+    for q in qtys {
+        total += q * weight;
+        weight *= decay;
+    }
+    total
+}
+
+#[inline(always)]
+fn normalized_imbalance(bid: f64, ask: f64) -> f64 {
+    let total = bid + ask;
+    if total > 0.0 {
+        (bid - ask) / total
+    } else {
+        0.0
+    }
+}
+
+/// Book key for a real price (simple truncating scale).
+#[inline(always)]
+pub(crate) fn scale_price(price: f64) -> u64 {
+    (price * PRICE_SCALE) as u64
+}
+
+/// Merge two price-sorted sides into ascending (price, cur - prev) deltas.
+fn diff_side(cur: &BTreeMap<u64, f64>, prev: &BTreeMap<u64, f64>) -> LevelDeltas {
+    let mut out = Vec::new();
+    let mut cur_it = cur.iter().peekable();
+    let mut prev_it = prev.iter().peekable();
+    loop {
+        let (price, delta) = match (cur_it.peek(), prev_it.peek()) {
+            (Some(&(&cp, &cq)), Some(&(&pp, &pq))) => {
+                if cp < pp {
+                    cur_it.next();
+                    (cp, cq)
+                } else if pp < cp {
+                    prev_it.next();
+                    (pp, -pq)
+                } else {
+                    cur_it.next();
+                    prev_it.next();
+                    (cp, cq - pq)
+                }
+            }
+            (Some(&(&cp, &cq)), None) => {
+                cur_it.next();
+                (cp, cq)
+            }
+            (None, Some(&(&pp, &pq))) => {
+                prev_it.next();
+                (pp, -pq)
+            }
+            (None, None) => break,
+        };
+        if delta != 0.0 {
+            out.push((price as f64 / PRICE_SCALE, delta));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_best_prices_and_mid() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), false);
+        assert_eq!(lob.mid(), None);
+        lob.update(true, 99.5, 5.0, None);
+        lob.update(true, 100.0, 1.0, None);
+        assert_eq!(lob.best_bid(), Some(100.0));
+        assert_eq!(lob.best_ask(), None);
+        assert_eq!(lob.mid(), None);
+        lob.update(false, 101.0, 2.0, None);
+        assert_eq!(lob.best_ask(), Some(101.0));
+        assert_eq!(lob.mid(), Some(100.5));
+    }
+
+    #[test]
+    fn test_crossing_level_kept_without_enforcement() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), false);
+        lob.update(false, 101.0, 2.0, None);
+        assert_eq!(lob.update(true, 102.0, 1.0, None), 0);
+        assert_eq!(lob.best_ask(), Some(101.0)); // crossed book left as-is
+    }
+
+    #[test]
+    fn test_enforce_uncrossed_heals_book() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), true);
+        lob.update(true, 100.0, 1.0, None);
+        lob.update(false, 101.0, 2.0, None);
+        lob.update(false, 102.0, 3.0, None);
+        lob.update(false, 103.0, 4.0, None);
+
+        // New bid at 102 crosses asks 101 and 102
+        assert_eq!(lob.update(true, 102.0, 5.0, None), 2);
+        assert_eq!(lob.best_bid(), Some(102.0));
+        assert_eq!(lob.best_ask(), Some(103.0));
+
+        // New ask at 99 crosses both bids
+        assert_eq!(lob.update(false, 99.0, 1.0, None), 2);
+        assert_eq!(lob.best_bid(), None);
+        assert_eq!(lob.best_ask(), Some(99.0));
+    }
+
+    #[test]
+    fn test_enforce_uncrossed_delete_never_cleans() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), true);
+        lob.update(false, 101.0, 2.0, None);
+        assert_eq!(lob.update(true, 105.0, 0.0, None), 0);
+        assert_eq!(lob.best_ask(), Some(101.0));
+    }
+
+    #[test]
+    fn test_seq_gap_and_stale_updates() {
+        let mut lob = LimitOrderBook::new("TEST".to_string(), false);
+        lob.update(true, 100.0, 1.0, Some(10));
+        lob.update(true, 100.0, 2.0, Some(11));
+        assert_eq!((lob.expected_next_seq, lob.seq_gap), (Some(12), false));
+        // Stale/duplicate: ignored
+        lob.update(true, 100.0, 9.0, Some(11));
+        assert_eq!(lob.bids.get(&scale_price(100.0)), Some(&2.0));
+        // 12 and 13 dropped: applied, but flagged
+        lob.update(true, 100.0, 3.0, Some(14));
+        assert_eq!(lob.bids.get(&scale_price(100.0)), Some(&3.0));
+        assert_eq!((lob.expected_next_seq, lob.seq_gap), (Some(15), true));
+        // Unsequenced updates leave the tracking alone
+        lob.update(false, 101.0, 1.0, None);
+        assert_eq!(lob.expected_next_seq, Some(15));
+
+        lob.reset_seq(Some(100));
+        assert!(!lob.seq_gap);
+        lob.update(true, 100.0, 4.0, Some(99));
+        assert_eq!(lob.bids.get(&scale_price(100.0)), Some(&3.0));
+        lob.update(true, 100.0, 4.0, Some(100));
+        assert!(!lob.seq_gap);
+
+        // u64::MAX has no successor: the next sequenced update re-anchors
+        lob.update(true, 100.0, 5.0, Some(u64::MAX));
+        assert_eq!(lob.expected_next_seq, None);
+        lob.update(true, 100.0, 6.0, Some(0));
+        assert_eq!(lob.expected_next_seq, Some(1));
+        assert_eq!(lob.bids.get(&scale_price(100.0)), Some(&6.0));
+    }
+
+    #[test]
+    fn test_traded_accrues_and_resets_on_removal() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), false);
+        lob.update(true, 100.0, 10.0, None);
+        assert!(lob.on_trade(100.0, 3.0));
+        assert!(lob.on_trade(100.0, 2.0));
+        assert!(!lob.on_trade(99.0, 1.0)); // no level there
+        assert_eq!(lob.traded_at(100.0), 5.0);
+
+        // Resize keeps the counter
+        lob.update(true, 100.0, 5.0, None);
+        assert_eq!(lob.level_detail(100.0), Some((5.0, 5.0)));
+
+        // Level removed → counter reset, even if re-added
+        lob.update(true, 100.0, 0.0, None);
+        assert_eq!(lob.level_detail(100.0), None);
+        lob.update(true, 100.0, 4.0, None);
+        assert_eq!(lob.level_detail(100.0), Some((4.0, 0.0)));
+    }
+
+    #[test]
+    fn test_microprice() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), false);
+        assert_eq!(lob.microprice(), None);
+        lob.update(true, 100.0, 200.0, None);
+        lob.update(false, 102.0, 100.0, None);
+        // (102*200 + 100*100) / 300 = 101.333...
+        let mp = lob.microprice().unwrap();
+        assert!((mp - 304.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weighted_mid() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), false);
+        lob.update(true, 100.0, 1.0, None);
+        lob.update(true, 99.0, 3.0, None);
+        lob.update(false, 101.0, 1.0, None);
+        lob.update(false, 102.0, 1.0, None);
+        assert_eq!(lob.weighted_mid(1), lob.mid());
+        // bid vwap = (100 + 297) / 4 = 99.25, ask vwap = 101.5
+        let wm = lob.weighted_mid(2).unwrap();
+        assert!((wm - (99.25 + 101.5) / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_checksum_field_format() {
+        assert_eq!(checksum_field(0.05, 8), "5000000");
+        assert_eq!(checksum_field(5541.3, 1), "55413");
+        assert_eq!(checksum_field(0.0, 2), "0");
+    }
+
+    #[test]
+    fn test_checksum_matches_published_kraken_snapshot() {
+        // Book-checksum example from Kraken's WebSocket v1 docs ("book-10",
+        // XBT/USD); note the bid ladder skips 0.04985.
+        let asks = [
+            0.05005, 0.05010, 0.05015, 0.05020, 0.05025, 0.05030, 0.05035, 0.05040, 0.05045,
+            0.05050,
+        ];
+        let bids = [
+            0.05000, 0.04995, 0.04990, 0.04980, 0.04975, 0.04970, 0.04965, 0.04960, 0.04955,
+            0.04950,
+        ];
+        let levels = asks.iter().chain(bids.iter()).map(|p| (*p, 0.000005));
+        let payload = checksum_payload(levels, 5, 8);
+        assert!(payload.starts_with("50055005010500"));
+        assert_eq!(crc32(payload.as_bytes()), 974_947_235);
+    }
+
+    #[test]
+    fn test_crc32_top10_asks_then_bids() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), false);
+        lob.update(true, 100.5, 2.0, None);
+        lob.update(false, 101.0, 0.5, None);
+        // ask "1010" + "5", then bid "1005" + "20"
+        let expected = crc32(b"10105100520");
+        assert_eq!(lob.crc32_top10(1, 1), expected);
+        assert!(lob.verify_crc32(expected, 1, 1));
+        lob.update(true, 100.5, 3.0, None);
+        assert!(!lob.verify_crc32(expected, 1, 1));
+    }
+
+    #[test]
+    fn test_levels_best_first() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), false);
+        lob.update(true, 99.0, 5.0, None);
+        lob.update(true, 100.0, 1.0, None);
+        lob.update(false, 102.0, 3.0, None);
+        lob.update(false, 101.0, 2.0, None);
+
+        assert_eq!(lob.levels(true), vec![(100.0, 1.0), (99.0, 5.0)]);
+        assert_eq!(lob.levels(false), vec![(101.0, 2.0), (102.0, 3.0)]);
+    }
+
+    #[test]
+    fn test_diff_added_removed_resized() {
+        let mut prev = LimitOrderBook::new("SYM".to_string(), false);
+        prev.update(true, 100.0, 5.0, None);
+        prev.update(true, 99.0, 3.0, None);
+        prev.update(false, 101.0, 4.0, None);
+
+        let mut cur = prev.clone();
+        cur.update(true, 100.0, 7.0, None); // resized +2
+        cur.update(true, 99.0, 0.0, None); // removed -3
+        cur.update(true, 98.0, 1.0, None); // added +1
+        cur.update(false, 102.0, 6.0, None); // added +6
+
+        let (bids, asks) = cur.diff(&prev);
+        assert_eq!(bids, vec![(100.0, 2.0), (99.0, -3.0), (98.0, 1.0)]);
+        assert_eq!(asks, vec![(102.0, 6.0)]);
+    }
+
+    #[test]
+    fn test_diff_identical_books_empty() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), false);
+        lob.update(true, 100.0, 5.0, None);
+        let (bids, asks) = lob.diff(&lob.clone());
+        assert!(bids.is_empty());
+        assert!(asks.is_empty());
+    }
+
+    #[test]
+    fn test_levels_empty_side() {
+        let lob = LimitOrderBook::new("SYM".to_string(), false);
+        assert!(lob.levels(true).is_empty());
+    }
+
+    #[test]
+    fn test_int_book_update_and_top() {
+        let mut lob = IntLimitOrderBook::new("SYM".to_string(), false);
+        lob.update(true, 99.5, 5);
+        lob.update(true, 100.0, 1);
+        lob.update(false, 101.0, 2);
+        lob.update(false, 102.0, 7);
+        assert_eq!(lob.top_bids(1), vec![(100.0, 1)]);
+        assert_eq!(lob.levels(false), vec![(101.0, 2), (102.0, 7)]);
+        assert_eq!(lob.mid(), Some(100.5));
+        lob.update(true, 100.0, 0);
+        assert_eq!(lob.best_bid(), Some(99.5));
+    }
+
+    #[test]
+    fn test_int_book_enforce_uncrossed() {
+        let mut lob = IntLimitOrderBook::new("SYM".to_string(), true);
+        lob.update(false, 101.0, 2);
+        lob.update(false, 102.0, 3);
+        assert_eq!(lob.update(true, 101.0, 4), 1);
+        assert_eq!(lob.best_ask(), Some(102.0));
+    }
+
+    #[test]
+    fn test_book_view_matches_across_books() {
+        let mut f = LimitOrderBook::new("SYM".to_string(), false);
+        let mut i = IntLimitOrderBook::new("SYM".to_string(), false);
+        for (is_bid, p, q) in [(true, 100.0, 3), (true, 99.0, 8), (false, 101.0, 5)] {
+            f.update(is_bid, p, q as f64, None);
+            i.update(is_bid, p, q);
+        }
+        assert_eq!(f.best_bid_level(), i.best_bid_level());
+        assert_eq!(f.best_ask_level(), i.best_ask_level());
+        assert_eq!(i.best_bid_level(), Some((1_000_000, 3.0)));
+    }
+
+    #[test]
+    fn test_apply_batch_matches_sequential_updates() {
+        let updates = vec![
+            (true, 100.0, 1.0),
+            (false, 101.0, 2.0),
+            (true, 99.5, 3.0),
+            (true, 100.0, 0.0),
+            (false, 100.5, 4.0),
+        ];
+        let mut seq = LimitOrderBook::new("SYM".to_string(), false);
+        for &(b, p, q) in &updates {
+            seq.update(b, p, q, None);
+        }
+        let mut batch = LimitOrderBook::new("SYM".to_string(), false);
+        assert_eq!(batch.apply_batch(updates), 0);
+        assert_eq!(batch.levels(true), seq.levels(true));
+        assert_eq!(batch.levels(false), seq.levels(false));
+
+        let mut enforced = LimitOrderBook::new("SYM".to_string(), true);
+        let n = enforced.apply_batch(vec![
+            (false, 101.0, 1.0),
+            (false, 102.0, 1.0),
+            (true, 102.0, 1.0),
+        ]);
+        assert_eq!(n, 2);
+    }
+
+    #[test]
+    fn test_replace_side_snapshot() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), false);
+        lob.update(true, 100.0, 1.0, None);
+        lob.update(true, 99.0, 2.0, None);
+        lob.update(false, 101.0, 5.0, None);
+        lob.on_trade(100.0, 1.0);
+        lob.on_trade(99.0, 1.0);
+
+        let n = lob
+            .replace_side(true, vec![98.0, 100.0, 97.0], vec![4.0, 6.0, 0.0])
+            .unwrap();
+        assert_eq!(n, 0);
+        assert_eq!(lob.levels(true), vec![(100.0, 6.0), (98.0, 4.0)]);
+        assert_eq!(lob.levels(false), vec![(101.0, 5.0)]);
+        // 100 still rests so keeps its counter; 99 is gone
+        assert_eq!(lob.traded_at(100.0), 1.0);
+        assert_eq!(lob.traded_at(99.0), 0.0);
+
+        assert!(lob.replace_side(false, vec![1.0], vec![]).is_err());
+
+        lob.enforce_uncrossed = true;
+        assert_eq!(lob.replace_side(false, vec![99.0], vec![1.0]).unwrap(), 1);
+        assert_eq!(lob.levels(true), vec![(98.0, 4.0)]);
+    }
+
+    #[test]
+    fn test_spread_and_depth_scalars() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), false);
+        assert_eq!(lob.spread(), None);
+        assert_eq!(lob.bid_depth(5), 0.0);
+        lob.update(true, 100.0, 1.0, None);
+        lob.update(true, 99.5, 2.0, None);
+        lob.update(true, 99.0, 4.0, None);
+        // One-sided
+        assert_eq!(lob.spread(), None);
+        lob.update(false, 100.25, 3.0, None);
+        lob.update(false, 101.0, 5.0, None);
+
+        assert_eq!(lob.spread(), Some(0.25));
+        assert_eq!(lob.bid_depth(2), 3.0);
+        assert_eq!(lob.bid_depth(10), 7.0);
+        assert_eq!(lob.ask_depth(1), 3.0);
+        assert_eq!(lob.ask_depth(0), 0.0);
+        let top: f64 = lob.top_asks(2).iter().map(|l| l.quantity).sum();
+        assert_eq!(lob.ask_depth(2), top);
+    }
+
+    #[test]
+    fn test_weighted_imbalance() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), false);
+        assert_eq!(lob.weighted_imbalance(0.5, 5).unwrap(), 0.0);
+        lob.update(true, 100.0, 1.0, None);
+        lob.update(true, 99.0, 8.0, None);
+        lob.update(false, 101.0, 3.0, None);
+        lob.update(false, 102.0, 2.0, None);
+
+        // decay 1: flat depth (9 vs 5)
+        let flat = lob.weighted_imbalance(1.0, 5).unwrap();
+        assert!((flat - 4.0 / 14.0).abs() < 1e-12);
+        // decay 0.5: bid 1 + 4 = 5, ask 3 + 1 = 4
+        let w = lob.weighted_imbalance(0.5, 5).unwrap();
+        assert!((w - 1.0 / 9.0).abs() < 1e-12);
+        // One level: L1 imbalance
+        let l1 = lob.weighted_imbalance(0.5, 1).unwrap();
+        assert!((l1 + 0.5).abs() < 1e-12);
+        assert!(lob.weighted_imbalance(0.0, 5).is_err());
+        assert!(lob.weighted_imbalance(1.5, 5).is_err());
+
+        let mut ib = IntLimitOrderBook::new("SYM".to_string(), false);
+        ib.update(true, 100.0, 1);
+        ib.update(true, 99.0, 8);
+        ib.update(false, 101.0, 3);
+        ib.update(false, 102.0, 2);
+        assert_eq!(ib.weighted_imbalance(0.5, 5).unwrap(), w);
+    }
+
+    #[test]
+    fn test_bbo_matches_book_l1() {
+        let mut lob = LimitOrderBook::new("SYM".to_string(), false);
+        lob.update(true, 100.0, 3.0, None);
+        lob.update(true, 99.0, 50.0, None);
+        lob.update(false, 100.5, 1.0, None);
+        let mut bbo = Bbo::default();
+        assert_eq!(bbo.mid(), 0.0);
+        assert_eq!(bbo.microprice(), 0.0);
+        bbo.update(100.0, 3.0, 100.5, 1.0);
+
+        assert_eq!(Some(bbo.mid()), lob.mid());
+        assert_eq!(bbo.spread(), lob.spread());
+        assert_eq!(Some(bbo.microprice()), lob.microprice());
+        assert_eq!(bbo.imbalance(), 0.5);
+        assert_eq!(bbo.best_bid_level(), lob.best_bid_level());
+        assert_eq!(bbo.best_ask_level(), lob.best_ask_level());
+
+        // One-sided
+        bbo.update(100.0, 3.0, 0.0, 0.0);
+        assert!(!bbo.is_valid());
+        assert_eq!(bbo.spread(), None);
+        assert_eq!(bbo.imbalance(), 0.0);
+        assert_eq!(bbo.best_ask_level(), None);
+    }
 }
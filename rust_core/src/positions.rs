@@ -1,13 +1,85 @@
 use pyo3::prelude::*;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+
+/// Default cap on recorded realized-PnL points per key.
+const DEFAULT_HISTORY_MAX_LEN: usize = 100_000;
+
+/// Rounding applied when re-averaging the entry price on an increasing fill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AvgRounding {
+    /// Round half to even (matches Python `round()` / `py_round_i64`).
+    HalfEven,
+    /// Default: `(2*total + net) // (2*net)`, as in Python `Position.update`.
+    HalfUp,
+    /// Integer division, truncating toward zero.
+    Truncate,
+}
+
+impl AvgRounding {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "half_even" => Ok(Self::HalfEven),
+            "half_up" => Ok(Self::HalfUp),
+            "truncate" => Ok(Self::Truncate),
+            other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown rounding_mode '{other}' (expected 'half_even', 'half_up' or 'truncate')"
+            ))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::HalfEven => "half_even",
+            Self::HalfUp => "half_up",
+            Self::Truncate => "truncate",
+        }
+    }
+
+    #[inline(always)]
+    fn div(&self, num: i64, den: i64) -> i64 {
+        match self {
+            Self::HalfEven => div_round_half_even(num, den),
+            Self::HalfUp => (2 * num + den) / (2 * den),
+            Self::Truncate => num / den,
+        }
+    }
+}
+
+/// Integer `num / den` rounded half to even. `den` must be non-zero.
+#[inline(always)]
+fn div_round_half_even(num: i64, den: i64) -> i64 {
+    let q = num / den;
+    let r = (num % den).unsigned_abs();
+    if r == 0 {
+        return q;
+    }
+    let d = den.unsigned_abs();
+    let away = if (num < 0) != (den < 0) { -1 } else { 1 };
+    // Compare 2r against d without overflowing
+    if r > d - r || (r == d - r && q % 2 != 0) {
+        q + away
+    } else {
+        q
+    }
+}
 
 /// Internal position state — all values in fixed-point (same scale as fill.price).
+#[derive(Clone, Serialize, Deserialize)]
 struct PositionState {
     net_qty: i64,
     avg_price_scaled: i64,
     realized_pnl_scaled: i64,
     fees_scaled: i64,
     last_update_ts: i64,
+    /// Realized PnL and closed quantity produced by the most recent fill.
+    #[serde(default)]
+    last_realized_delta: i64,
+    #[serde(default)]
+    last_close_qty: i64,
+    /// Open lots (signed_qty, price_scaled, ts), oldest first. FIFO mode only.
+    lots: VecDeque<(i64, i64, i64)>,
 }
 
 impl PositionState {
@@ -18,7 +90,143 @@ impl PositionState {
             realized_pnl_scaled: 0,
             fees_scaled: 0,
             last_update_ts: 0,
+            last_realized_delta: 0,
+            last_close_qty: 0,
+            lots: VecDeque::new(),
+        }
+    }
+
+    /// FIFO fill: close opposite lots oldest-first (realizing per-lot PnL),
+    /// push any remainder as a new lot, then re-average over the open lots.
+    /// Returns the (realized PnL, closed quantity) of this fill.
+    fn apply_fifo(
+        &mut self,
+        signed_fill_qty: i64,
+        price_scaled: i64,
+        ts: i64,
+        multiplier: i64,
+        rounding: AvgRounding,
+    ) -> (i64, i64) {
+        let mut realized = 0;
+        let mut closed = 0;
+        let mut remaining = signed_fill_qty;
+        while remaining != 0 {
+            match self.lots.front_mut() {
+                Some(lot) if (lot.0 > 0) != (remaining > 0) => {
+                    let close_qty = lot.0.abs().min(remaining.abs());
+                    let pnl = if lot.0 > 0 {
+                        // Selling a long lot
+                        (price_scaled - lot.1) * close_qty * multiplier
+                    } else {
+                        // Covering a short lot
+                        (lot.1 - price_scaled) * close_qty * multiplier
+                    };
+                    realized += pnl;
+                    closed += close_qty;
+                    lot.0 -= lot.0.signum() * close_qty;
+                    remaining -= remaining.signum() * close_qty;
+                    if lot.0 == 0 {
+                        self.lots.pop_front();
+                    }
+                }
+                _ => {
+                    self.lots.push_back((remaining, price_scaled, ts));
+                    remaining = 0;
+                }
+            }
+        }
+
+        self.net_qty += signed_fill_qty;
+        self.avg_price_scaled = if self.net_qty == 0 {
+            0
+        } else {
+            let total_val: i64 = self.lots.iter().map(|lot| lot.0 * lot.1).sum();
+            rounding.div(total_val, self.net_qty)
+        };
+        self.realized_pnl_scaled += realized;
+        (realized, closed)
+    }
+}
+
+fn check_price_scale(price_scale: i64) -> PyResult<()> {
+    if price_scale > 0 {
+        Ok(())
+    } else {
+        Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "price_scale must be positive, got {price_scale}"
+        )))
+    }
+}
+
+/// Notional fee `notional * rate_e8 / 1e8`, rounded half to even.
+/// `rate_e8` is the fee rate in units of 1e-8 (1 bp = 10_000).
+#[inline(always)]
+fn bps_fee(notional: i128, rate_e8: i64) -> i64 {
+    const DEN: i128 = 100_000_000;
+    let num = notional * rate_e8 as i128;
+    let q = num / DEN;
+    let r = (num % DEN).abs();
+    let away = if num < 0 { -1 } else { 1 };
+    let fee = if 2 * r > DEN || (2 * r == DEN && q % 2 != 0) {
+        q + away
+    } else {
+        q
+    };
+    fee as i64
+}
+
+/// Split "{account}:{strategy}:{symbol}" into its parts. Only the first two
+/// colons separate fields, so a symbol containing ':' is kept whole.
+fn split_key(key: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = key.splitn(3, ':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(account), Some(strategy), Some(symbol)) => Some((account, strategy, symbol)),
+        _ => None,
+    }
+}
+
+/// Bounded (ts, realized_pnl_scaled) trajectory for one position key.
+struct PnlHistory {
+    points: VecDeque<(i64, i64)>,
+    max_len: usize,
+}
+
+/// On-disk format version written by `save`.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// JSON document written by `RustPositionTracker.save`.
+#[derive(Serialize, Deserialize)]
+struct TrackerSnapshot<'a> {
+    version: u32,
+    /// Fixed-point price scale the caller declared at save time.
+    price_scale: i64,
+    rounding_mode: String,
+    fifo: bool,
+    positions: Cow<'a, HashMap<String, PositionState>>,
+}
+
+impl TrackerSnapshot<'static> {
+    /// Read `path` and check it was written with `price_scale`.
+    fn read(path: &str, price_scale: i64) -> PyResult<Self> {
+        let bytes = std::fs::read(path)?;
+        let snap: Self = serde_json::from_slice(&bytes).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "invalid position snapshot {path}: {e}"
+            ))
+        })?;
+        if snap.version != SNAPSHOT_VERSION {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unsupported position snapshot version {} (expected {SNAPSHOT_VERSION})",
+                snap.version
+            )));
+        }
+        if snap.price_scale != price_scale {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "position snapshot price_scale {} does not match {price_scale}",
+                snap.price_scale
+            )));
         }
+        Ok(snap)
     }
 }
 
@@ -26,25 +234,135 @@ impl PositionState {
 ///
 /// All arithmetic uses i64 fixed-point values at the same scale as the
 /// incoming fill prices.  No float conversion is ever performed.
+///
+/// Realized-PnL history is opt-in per key (`enable_history`) so the default
+/// hot path stays allocation-free.
 #[pyclass]
 pub struct RustPositionTracker {
     positions: HashMap<String, PositionState>,
+    history: HashMap<String, PnlHistory>,
+    rounding: AvgRounding,
+    fifo: bool,
+    /// Fee rate in 1e-8 units when fees are charged on notional.
+    fee_rate_e8: Option<i64>,
 }
 
 impl Default for RustPositionTracker {
     fn default() -> Self {
-        Self::new()
+        Self::with_mode(AvgRounding::HalfUp, false)
     }
 }
 
-#[pymethods]
 impl RustPositionTracker {
-    #[new]
-    pub fn new() -> Self {
+    fn with_mode(rounding: AvgRounding, fifo: bool) -> Self {
         Self {
             positions: HashMap::new(),
+            history: HashMap::new(),
+            rounding,
+            fifo,
+            fee_rate_e8: None,
         }
     }
+}
+
+#[pymethods]
+impl RustPositionTracker {
+    /// `rounding_mode` controls the weighted-average price update:
+    /// "half_up" (default, matches the Python `Position` reference),
+    /// "half_even" or "truncate".
+    ///
+    /// With `fifo=True` each opening fill is kept as a lot and closing fills
+    /// match oldest-first, so realized PnL is per lot rather than against a
+    /// single average price. `avg_price_scaled` is then the average over the
+    /// remaining open lots.
+    ///
+    /// With `fee_bps` set, each fill's fee is computed as
+    /// `price_scaled * qty * multiplier * fee_bps / 1e4` (rounded half to
+    /// even, resolved to 1e-4 bp) instead of taking the `fee` argument;
+    /// `tax` is still added as given.
+    #[new]
+    #[pyo3(signature = (rounding_mode="half_up", fifo=false, fee_bps=None))]
+    pub fn new(rounding_mode: &str, fifo: bool, fee_bps: Option<f64>) -> PyResult<Self> {
+        let mut tracker = Self::with_mode(AvgRounding::parse(rounding_mode)?, fifo);
+        if let Some(bps) = fee_bps {
+            if !(bps.is_finite() && bps >= 0.0) {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "fee_bps must be non-negative, got {bps}"
+                )));
+            }
+            tracker.fee_rate_e8 = Some((bps * 1e4).round() as i64);
+        }
+        Ok(tracker)
+    }
+
+    /// Notional fee rate in bps, or None when fees are passed explicitly.
+    #[getter]
+    pub fn get_fee_bps(&self) -> Option<f64> {
+        self.fee_rate_e8.map(|r| r as f64 / 1e4)
+    }
+
+    #[getter]
+    pub fn get_fifo(&self) -> bool {
+        self.fifo
+    }
+
+    #[getter]
+    pub fn get_rounding_mode(&self) -> &'static str {
+        self.rounding.name()
+    }
+
+    /// Write every position to `path` as JSON, tagged with `price_scale`
+    /// (the fixed-point scale of the prices fed to `update`) and the
+    /// tracker's mode. The file is written beside `path` and renamed into
+    /// place, so a crash mid-save leaves the previous file intact.
+    /// PnL history is not persisted.
+    pub fn save(&self, path: &str, price_scale: i64) -> PyResult<()> {
+        check_price_scale(price_scale)?;
+        let snap = TrackerSnapshot {
+            version: SNAPSHOT_VERSION,
+            price_scale,
+            rounding_mode: self.rounding.name().to_string(),
+            fifo: self.fifo,
+            positions: Cow::Borrowed(&self.positions),
+        };
+        let bytes = serde_json::to_vec(&snap)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        let tmp = format!("{path}.tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Replace all positions with those saved at `path`; returns how many
+    /// were loaded. Fails, leaving the tracker untouched, if the file was
+    /// saved with a different `price_scale`, rounding mode or FIFO setting.
+    pub fn load(&mut self, path: &str, price_scale: i64) -> PyResult<usize> {
+        check_price_scale(price_scale)?;
+        let snap = TrackerSnapshot::read(path, price_scale)?;
+        if snap.fifo != self.fifo || snap.rounding_mode != self.rounding.name() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "position snapshot mode (rounding_mode={}, fifo={}) does not match tracker (rounding_mode={}, fifo={})",
+                snap.rounding_mode,
+                snap.fifo,
+                self.rounding.name(),
+                self.fifo
+            )));
+        }
+        self.positions = snap.positions.into_owned();
+        Ok(self.positions.len())
+    }
+
+    /// Build a tracker from a file written by `save`, in the saved mode.
+    /// `fee_bps` is not persisted and is taken as for `new`.
+    #[staticmethod]
+    #[pyo3(signature = (path, price_scale, fee_bps=None))]
+    pub fn from_file(path: &str, price_scale: i64, fee_bps: Option<f64>) -> PyResult<Self> {
+        check_price_scale(price_scale)?;
+        let snap = TrackerSnapshot::read(path, price_scale)?;
+        let mut tracker = Self::new(&snap.rounding_mode, snap.fifo, fee_bps)?;
+        tracker.positions = snap.positions.into_owned();
+        Ok(tracker)
+    }
 
     /// Process a fill and return the updated position state as a tuple.
     ///
@@ -53,7 +371,7 @@ impl RustPositionTracker {
     ///   side         – 0 = BUY, 1 = SELL  (matches Python Side IntEnum)
     ///   qty          – fill quantity (always positive)
     ///   price_scaled – fill price in fixed-point
-    ///   fee          – fee in fixed-point
+    ///   fee          – fee in fixed-point (ignored when `fee_bps` is set)
     ///   tax          – tax in fixed-point
     ///   match_ts     – exchange match timestamp (nanoseconds)
     ///   multiplier   – contract point-value multiplier (stocks=1, futures=point_value)
@@ -73,72 +391,107 @@ impl RustPositionTracker {
         match_ts: i64,
         multiplier: i64,
     ) -> (i64, i64, i64, i64) {
+        let rounding = self.rounding;
+        let history = if self.history.is_empty() {
+            None
+        } else {
+            self.history.get_mut(&key)
+        };
         let pos = self.positions.entry(key).or_insert_with(PositionState::new);
 
         let is_buy = side == 0; // Side.BUY == 0
         let signed_fill_qty: i64 = if is_buy { qty } else { -qty };
 
         // Accumulate fees
+        let fee = match self.fee_rate_e8 {
+            Some(rate) => bps_fee(
+                price_scaled as i128 * qty as i128 * multiplier as i128,
+                rate,
+            ),
+            None => fee,
+        };
         pos.fees_scaled += fee + tax;
 
-        // Determine if this fill closes existing exposure
-        let current_sign = if pos.net_qty > 0 {
-            1
-        } else if pos.net_qty < 0 {
-            -1
+        let (realized_delta, closed_qty) = if self.fifo {
+            pos.apply_fifo(
+                signed_fill_qty,
+                price_scaled,
+                match_ts,
+                multiplier,
+                rounding,
+            )
         } else {
-            0
-        };
-        let fill_sign: i64 = if is_buy { 1 } else { -1 };
-
-        let closing = current_sign != 0 && fill_sign != current_sign;
-
-        if closing {
-            let abs_net = pos.net_qty.abs();
-            let abs_fill = qty; // qty is always positive
-            let close_qty = abs_net.min(abs_fill);
-
-            // PnL in fixed-point:
-            //   Long closing (sell):  (fill_price - avg_price) * close_qty * multiplier
-            //   Short closing (buy):  (avg_price - fill_price) * close_qty * multiplier
-            // multiplier: stocks=1, futures=point_value (TMF=10, MXF=50, TXF=200)
-            let pnl = if is_buy {
-                // Covering a short
-                (pos.avg_price_scaled - price_scaled) * close_qty * multiplier
+            // Determine if this fill closes existing exposure
+            let current_sign = if pos.net_qty > 0 {
+                1
+            } else if pos.net_qty < 0 {
+                -1
             } else {
-                // Selling a long
-                (price_scaled - pos.avg_price_scaled) * close_qty * multiplier
+                0
             };
-            pos.realized_pnl_scaled += pnl;
+            let fill_sign: i64 = if is_buy { 1 } else { -1 };
 
-            pos.net_qty += signed_fill_qty;
+            let closing = current_sign != 0 && fill_sign != current_sign;
+
+            if closing {
+                let abs_net = pos.net_qty.abs();
+                let abs_fill = qty; // qty is always positive
+                let close_qty = abs_net.min(abs_fill);
+
+                // PnL in fixed-point:
+                //   Long closing (sell):  (fill_price - avg_price) * close_qty * multiplier
+                //   Short closing (buy):  (avg_price - fill_price) * close_qty * multiplier
+                // multiplier: stocks=1, futures=point_value (TMF=10, MXF=50, TXF=200)
+                let pnl = if is_buy {
+                    // Covering a short
+                    (pos.avg_price_scaled - price_scaled) * close_qty * multiplier
+                } else {
+                    // Selling a long
+                    (price_scaled - pos.avg_price_scaled) * close_qty * multiplier
+                };
+                pos.realized_pnl_scaled += pnl;
 
-            if pos.net_qty == 0 {
-                // Closed to flat — reset avg_price (match Python Position.update)
-                pos.avg_price_scaled = 0;
-            } else if (current_sign > 0 && pos.net_qty < 0) || (current_sign < 0 && pos.net_qty > 0)
-            {
-                // Flipped sides — remainder starts at the new fill price
-                pos.avg_price_scaled = price_scaled;
-            }
-        } else {
-            // Opening or increasing position
-            if pos.net_qty == 0 {
-                pos.avg_price_scaled = price_scaled;
                 pos.net_qty += signed_fill_qty;
+
+                if pos.net_qty == 0 {
+                    // Closed to flat — reset avg_price (match Python Position.update)
+                    pos.avg_price_scaled = 0;
+                } else if (current_sign > 0 && pos.net_qty < 0)
+                    || (current_sign < 0 && pos.net_qty > 0)
+                {
+                    // Flipped sides — remainder starts at the new fill price
+                    pos.avg_price_scaled = price_scaled;
+                }
+                (pnl, close_qty)
             } else {
-                // Weighted average:
-                //   new_avg = (old_net * old_avg + signed_qty * fill_price) / new_net
-                let total_val = pos.net_qty * pos.avg_price_scaled + signed_fill_qty * price_scaled;
-                pos.net_qty += signed_fill_qty;
-                if pos.net_qty != 0 {
-                    // Round-to-nearest matching Python: (2*total + net) // (2*net)
-                    pos.avg_price_scaled = (2 * total_val + pos.net_qty) / (2 * pos.net_qty);
+                // Opening or increasing position
+                if pos.net_qty == 0 {
+                    pos.avg_price_scaled = price_scaled;
+                    pos.net_qty += signed_fill_qty;
+                } else {
+                    // Weighted average:
+                    //   new_avg = (old_net * old_avg + signed_qty * fill_price) / new_net
+                    let total_val =
+                        pos.net_qty * pos.avg_price_scaled + signed_fill_qty * price_scaled;
+                    pos.net_qty += signed_fill_qty;
+                    if pos.net_qty != 0 {
+                        pos.avg_price_scaled = rounding.div(total_val, pos.net_qty);
+                    }
                 }
+                (0, 0)
             }
-        }
+        };
 
         pos.last_update_ts = match_ts;
+        pos.last_realized_delta = realized_delta;
+        pos.last_close_qty = closed_qty;
+
+        if let Some(hist) = history {
+            if hist.points.len() >= hist.max_len {
+                hist.points.pop_front();
+            }
+            hist.points.push_back((match_ts, pos.realized_pnl_scaled));
+        }
 
         (
             pos.net_qty,
@@ -163,11 +516,89 @@ impl RustPositionTracker {
         }
     }
 
+    /// (realized_pnl_delta, close_qty) produced by the most recent fill on
+    /// `key` alone; (0, 0) after an opening fill or for an unknown key.
+    /// Per key, so interleaved fills on other keys never disturb it.
+    pub fn last_realized_delta(&self, key: &str) -> (i64, i64) {
+        self.positions
+            .get(key)
+            .map(|pos| (pos.last_realized_delta, pos.last_close_qty))
+            .unwrap_or((0, 0))
+    }
+
+    /// Open FIFO lots for `key` as (signed_qty, price_scaled, ts), oldest
+    /// first. Empty when not in FIFO mode or the key is flat/unknown.
+    pub fn lots(&self, key: &str) -> Vec<(i64, i64, i64)> {
+        self.positions
+            .get(key)
+            .map(|pos| pos.lots.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// All tracked position keys, sorted.
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.positions.keys().cloned().collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// Every position as (key, net_qty, avg_price_scaled, realized_pnl_scaled,
+    /// fees_scaled), sorted by key. Taken in one call so no fill can land
+    /// between rows.
+    pub fn snapshot(&self) -> Vec<(String, i64, i64, i64, i64)> {
+        let mut rows: Vec<(String, i64, i64, i64, i64)> = self
+            .positions
+            .iter()
+            .map(|(key, pos)| {
+                (
+                    key.clone(),
+                    pos.net_qty,
+                    pos.avg_price_scaled,
+                    pos.realized_pnl_scaled,
+                    pos.fees_scaled,
+                )
+            })
+            .collect();
+        rows.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        rows
+    }
+
     /// Reset a single position to zero.
     pub fn reset(&mut self, key: &str) {
         self.positions.remove(key);
     }
 
+    /// Start recording (match_ts, realized_pnl_scaled) on every fill for `key`.
+    ///
+    /// At most `max_len` points are kept; the oldest are dropped first.
+    /// Re-enabling an already recorded key only updates the cap.
+    #[pyo3(signature = (key, max_len=DEFAULT_HISTORY_MAX_LEN))]
+    pub fn enable_history(&mut self, key: String, max_len: usize) {
+        let max_len = max_len.max(1);
+        let hist = self.history.entry(key).or_insert_with(|| PnlHistory {
+            points: VecDeque::new(),
+            max_len,
+        });
+        hist.max_len = max_len;
+        while hist.points.len() > max_len {
+            hist.points.pop_front();
+        }
+    }
+
+    /// Stop recording for `key` and drop its recorded points.
+    pub fn disable_history(&mut self, key: &str) {
+        self.history.remove(key);
+    }
+
+    /// Recorded (match_ts, realized_pnl_scaled) points for `key`, oldest first.
+    /// Empty if history was never enabled for the key.
+    pub fn pnl_history(&self, key: &str) -> Vec<(i64, i64)> {
+        self.history
+            .get(key)
+            .map(|h| h.points.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
     /// Number of tracked positions.
     pub fn len(&self) -> usize {
         self.positions.len()
@@ -181,14 +612,11 @@ impl RustPositionTracker {
     pub fn get_positions_by_strategy(&self) -> HashMap<String, HashMap<String, i64>> {
         let mut result: HashMap<String, HashMap<String, i64>> = HashMap::new();
         for (key, pos) in &self.positions {
-            let parts: Vec<&str> = key.splitn(3, ':').collect();
-            if parts.len() >= 3 {
-                let strategy_id = parts[1].to_string();
-                let symbol = parts[2].to_string();
+            if let Some((_, strategy_id, symbol)) = split_key(key) {
                 result
-                    .entry(strategy_id)
+                    .entry(strategy_id.to_string())
                     .or_default()
-                    .insert(symbol, pos.net_qty);
+                    .insert(symbol.to_string(), pos.net_qty);
             } else {
                 result
                     .entry("*".to_string())
@@ -198,6 +626,102 @@ impl RustPositionTracker {
         }
         result
     }
+
+    /// Signed net quantity for `symbol` summed across all accounts and
+    /// strategies. Returns 0 for an unknown symbol.
+    pub fn net_by_symbol(&self, symbol: &str) -> i64 {
+        self.positions
+            .iter()
+            .filter(|(key, _)| matches!(split_key(key), Some((_, _, sym)) if sym == symbol))
+            .map(|(_, pos)| pos.net_qty)
+            .sum()
+    }
+
+    /// Signed net quantity for `account` summed across all strategies and
+    /// symbols. Returns 0 for an unknown account.
+    pub fn net_by_account(&self, account: &str) -> i64 {
+        self.positions
+            .iter()
+            .filter(|(key, _)| matches!(split_key(key), Some((acc, _, _)) if acc == account))
+            .map(|(_, pos)| pos.net_qty)
+            .sum()
+    }
+}
+
+/// Running high-water mark and drawdown of a PnL series, in the same
+/// scaled integer units as `RustPositionTracker`.
+///
+/// The first `update` sets the high-water mark. `update` returns true while
+/// the current drawdown exceeds `drawdown_limit` (if set), e.g. to drive
+/// `FastGate.set_kill_switch`.
+#[pyclass]
+pub struct EquityMonitor {
+    drawdown_limit: Option<i64>,
+    high_water: Option<i64>,
+    last_pnl: i64,
+    max_drawdown: i64,
+}
+
+#[pymethods]
+impl EquityMonitor {
+    #[new]
+    #[pyo3(signature = (drawdown_limit=None))]
+    pub fn new(drawdown_limit: Option<i64>) -> PyResult<Self> {
+        if let Some(limit) = drawdown_limit {
+            if limit < 0 {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "drawdown_limit must be >= 0, got {limit}"
+                )));
+            }
+        }
+        Ok(Self {
+            drawdown_limit,
+            high_water: None,
+            last_pnl: 0,
+            max_drawdown: 0,
+        })
+    }
+
+    /// Record the latest total PnL. Returns true if the drawdown limit is breached.
+    pub fn update(&mut self, total_pnl: i64) -> bool {
+        let high_water = self.high_water.map_or(total_pnl, |hw| hw.max(total_pnl));
+        self.high_water = Some(high_water);
+        self.last_pnl = total_pnl;
+        self.max_drawdown = self.max_drawdown.max(self.drawdown());
+        self.is_breached()
+    }
+
+    /// High-water mark minus the latest PnL (>= 0).
+    pub fn drawdown(&self) -> i64 {
+        self.high_water
+            .map_or(0, |hw| hw.saturating_sub(self.last_pnl))
+    }
+
+    /// Largest drawdown seen since construction or `reset`.
+    pub fn max_drawdown(&self) -> i64 {
+        self.max_drawdown
+    }
+
+    /// Highest PnL seen, or None before the first update.
+    pub fn high_water_mark(&self) -> Option<i64> {
+        self.high_water
+    }
+
+    pub fn is_breached(&self) -> bool {
+        self.drawdown_limit
+            .is_some_and(|limit| self.drawdown() > limit)
+    }
+
+    #[getter]
+    pub fn get_drawdown_limit(&self) -> Option<i64> {
+        self.drawdown_limit
+    }
+
+    pub fn reset(&mut self) {
+        self.high_water = None;
+        self.last_pnl = 0;
+        self.max_drawdown = 0;
+    }
 }
 
 #[cfg(test)]
@@ -209,7 +733,7 @@ mod tests {
 
     #[test]
     fn test_open_long_then_close() {
-        let mut tracker = RustPositionTracker::new();
+        let mut tracker = RustPositionTracker::default();
         let key = "acc:strat:SYM".to_string();
 
         // Buy 10 @ 1000 (scaled), multiplier=1 (stock)
@@ -228,7 +752,7 @@ mod tests {
 
     #[test]
     fn test_open_short_then_close() {
-        let mut tracker = RustPositionTracker::new();
+        let mut tracker = RustPositionTracker::default();
         let key = "acc:strat:SYM".to_string();
 
         // Sell 5 @ 2000 (open short)
@@ -245,7 +769,7 @@ mod tests {
 
     #[test]
     fn test_increase_long_weighted_avg() {
-        let mut tracker = RustPositionTracker::new();
+        let mut tracker = RustPositionTracker::default();
         let key = "acc:strat:SYM".to_string();
 
         // Buy 10 @ 1000
@@ -259,7 +783,7 @@ mod tests {
 
     #[test]
     fn test_flip_position() {
-        let mut tracker = RustPositionTracker::new();
+        let mut tracker = RustPositionTracker::default();
         let key = "acc:strat:SYM".to_string();
 
         // Buy 10 @ 1000
@@ -271,9 +795,188 @@ mod tests {
         assert_eq!(pnl, 1000); // (1100-1000)*10*1
     }
 
+    #[test]
+    fn test_pnl_history_disabled_by_default() {
+        let mut tracker = RustPositionTracker::default();
+        let key = "acc:strat:SYM".to_string();
+        tracker.update(key.clone(), BUY, 10, 1000, 0, 0, 100, 1);
+        assert!(tracker.pnl_history(&key).is_empty());
+    }
+
+    #[test]
+    fn test_pnl_history_records_each_fill() {
+        let mut tracker = RustPositionTracker::default();
+        let key = "acc:strat:SYM".to_string();
+        tracker.enable_history(key.clone(), 100);
+
+        tracker.update(key.clone(), BUY, 10, 1000, 0, 0, 100, 1);
+        tracker.update(key.clone(), SELL, 5, 1100, 0, 0, 200, 1);
+        tracker.update(key.clone(), SELL, 5, 900, 0, 0, 300, 1);
+        // Other keys are not recorded
+        tracker.update("acc:strat:OTHER".to_string(), BUY, 1, 1, 0, 0, 400, 1);
+
+        assert_eq!(
+            tracker.pnl_history(&key),
+            vec![(100, 0), (200, 500), (300, 0)]
+        );
+        assert!(tracker.pnl_history("acc:strat:OTHER").is_empty());
+    }
+
+    #[test]
+    fn test_pnl_history_capped() {
+        let mut tracker = RustPositionTracker::default();
+        let key = "acc:strat:SYM".to_string();
+        tracker.enable_history(key.clone(), 2);
+        for ts in 1..=5 {
+            tracker.update(key.clone(), BUY, 1, 1000, 0, 0, ts, 1);
+        }
+        assert_eq!(tracker.pnl_history(&key), vec![(4, 0), (5, 0)]);
+
+        tracker.disable_history(&key);
+        assert!(tracker.pnl_history(&key).is_empty());
+    }
+
+    #[test]
+    fn test_keys_and_snapshot() {
+        let mut tracker = RustPositionTracker::default();
+        assert!(tracker.keys().is_empty());
+        tracker.update("acc:s2:B".to_string(), SELL, 3, 500, 1, 0, 100, 1);
+        tracker.update("acc:s1:A".to_string(), BUY, 10, 1000, 2, 0, 200, 1);
+
+        assert_eq!(tracker.keys(), vec!["acc:s1:A", "acc:s2:B"]);
+        assert_eq!(
+            tracker.snapshot(),
+            vec![
+                ("acc:s1:A".to_string(), 10, 1000, 0, 2),
+                ("acc:s2:B".to_string(), -3, 500, 0, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_net_by_symbol_and_account() {
+        let mut tracker = RustPositionTracker::default();
+        tracker.update("a1:mm:TXFD6".to_string(), BUY, 3, 1000, 0, 0, 1, 1);
+        tracker.update("a1:arb:TXFD6".to_string(), SELL, 5, 1000, 0, 0, 2, 1);
+        tracker.update("a2:mm:TXFD6".to_string(), BUY, 4, 1000, 0, 0, 3, 1);
+        tracker.update("a1:mm:2330".to_string(), BUY, 7, 500, 0, 0, 4, 1);
+        // Symbol containing a colon stays intact
+        tracker.update("a2:mm:OPT:C20000".to_string(), SELL, 2, 10, 0, 0, 5, 1);
+
+        assert_eq!(tracker.net_by_symbol("TXFD6"), 2);
+        assert_eq!(tracker.net_by_symbol("OPT:C20000"), -2);
+        assert_eq!(tracker.net_by_symbol("C20000"), 0);
+        assert_eq!(tracker.net_by_symbol("UNKNOWN"), 0);
+
+        assert_eq!(tracker.net_by_account("a1"), 5);
+        assert_eq!(tracker.net_by_account("a2"), 2);
+        assert_eq!(tracker.net_by_account("a3"), 0);
+    }
+
+    #[test]
+    fn test_div_round_half_even() {
+        assert_eq!(div_round_half_even(5, 2), 2);
+        assert_eq!(div_round_half_even(7, 2), 4);
+        assert_eq!(div_round_half_even(-5, 2), -2);
+        assert_eq!(div_round_half_even(-7, 2), -4);
+        assert_eq!(div_round_half_even(10, -4), -2);
+        assert_eq!(div_round_half_even(11, 4), 3);
+        assert_eq!(div_round_half_even(-11, 4), -3);
+        assert_eq!(div_round_half_even(9, 3), 3);
+    }
+
+    #[test]
+    fn test_odd_lot_avg_price_drift() {
+        // Exact VWAP = (3*1000 + 5*1001 + 7*1002) / 15 = 1001.27
+        let fills = [(3, 1000), (5, 1001), (7, 1002)];
+        let run = |mode: &str| {
+            let mut tracker = RustPositionTracker::new(mode, false, None).unwrap();
+            let mut avg = 0;
+            for (i, (qty, px)) in fills.iter().enumerate() {
+                avg = tracker
+                    .update(
+                        "acc:strat:SYM".to_string(),
+                        BUY,
+                        *qty,
+                        *px,
+                        0,
+                        0,
+                        i as i64,
+                        1,
+                    )
+                    .1;
+            }
+            avg
+        };
+        assert_eq!(run("truncate"), 1000); // drifts a full tick low
+        assert_eq!(run("half_even"), 1001);
+        assert!(RustPositionTracker::new("bogus", false, None).is_err());
+    }
+
+    #[test]
+    fn test_default_rounding_is_half_up() {
+        // 1 @ 1000 then 1 @ 1001: avg 1000.5 ties. Half-up (the Python
+        // reference) gives 1001; half-even would give 1000.
+        let run = |mut tracker: RustPositionTracker| {
+            tracker.update("acc:strat:SYM".to_string(), BUY, 1, 1000, 0, 0, 1, 1);
+            tracker
+                .update("acc:strat:SYM".to_string(), BUY, 1, 1001, 0, 0, 2, 1)
+                .1
+        };
+        assert_eq!(
+            RustPositionTracker::default().get_rounding_mode(),
+            "half_up"
+        );
+        assert_eq!(run(RustPositionTracker::default()), 1001);
+        assert_eq!(
+            run(RustPositionTracker::new("half_even", false, None).unwrap()),
+            1000
+        );
+    }
+
+    #[test]
+    fn test_fifo_matches_oldest_lot_first() {
+        let mut tracker = RustPositionTracker::new("half_even", true, None).unwrap();
+        let key = "acc:strat:SYM".to_string();
+
+        tracker.update(key.clone(), BUY, 10, 100, 0, 0, 1, 1);
+        tracker.update(key.clone(), BUY, 10, 110, 0, 0, 2, 1);
+        assert_eq!(tracker.lots(&key), vec![(10, 100, 1), (10, 110, 2)]);
+
+        // Sell 15 @ 120: 10 from lot@100 (+200), 5 from lot@110 (+50).
+        // The average-price model would give (120-105)*15 = 225.
+        let (net, avg, pnl, _) = tracker.update(key.clone(), SELL, 15, 120, 0, 0, 3, 1);
+        assert_eq!(net, 5);
+        assert_eq!(pnl, 250);
+        assert_eq!(avg, 110);
+        assert_eq!(tracker.lots(&key), vec![(5, 110, 2)]);
+
+        // Flip: close the last 5 @ 100 (-50), open a short lot of 5
+        let (net, avg, pnl, _) = tracker.update(key.clone(), SELL, 10, 100, 0, 0, 4, 1);
+        assert_eq!(net, -5);
+        assert_eq!(avg, 100);
+        assert_eq!(pnl, 200);
+        assert_eq!(tracker.lots(&key), vec![(-5, 100, 4)]);
+
+        // Cover with a multiplier
+        let (net, avg, pnl, _) = tracker.update(key.clone(), BUY, 5, 90, 0, 0, 5, 10);
+        assert_eq!(net, 0);
+        assert_eq!(avg, 0);
+        assert_eq!(pnl, 200 + 10 * 5 * 10);
+        assert!(tracker.lots(&key).is_empty());
+    }
+
+    #[test]
+    fn test_lots_empty_in_average_mode() {
+        let mut tracker = RustPositionTracker::default();
+        let key = "acc:strat:SYM".to_string();
+        tracker.update(key.clone(), BUY, 10, 100, 0, 0, 1, 1);
+        assert!(tracker.lots(&key).is_empty());
+    }
+
     #[test]
     fn test_futures_multiplier_10() {
-        let mut tracker = RustPositionTracker::new();
+        let mut tracker = RustPositionTracker::default();
         let key = "acc:strat:TMFD6".to_string();
 
         // Buy 1 @ 333450000, multiplier=10 (微台指)
@@ -286,7 +989,7 @@ mod tests {
 
     #[test]
     fn test_futures_multiplier_50() {
-        let mut tracker = RustPositionTracker::new();
+        let mut tracker = RustPositionTracker::default();
         let key = "acc:strat:MXFD6".to_string();
 
         // Buy 1 @ 333450000, multiplier=50 (小台指)
@@ -296,4 +999,125 @@ mod tests {
         assert_eq!(net, 0);
         assert_eq!(pnl, -500_000); // -50 NTD (descaled)
     }
+
+    #[test]
+    fn test_save_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("positions.json");
+        let path = path.to_str().unwrap();
+
+        let mut tracker = RustPositionTracker::new("half_even", true, None).unwrap();
+        tracker.update("acc:s:A".to_string(), BUY, 10, 1000, 3, 1, 1, 1);
+        tracker.update("acc:s:A".to_string(), BUY, 5, 1010, 0, 0, 2, 1);
+        tracker.update("acc:s:B".to_string(), SELL, 2, 500, 0, 0, 3, 1);
+        tracker.save(path, 10_000).unwrap();
+
+        let restored = RustPositionTracker::from_file(path, 10_000, None).unwrap();
+        assert!(restored.get_fifo());
+        assert_eq!(restored.snapshot(), tracker.snapshot());
+        assert_eq!(restored.lots("acc:s:A"), tracker.lots("acc:s:A"));
+
+        // Restored state keeps trading from where it left off
+        let mut restored = restored;
+        let after = restored.update("acc:s:A".to_string(), SELL, 15, 1020, 0, 0, 4, 1);
+        let expected = tracker.update("acc:s:A".to_string(), SELL, 15, 1020, 0, 0, 4, 1);
+        assert_eq!(after, expected);
+
+        let mut other = RustPositionTracker::new("half_even", true, None).unwrap();
+        assert_eq!(other.load(path, 10_000).unwrap(), 2);
+        assert_eq!(other.get("acc:s:B"), (-2, 500, 0, 0));
+    }
+
+    #[test]
+    fn test_load_rejects_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("positions.json");
+        let path = path.to_str().unwrap();
+
+        let mut tracker = RustPositionTracker::default();
+        tracker.update("acc:s:A".to_string(), BUY, 1, 1000, 0, 0, 1, 1);
+        tracker.save(path, 10_000).unwrap();
+
+        assert!(RustPositionTracker::from_file(path, 100, None).is_err());
+        let mut fifo = RustPositionTracker::new("half_even", true, None).unwrap();
+        assert!(fifo.load(path, 10_000).is_err());
+        assert_eq!(fifo.len(), 0);
+        assert!(tracker.save(path, 0).is_err());
+
+        std::fs::write(path, b"not json").unwrap();
+        assert!(RustPositionTracker::from_file(path, 10_000, None).is_err());
+    }
+
+    #[test]
+    fn test_last_realized_delta_per_fill() {
+        for fifo in [false, true] {
+            let mut tracker = RustPositionTracker::new("half_even", fifo, None).unwrap();
+            let a = "acc:s:A".to_string();
+            let b = "acc:s:B".to_string();
+            assert_eq!(tracker.last_realized_delta(&a), (0, 0));
+
+            tracker.update(a.clone(), BUY, 10, 1000, 0, 0, 1, 1);
+            assert_eq!(tracker.last_realized_delta(&a), (0, 0));
+            tracker.update(a.clone(), SELL, 4, 1050, 0, 0, 2, 1);
+            // Interleaved fill on another key does not disturb A
+            tracker.update(b.clone(), SELL, 1, 10, 0, 0, 3, 1);
+            assert_eq!(tracker.last_realized_delta(&a), (200, 4));
+
+            // Flip: closes 6, opens 2 short
+            let (_, _, cum, _) = tracker.update(a.clone(), SELL, 8, 990, 0, 0, 4, 1);
+            assert_eq!(tracker.last_realized_delta(&a), (-60, 6));
+            assert_eq!(cum, 200 - 60);
+            tracker.update(a.clone(), SELL, 1, 980, 0, 0, 5, 1);
+            assert_eq!(tracker.last_realized_delta(&a), (0, 0));
+        }
+    }
+
+    #[test]
+    fn test_bps_fee_mode() {
+        // 1.425 bps on notional
+        let mut tracker = RustPositionTracker::new("half_even", false, Some(1.425)).unwrap();
+        assert_eq!(tracker.get_fee_bps(), Some(1.425));
+        let key = "acc:s:2330".to_string();
+        // 1_000_000 * 3 * 1.425e-4 = 427.5 -> 428 (half to even)
+        let (_, _, _, fees) = tracker.update(key.clone(), BUY, 3, 1_000_000, 999, 0, 1, 1);
+        assert_eq!(fees, 428);
+        // Tax still added; multiplier scales notional: 1_000_000 * 1 * 50 * 1.425e-4 = 7125
+        let (_, _, _, fees) = tracker.update(key.clone(), SELL, 1, 1_000_000, 0, 30, 2, 50);
+        assert_eq!(fees, 428 + 7125 + 30);
+
+        assert!(RustPositionTracker::new("half_even", false, Some(-1.0)).is_err());
+        assert_eq!(RustPositionTracker::default().get_fee_bps(), None);
+    }
+
+    #[test]
+    fn test_bps_fee_rounding() {
+        assert_eq!(bps_fee(25_000, 20_000), 5); // 25_000 * 2bp = 5
+        assert_eq!(bps_fee(12_500, 20_000), 2); // 2.5 -> 2
+        assert_eq!(bps_fee(17_500, 20_000), 4); // 3.5 -> 4
+        assert_eq!(bps_fee(17_499, 20_000), 3);
+        assert_eq!(bps_fee(0, 20_000), 0);
+    }
+
+    #[test]
+    fn test_equity_monitor_drawdown() {
+        let mut eq = EquityMonitor::new(Some(300)).unwrap();
+        assert_eq!(eq.high_water_mark(), None);
+        assert!(!eq.update(-100)); // first point sets the mark
+        assert_eq!(eq.drawdown(), 0);
+        assert!(!eq.update(500));
+        assert!(!eq.update(200));
+        assert_eq!(eq.drawdown(), 300);
+        assert!(eq.update(150));
+        assert_eq!(eq.drawdown(), 350);
+        assert!(!eq.update(400)); // recovered under the limit
+        assert_eq!((eq.drawdown(), eq.max_drawdown()), (100, 350));
+        assert_eq!(eq.high_water_mark(), Some(500));
+
+        eq.reset();
+        assert_eq!((eq.drawdown(), eq.max_drawdown()), (0, 0));
+        let mut unlimited = EquityMonitor::new(None).unwrap();
+        unlimited.update(1_000);
+        assert!(!unlimited.update(-1_000_000));
+        assert!(EquityMonitor::new(Some(-1)).is_err());
+    }
 }
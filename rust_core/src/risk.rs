@@ -1,7 +1,79 @@
+use crate::session::SessionCalendar;
 use memmap2::MmapMut;
 use pyo3::prelude::*;
+use pyo3::types::PyBool;
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::fs::OpenOptions;
 use std::sync::atomic::{AtomicU8, Ordering::Acquire, Ordering::Release};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Kill-switch levels (SHM byte / `set_kill_switch`).
+pub const KILL_NORMAL: u8 = 0;
+/// Block everything. Same byte legacy writers use for a full kill.
+pub const KILL_HALT_ALL: u8 = 1;
+/// Block position-opening orders; reduce-only orders still pass.
+pub const KILL_HALT_NEW: u8 = 2;
+
+/// Severity order of a kill-switch byte. Any byte other than
+/// `KILL_NORMAL` / `KILL_HALT_NEW` is a full kill, as legacy writers treat
+/// every non-zero byte that way.
+#[inline(always)]
+fn kill_rank(level: u8) -> u8 {
+    match level {
+        KILL_NORMAL => 0,
+        KILL_HALT_NEW => 1,
+        _ => 2,
+    }
+}
+
+/// The more severe of two kill-switch bytes, as a known level.
+#[inline(always)]
+fn combine_kill(a: u8, b: u8) -> u8 {
+    match kill_rank(a).max(kill_rank(b)) {
+        0 => KILL_NORMAL,
+        1 => KILL_HALT_NEW,
+        _ => KILL_HALT_ALL,
+    }
+}
+
+/// Order-rate token bucket driven by a monotonic nanosecond clock.
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    rate_per_sec: f64,
+    burst: f64,
+    tokens: f64,
+    last_ns: u64,
+}
+
+impl TokenBucket {
+    fn new(rate_per_sec: f64, burst: f64, now_ns: u64) -> Self {
+        Self {
+            rate_per_sec,
+            burst,
+            tokens: burst,
+            last_ns: now_ns,
+        }
+    }
+
+    #[inline(always)]
+    fn available_at(&self, now_ns: u64) -> f64 {
+        let dt = now_ns.saturating_sub(self.last_ns) as f64 * 1e-9;
+        (self.tokens + dt * self.rate_per_sec).min(self.burst)
+    }
+
+    #[inline(always)]
+    fn try_take(&mut self, now_ns: u64) -> bool {
+        self.tokens = self.available_at(now_ns);
+        self.last_ns = self.last_ns.max(now_ns);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
 
 #[pyclass]
 pub struct FastGate {
@@ -9,6 +81,15 @@ pub struct FastGate {
     kill_atomic: AtomicU8,
     max_price: f64,
     max_qty: f64,
+    max_notional: f64,
+    // symbol -> (max_price, max_qty); unconfigured symbols use the globals
+    symbol_limits: HashMap<String, (f64, f64)>,
+    // None = unlimited; Cell so `check` can consume tokens through &self
+    rate_limit: Cell<Option<TokenBucket>>,
+    clock_origin: Instant,
+    flatten_on_kill: bool,
+    // None = always in session
+    calendar: Option<SessionCalendar>,
 }
 
 unsafe impl Send for FastGate {}
@@ -16,7 +97,13 @@ unsafe impl Send for FastGate {}
 #[pymethods]
 impl FastGate {
     #[new]
-    pub fn new(kill_shm_name: String, max_price: f64, max_qty: f64) -> PyResult<Self> {
+    #[pyo3(signature = (kill_shm_name, max_price, max_qty, max_notional=f64::INFINITY))]
+    pub fn new(
+        kill_shm_name: String,
+        max_price: f64,
+        max_qty: f64,
+        max_notional: f64,
+    ) -> PyResult<Self> {
         let path = if kill_shm_name.starts_with('/') {
             kill_shm_name
         } else {
@@ -39,47 +126,268 @@ impl FastGate {
             kill_atomic: AtomicU8::new(0),
             max_price,
             max_qty,
+            max_notional,
+            symbol_limits: HashMap::new(),
+            rate_limit: Cell::new(None),
+            clock_origin: Instant::now(),
+            flatten_on_kill: false,
+            calendar: None,
         })
     }
 
-    pub fn check(&self, price: f64, qty: f64) -> (bool, u8) {
+    /// `reduce_only` marks orders that only shrink an existing position;
+    /// these still pass at `KILL_HALT_NEW`.
+    #[pyo3(signature = (price, qty, reduce_only=false))]
+    pub fn check(&self, price: f64, qty: f64, reduce_only: bool) -> (bool, u8) {
+        self.check_with_limits(
+            price,
+            qty,
+            reduce_only,
+            self.max_price,
+            self.max_qty,
+            None,
+            None,
+        )
+    }
+
+    /// `check` with the session calendar evaluated at `ts_ns` (UTC epoch
+    /// nanoseconds) instead of the wall clock, e.g. for replay.
+    #[pyo3(signature = (ts_ns, price, qty, reduce_only=false))]
+    pub fn check_at(&self, ts_ns: i64, price: f64, qty: f64, reduce_only: bool) -> (bool, u8) {
+        self.check_with_limits(
+            price,
+            qty,
+            reduce_only,
+            self.max_price,
+            self.max_qty,
+            None,
+            Some(ts_ns),
+        )
+    }
+
+    /// Like `check`, but applies the bounds configured for `symbol` via
+    /// `set_limits`, falling back to the global bounds.
+    #[pyo3(signature = (symbol, price, qty, reduce_only=false))]
+    pub fn check_symbol(
+        &self,
+        symbol: &str,
+        price: f64,
+        qty: f64,
+        reduce_only: bool,
+    ) -> (bool, u8) {
+        let (max_price, max_qty) = self
+            .symbol_limits
+            .get(symbol)
+            .copied()
+            .unwrap_or((self.max_price, self.max_qty));
+        self.check_with_limits(price, qty, reduce_only, max_price, max_qty, None, None)
+    }
+
+    /// `check` plus a fat-finger band: reject (code 7) when `price` is more
+    /// than `max_ticks` ticks away from `ref_price` (e.g. the current mid).
+    #[pyo3(signature = (price, qty, ref_price, tick_size, max_ticks, reduce_only=false))]
+    pub fn check_ref(
+        &self,
+        price: f64,
+        qty: f64,
+        ref_price: f64,
+        tick_size: f64,
+        max_ticks: f64,
+        reduce_only: bool,
+    ) -> (bool, u8) {
+        self.check_with_limits(
+            price,
+            qty,
+            reduce_only,
+            self.max_price,
+            self.max_qty,
+            Some((ref_price, tick_size, max_ticks)),
+            None,
+        )
+    }
+
+    /// Enable order-rate limiting: `burst` orders immediately, refilled at
+    /// `rate_per_sec`. Orders passing all other checks consume one token;
+    /// an empty bucket rejects with code 8.
+    pub fn set_rate_limit(&mut self, rate_per_sec: f64, burst: f64) -> PyResult<()> {
+        if !(rate_per_sec > 0.0 && burst >= 1.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "rate_per_sec must be > 0 and burst >= 1, got {rate_per_sec}, {burst}"
+            )));
+        }
+        self.rate_limit
+            .set(Some(TokenBucket::new(rate_per_sec, burst, self.now_ns())));
+        Ok(())
+    }
+
+    pub fn clear_rate_limit(&mut self) {
+        self.rate_limit.set(None);
+    }
+
+    /// Tokens currently in the bucket (infinite when rate limiting is off).
+    pub fn tokens_available(&self) -> f64 {
+        match self.rate_limit.get() {
+            Some(bucket) => bucket.available_at(self.now_ns()),
+            None => f64::INFINITY,
+        }
+    }
+
+    /// Reject orders (code 9) while the wall clock (or `check_at`'s
+    /// `ts_ns`) is outside `calendar`'s tradable windows. Pass None to
+    /// remove the calendar.
+    #[pyo3(signature = (calendar=None))]
+    pub fn set_session_calendar(&mut self, calendar: Option<SessionCalendar>) {
+        self.calendar = calendar;
+    }
+
+    /// Configure per-symbol price/qty bounds.
+    pub fn set_limits(&mut self, symbol: String, max_price: f64, max_qty: f64) {
+        self.symbol_limits.insert(symbol, (max_price, max_qty));
+    }
+
+    /// Set the kill-switch level: `KILL_NORMAL`, `KILL_HALT_ALL` or
+    /// `KILL_HALT_NEW`. Booleans are rejected rather than read as 0/1.
+    pub fn set_kill_switch(&mut self, level: &Bound<'_, PyAny>) -> PyResult<()> {
+        if level.is_instance_of::<PyBool>() {
+            return Err(pyo3::exceptions::PyTypeError::new_err(
+                "kill switch level must be an int (KILL_NORMAL, KILL_HALT_ALL or KILL_HALT_NEW), not bool",
+            ));
+        }
+        self.set_kill_level(level.extract()?)
+    }
+
+    /// Effective kill-switch level (the more severe of local and
+    /// cross-process).
+    pub fn kill_level(&self) -> u8 {
+        let local = self.kill_atomic.load(Acquire);
+        // read volatile in case another process writes
+        let shared = unsafe { std::ptr::read_volatile(self.mmap.as_ptr()) };
+        combine_kill(local, shared)
+    }
+
+    /// Whether an active kill switch should also trigger flatten-all
+    /// downstream, rather than only blocking new orders.
+    pub fn set_flatten_on_kill(&mut self, enabled: bool) {
+        self.flatten_on_kill = enabled;
+    }
+
+    /// True while the kill switch is at `KILL_HALT_ALL` (local or
+    /// cross-process) and flatten-on-kill is enabled.
+    pub fn should_flatten(&self) -> bool {
+        self.flatten_on_kill && self.kill_level() == KILL_HALT_ALL
+    }
+
+    #[classattr]
+    pub const KILL_NORMAL: u8 = KILL_NORMAL;
+    #[classattr]
+    pub const KILL_HALT_NEW: u8 = KILL_HALT_NEW;
+    #[classattr]
+    pub const KILL_HALT_ALL: u8 = KILL_HALT_ALL;
+}
+
+impl FastGate {
+    fn set_kill_level(&mut self, level: u8) -> PyResult<()> {
+        if !matches!(level, KILL_NORMAL | KILL_HALT_ALL | KILL_HALT_NEW) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "kill switch level must be {KILL_NORMAL}, {KILL_HALT_ALL} or {KILL_HALT_NEW}, got {level}"
+            )));
+        }
+        self.kill_atomic.store(level, Release);
+        self.mmap[0] = level;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_with_limits(
+        &self,
+        price: f64,
+        qty: f64,
+        reduce_only: bool,
+        max_price: f64,
+        max_qty: f64,
+        // (ref_price, tick_size, max_ticks) for `check_ref`
+        band: Option<(f64, f64, f64)>,
+        // Session-calendar time; None reads the wall clock
+        ts_ns: Option<i64>,
+    ) -> (bool, u8) {
         // Fast-path: same-process atomic (~5ns) before cross-process mmap volatile (~100ns)
-        if self.kill_atomic.load(Acquire) > 0 {
+        let local = self.kill_atomic.load(Acquire);
+        if local == KILL_HALT_ALL {
             return (false, 1);
         }
 
-        unsafe {
-            // 1. Zero-latency Kill Switch
-            // read volatile in case another process writes
-            let kill_flag = std::ptr::read_volatile(self.mmap.as_ptr());
-            if kill_flag > 0 {
-                return (false, 1);
+        // 1. Zero-latency Kill Switch
+        // read volatile in case another process writes
+        let kill_level = combine_kill(local, unsafe {
+            std::ptr::read_volatile(self.mmap.as_ptr())
+        });
+        if kill_level == KILL_HALT_ALL || (kill_level == KILL_HALT_NEW && !reduce_only) {
+            return (false, 1);
+        }
+
+        // Clock is only read when a calendar is set
+        if let Some(cal) = &self.calendar {
+            if !cal.is_tradable(ts_ns.unwrap_or_else(wall_clock_ns)) {
+                return (false, 9);
             }
         }
 
         if price <= 0.0 {
             return (false, 2);
         }
-        if price > self.max_price {
+        if price > max_price {
             return (false, 3);
         }
         if qty <= 0.0 {
             return (false, 5);
         }
-        if qty > self.max_qty {
+        if qty > max_qty {
             return (false, 4);
         }
+        if price * qty > self.max_notional {
+            return (false, 6);
+        }
+        if let Some((ref_price, tick_size, max_ticks)) = band {
+            if ref_price > 0.0
+                && tick_size > 0.0
+                && (price - ref_price).abs() / tick_size > max_ticks
+            {
+                return (false, 7);
+            }
+        }
+        if !self.take_token() {
+            return (false, 8);
+        }
 
         (true, 0)
     }
 
-    pub fn set_kill_switch(&mut self, active: bool) {
-        let val = if active { 1 } else { 0 };
-        self.kill_atomic.store(val, Release);
-        self.mmap[0] = val;
+    #[inline(always)]
+    fn now_ns(&self) -> u64 {
+        self.clock_origin.elapsed().as_nanos() as u64
+    }
+
+    /// Consume one token; the clock is only read when a limit is set.
+    #[inline(always)]
+    fn take_token(&self) -> bool {
+        match self.rate_limit.get() {
+            Some(mut bucket) => {
+                let ok = bucket.try_take(self.now_ns());
+                self.rate_limit.set(Some(bucket));
+                ok
+            }
+            None => true,
+        }
     }
 }
 
+/// UTC epoch nanoseconds, for session-calendar checks.
+fn wall_clock_ns() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as i64)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,13 +402,13 @@ mod tests {
         let path = f.path().to_string_lossy().to_string();
         // Keep the file alive by leaking (test only)
         std::mem::forget(f);
-        FastGate::new(path, max_price, max_qty).unwrap()
+        FastGate::new(path, max_price, max_qty, f64::INFINITY).unwrap()
     }
 
     #[test]
     fn test_check_pass() {
         let gate = make_gate(100_000.0, 10_000.0);
-        let (ok, code) = gate.check(50_000.0, 100.0);
+        let (ok, code) = gate.check(50_000.0, 100.0, false);
         assert!(ok);
         assert_eq!(code, 0);
     }
@@ -108,7 +416,7 @@ mod tests {
     #[test]
     fn test_check_price_zero() {
         let gate = make_gate(100_000.0, 10_000.0);
-        let (ok, code) = gate.check(0.0, 100.0);
+        let (ok, code) = gate.check(0.0, 100.0, false);
         assert!(!ok);
         assert_eq!(code, 2);
     }
@@ -116,7 +424,7 @@ mod tests {
     #[test]
     fn test_check_price_exceeds() {
         let gate = make_gate(100_000.0, 10_000.0);
-        let (ok, code) = gate.check(200_000.0, 100.0);
+        let (ok, code) = gate.check(200_000.0, 100.0, false);
         assert!(!ok);
         assert_eq!(code, 3);
     }
@@ -124,7 +432,7 @@ mod tests {
     #[test]
     fn test_check_qty_exceeds() {
         let gate = make_gate(100_000.0, 10_000.0);
-        let (ok, code) = gate.check(50_000.0, 20_000.0);
+        let (ok, code) = gate.check(50_000.0, 20_000.0, false);
         assert!(!ok);
         assert_eq!(code, 4);
     }
@@ -132,20 +440,222 @@ mod tests {
     #[test]
     fn test_kill_switch_atomic() {
         let mut gate = make_gate(100_000.0, 10_000.0);
-        assert!(gate.check(50_000.0, 100.0).0);
-        gate.set_kill_switch(true);
-        let (ok, code) = gate.check(50_000.0, 100.0);
+        assert!(gate.check(50_000.0, 100.0, false).0);
+        gate.set_kill_level(KILL_HALT_ALL).unwrap();
+        let (ok, code) = gate.check(50_000.0, 100.0, false);
         assert!(!ok);
         assert_eq!(code, 1);
-        gate.set_kill_switch(false);
-        assert!(gate.check(50_000.0, 100.0).0);
+        gate.set_kill_level(KILL_NORMAL).unwrap();
+        assert!(gate.check(50_000.0, 100.0, false).0);
+    }
+
+    #[test]
+    fn test_should_flatten_requires_flag_and_kill() {
+        let mut gate = make_gate(100_000.0, 10_000.0);
+        gate.set_kill_level(KILL_HALT_ALL).unwrap();
+        assert!(!gate.should_flatten()); // flag off: block only
+        gate.set_flatten_on_kill(true);
+        assert!(gate.should_flatten());
+        gate.set_kill_level(KILL_NORMAL).unwrap();
+        assert!(!gate.should_flatten());
     }
 
     #[test]
     fn test_qty_zero_rejected() {
         let gate = make_gate(100_000.0, 10_000.0);
-        let (ok, code) = gate.check(50_000.0, 0.0);
+        let (ok, code) = gate.check(50_000.0, 0.0, false);
         assert!(!ok);
         assert_eq!(code, 5);
     }
+
+    #[test]
+    fn test_check_symbol_uses_per_symbol_limits() {
+        let mut gate = make_gate(100_000.0, 10_000.0);
+        gate.set_limits("TXFD6".to_string(), 30_000.0, 50.0);
+        // Within global bounds but outside the symbol's
+        assert_eq!(gate.check_symbol("TXFD6", 50_000.0, 1.0, false), (false, 3));
+        assert_eq!(
+            gate.check_symbol("TXFD6", 20_000.0, 100.0, false),
+            (false, 4)
+        );
+        assert_eq!(gate.check_symbol("TXFD6", 20_000.0, 10.0, false), (true, 0));
+        // Unconfigured symbol falls back to global bounds
+        assert_eq!(gate.check_symbol("2330", 50_000.0, 100.0, false), (true, 0));
+    }
+
+    #[test]
+    fn test_check_symbol_kill_switch_first() {
+        let mut gate = make_gate(100_000.0, 10_000.0);
+        gate.set_limits("TXFD6".to_string(), 30_000.0, 50.0);
+        gate.set_kill_level(KILL_HALT_ALL).unwrap();
+        assert_eq!(
+            gate.check_symbol("TXFD6", 20_000.0, 10.0, false),
+            (false, 1)
+        );
+        assert_eq!(
+            gate.check_symbol("2330", 50_000.0, 100.0, false),
+            (false, 1)
+        );
+    }
+
+    #[test]
+    fn test_notional_limit() {
+        let mut f = NamedTempFile::new().unwrap();
+        f.write_all(&[0u8]).unwrap();
+        let path = f.path().to_string_lossy().to_string();
+        std::mem::forget(f);
+        let gate = FastGate::new(path, 100_000.0, 10_000.0, 1_000_000.0).unwrap();
+        // Each bound passes individually, but the notional is absurd
+        assert_eq!(gate.check(90_000.0, 9_000.0, false), (false, 6));
+        assert_eq!(gate.check(1_000.0, 1_000.0, false), (true, 0));
+    }
+
+    #[test]
+    fn test_check_ref_rejects_far_from_mid() {
+        let gate = make_gate(100_000.0, 10_000.0);
+        // 5 ticks away: within a 10-tick band
+        assert_eq!(
+            gate.check_ref(20_005.0, 1.0, 20_000.0, 1.0, 10.0, false),
+            (true, 0)
+        );
+        // 50 ticks away on either side
+        assert_eq!(
+            gate.check_ref(20_050.0, 1.0, 20_000.0, 1.0, 10.0, false),
+            (false, 7)
+        );
+        assert_eq!(
+            gate.check_ref(19_950.0, 1.0, 20_000.0, 1.0, 10.0, false),
+            (false, 7)
+        );
+        // Base checks still take precedence
+        assert_eq!(
+            gate.check_ref(0.0, 1.0, 20_000.0, 1.0, 10.0, false),
+            (false, 2)
+        );
+    }
+
+    #[test]
+    fn test_token_bucket_refill() {
+        let mut bucket = TokenBucket::new(10.0, 2.0, 0);
+        assert!(bucket.try_take(0));
+        assert!(bucket.try_take(0));
+        assert!(!bucket.try_take(0));
+        // 10/s -> one token every 100ms
+        assert!(!bucket.try_take(50_000_000));
+        assert!(bucket.try_take(100_000_000));
+        // Long idle refills only up to burst
+        assert_eq!(bucket.available_at(10_000_000_000), 2.0);
+    }
+
+    #[test]
+    fn test_rate_limit_rejects_when_empty() {
+        let mut gate = make_gate(100_000.0, 10_000.0);
+        assert_eq!(gate.tokens_available(), f64::INFINITY);
+        gate.set_rate_limit(1e-6, 2.0).unwrap();
+        assert_eq!(gate.check(50_000.0, 1.0, false), (true, 0));
+        // Rejected orders do not consume tokens
+        assert_eq!(gate.check(0.0, 1.0, false), (false, 2));
+        assert_eq!(gate.check(50_000.0, 1.0, false), (true, 0));
+        assert_eq!(gate.check(50_000.0, 1.0, false), (false, 8));
+        assert!(gate.tokens_available() < 1.0);
+
+        gate.clear_rate_limit();
+        assert_eq!(gate.check(50_000.0, 1.0, false), (true, 0));
+    }
+
+    #[test]
+    fn test_fat_finger_reject_keeps_token() {
+        let mut gate = make_gate(100_000.0, 10_000.0);
+        gate.set_rate_limit(1e-6, 2.0).unwrap();
+        let before = gate.tokens_available();
+        assert_eq!(
+            gate.check_ref(20_050.0, 1.0, 20_000.0, 1.0, 10.0, false),
+            (false, 7)
+        );
+        assert!((gate.tokens_available() - before).abs() < 1e-3);
+        assert_eq!(
+            gate.check_ref(20_005.0, 1.0, 20_000.0, 1.0, 10.0, false),
+            (true, 0)
+        );
+        assert!((gate.tokens_available() - (before - 1.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_rate_limit_validates_params() {
+        let mut gate = make_gate(100_000.0, 10_000.0);
+        assert!(gate.set_rate_limit(0.0, 5.0).is_err());
+        assert!(gate.set_rate_limit(10.0, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_halt_new_allows_reduce_only() {
+        let mut gate = make_gate(100_000.0, 10_000.0);
+        gate.set_kill_level(KILL_HALT_NEW).unwrap();
+        assert_eq!(gate.check(50_000.0, 1.0, false), (false, 1));
+        assert_eq!(gate.check(50_000.0, 1.0, true), (true, 0));
+        // Reduce-only orders still go through the other checks
+        assert_eq!(gate.check(0.0, 1.0, true), (false, 2));
+        // Halt-new does not trigger flatten
+        gate.set_flatten_on_kill(true);
+        assert!(!gate.should_flatten());
+
+        gate.set_kill_level(KILL_HALT_ALL).unwrap();
+        assert_eq!(gate.check(50_000.0, 1.0, true), (false, 1));
+        assert!(gate.should_flatten());
+    }
+
+    #[test]
+    fn test_kill_level_from_shm() {
+        let mut gate = make_gate(100_000.0, 10_000.0);
+        // Simulate a supervising process writing the SHM byte directly
+        gate.mmap[0] = KILL_HALT_NEW;
+        assert_eq!(gate.kill_level(), KILL_HALT_NEW);
+        assert_eq!(gate.check(50_000.0, 1.0, false), (false, 1));
+        assert_eq!(gate.check(50_000.0, 1.0, true), (true, 0));
+        assert!(gate.set_kill_level(3).is_err());
+
+        // Legacy writers set 1 for a full kill; other non-zero bytes too
+        for byte in [1, 7] {
+            gate.mmap[0] = byte;
+            assert_eq!(gate.kill_level(), KILL_HALT_ALL);
+            assert_eq!(gate.check(50_000.0, 1.0, true), (false, 1));
+        }
+        gate.mmap[0] = KILL_NORMAL;
+    }
+
+    #[test]
+    fn test_set_kill_switch_rejects_bool() {
+        let mut gate = make_gate(100_000.0, 10_000.0);
+        Python::with_gil(|py| {
+            assert!(gate
+                .set_kill_switch(PyBool::new_bound(py, true).as_any())
+                .is_err());
+            gate.set_kill_switch(&KILL_HALT_NEW.into_py(py).into_bound(py))
+                .unwrap();
+        });
+        assert_eq!(gate.kill_level(), KILL_HALT_NEW);
+    }
+
+    #[test]
+    fn test_session_calendar_gate() {
+        let mut gate = make_gate(100_000.0, 10_000.0);
+        let cal = SessionCalendar::new(vec![("09:00".into(), "13:30".into())], 0, true).unwrap();
+        gate.set_session_calendar(Some(cal));
+        // Mon 2024-01-08 10:00 UTC is in session, 14:00 is not
+        let monday = 1_704_672_000i64 * 1_000_000_000;
+        let in_session = monday + 10 * 3_600_000_000_000;
+        let after_close = monday + 14 * 3_600_000_000_000;
+        assert_eq!(gate.check_at(in_session, 50_000.0, 1.0, false), (true, 0));
+        assert_eq!(gate.check_at(after_close, 50_000.0, 1.0, false), (false, 9));
+        // Kill switch still takes precedence over the session check
+        gate.set_kill_level(KILL_HALT_ALL).unwrap();
+        assert_eq!(gate.check_at(after_close, 50_000.0, 1.0, false), (false, 1));
+        gate.set_kill_level(KILL_NORMAL).unwrap();
+
+        // A calendar that is never open rejects with code 9
+        gate.set_session_calendar(Some(SessionCalendar::new(Vec::new(), 0, false).unwrap()));
+        assert_eq!(gate.check(50_000.0, 1.0, false), (false, 9));
+        gate.set_session_calendar(None);
+        assert_eq!(gate.check(50_000.0, 1.0, false), (true, 0));
+    }
 }
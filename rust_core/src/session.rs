@@ -0,0 +1,172 @@
+use pyo3::prelude::*;
+
+const SECS_PER_DAY: i64 = 86_400;
+/// Default exchange offset: UTC+8 (Taipei).
+const DEFAULT_UTC_OFFSET_MINUTES: i32 = 480;
+
+/// Seconds since local midnight for "HH:MM" or "HH:MM:SS".
+fn parse_local_time(s: &str) -> PyResult<i64> {
+    let bad = || {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "invalid time '{s}' (expected HH:MM or HH:MM:SS)"
+        ))
+    };
+    let parts: Vec<&str> = s.trim().split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return Err(bad());
+    }
+    let mut fields = [0i64; 3];
+    for (field, part) in fields.iter_mut().zip(&parts) {
+        *field = part.parse().map_err(|_| bad())?;
+    }
+    let [h, m, sec] = fields;
+    if !((0..24).contains(&h) && (0..60).contains(&m) && (0..60).contains(&sec)) {
+        return Err(bad());
+    }
+    Ok(h * 3600 + m * 60 + sec)
+}
+
+/// Tradable windows in exchange-local time, e.g. TXF day 08:45-13:45 and
+/// night 15:00-05:00.
+///
+/// Windows are `[start, end)` and may cross midnight; a window belongs to
+/// the local day it opens on. With `weekdays_only` (the default) windows
+/// only open Monday to Friday, so a Friday night session still runs into
+/// Saturday morning. Leave auction periods out of the windows to keep
+/// strategies from quoting into them.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct SessionCalendar {
+    utc_offset_secs: i64,
+    weekdays_only: bool,
+    /// (start, end) seconds since local midnight.
+    windows: Vec<(i64, i64)>,
+}
+
+#[pymethods]
+impl SessionCalendar {
+    #[new]
+    #[pyo3(signature = (windows=Vec::new(), utc_offset_minutes=DEFAULT_UTC_OFFSET_MINUTES, weekdays_only=true))]
+    pub fn new(
+        windows: Vec<(String, String)>,
+        utc_offset_minutes: i32,
+        weekdays_only: bool,
+    ) -> PyResult<Self> {
+        if utc_offset_minutes.abs() > 14 * 60 {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "utc_offset_minutes must be within +/-840, got {utc_offset_minutes}"
+            )));
+        }
+        let mut calendar = Self {
+            utc_offset_secs: utc_offset_minutes as i64 * 60,
+            weekdays_only,
+            windows: Vec::with_capacity(windows.len()),
+        };
+        for (start, end) in windows {
+            calendar.add_window(&start, &end)?;
+        }
+        Ok(calendar)
+    }
+
+    /// Add a `[start, end)` window in local "HH:MM[:SS]"; end before start
+    /// crosses midnight.
+    pub fn add_window(&mut self, start: &str, end: &str) -> PyResult<()> {
+        let (start_s, end_s) = (parse_local_time(start)?, parse_local_time(end)?);
+        if start_s == end_s {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "empty window {start}-{end}"
+            )));
+        }
+        self.windows.push((start_s, end_s));
+        Ok(())
+    }
+
+    /// True if `ts_ns` (UTC epoch nanoseconds) falls inside a window.
+    pub fn is_tradable(&self, ts_ns: i64) -> bool {
+        let local = ts_ns.div_euclid(1_000_000_000) + self.utc_offset_secs;
+        let day = local.div_euclid(SECS_PER_DAY);
+        let sod = local.rem_euclid(SECS_PER_DAY);
+        self.windows.iter().any(|&(start, end)| {
+            if start < end {
+                (start..end).contains(&sod) && self.opens_on(day)
+            } else if sod >= start {
+                self.opens_on(day)
+            } else {
+                sod < end && self.opens_on(day - 1)
+            }
+        })
+    }
+
+    /// Windows as (start, end) seconds since local midnight.
+    pub fn windows(&self) -> Vec<(i64, i64)> {
+        self.windows.clone()
+    }
+
+    #[getter]
+    pub fn get_utc_offset_minutes(&self) -> i64 {
+        self.utc_offset_secs / 60
+    }
+}
+
+impl SessionCalendar {
+    /// Whether windows open on local day `day` (days since 1970-01-01).
+    fn opens_on(&self, day: i64) -> bool {
+        // 1970-01-01 was a Thursday; Monday = 0
+        !self.weekdays_only || (day + 3).rem_euclid(7) < 5
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// UTC epoch ns for a local (UTC+8) date-time in the week of Mon 2024-01-08.
+    fn taipei(weekday: i64, h: i64, m: i64) -> i64 {
+        // 2024-01-08 00:00 UTC = 1_704_672_000
+        let local = 1_704_672_000 + weekday * SECS_PER_DAY + h * 3600 + m * 60;
+        (local - 8 * 3600) * 1_000_000_000
+    }
+
+    fn txf() -> SessionCalendar {
+        SessionCalendar::new(
+            vec![
+                ("08:45".into(), "13:45".into()),
+                ("15:00".into(), "05:00".into()),
+            ],
+            480,
+            true,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_day_and_night_sessions() {
+        let cal = txf();
+        assert!(!cal.is_tradable(taipei(0, 8, 44)));
+        assert!(cal.is_tradable(taipei(0, 8, 45)));
+        assert!(!cal.is_tradable(taipei(0, 13, 45))); // end is exclusive
+        assert!(!cal.is_tradable(taipei(0, 14, 30)));
+        assert!(cal.is_tradable(taipei(0, 23, 0)));
+        assert!(cal.is_tradable(taipei(1, 4, 59))); // Monday's night session
+        assert!(!cal.is_tradable(taipei(1, 5, 0)));
+        // Friday night runs into Saturday; nothing opens on the weekend
+        assert!(cal.is_tradable(taipei(5, 3, 0)));
+        assert!(!cal.is_tradable(taipei(5, 9, 0)));
+        assert!(!cal.is_tradable(taipei(6, 16, 0)));
+        // Monday early morning: Sunday had no night session
+        assert!(!cal.is_tradable(taipei(0, 2, 0)));
+    }
+
+    #[test]
+    fn test_rejects_bad_windows() {
+        let mut cal = SessionCalendar::new(Vec::new(), 0, false).unwrap();
+        assert!(!cal.is_tradable(0));
+        assert!(cal.add_window("9:00", "9:00").is_err());
+        assert!(cal.add_window("24:00", "01:00").is_err());
+        assert!(cal.add_window("09:00", "noon").is_err());
+        cal.add_window("00:00:00", "12:00:00").unwrap();
+        assert!(cal.is_tradable(0)); // 1970-01-01 is a Thursday, weekends allowed anyway
+        assert_eq!(cal.windows(), vec![(0, 43_200)]);
+        assert!(SessionCalendar::new(Vec::new(), 900, true).is_err());
+    }
+}
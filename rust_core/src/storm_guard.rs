@@ -1,4 +1,5 @@
 use pyo3::prelude::*;
+use std::collections::VecDeque;
 
 /// Rust-native StormGuard FSM validator for hot-path risk checks.
 #[pyclass]
@@ -40,6 +41,112 @@ impl RustStormGuardValidator {
     }
 }
 
+/// Quote-stuffing / microburst detector on message timestamps.
+///
+/// `tick(ts_ns)` counts messages in the trailing `window_ns` and flags the
+/// tick when that count exceeds `threshold_mult` times the median count over
+/// the previous `history` ticks. Never flags until `history` ticks are seen.
+#[pyclass]
+pub struct MessageRateMonitor {
+    window_ns: u64,
+    threshold_mult: f64,
+    history: usize,
+    stamps: VecDeque<u64>,
+    counts: VecDeque<u32>,
+    scratch: Vec<u32>,
+}
+
+#[pymethods]
+impl MessageRateMonitor {
+    #[new]
+    #[pyo3(signature = (window_ns, threshold_mult, history=256))]
+    pub fn new(window_ns: u64, threshold_mult: f64, history: usize) -> PyResult<Self> {
+        if window_ns == 0 || history == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "window_ns and history must be positive",
+            ));
+        }
+        if !(threshold_mult.is_finite() && threshold_mult > 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "threshold_mult must be positive, got {threshold_mult}"
+            )));
+        }
+        Ok(Self {
+            window_ns,
+            threshold_mult,
+            history,
+            stamps: VecDeque::new(),
+            counts: VecDeque::with_capacity(history + 1),
+            scratch: Vec::with_capacity(history),
+        })
+    }
+
+    /// Record one message; returns true if the rate is anomalous.
+    pub fn tick(&mut self, ts_ns: u64) -> bool {
+        self.stamps.push_back(ts_ns);
+        while let Some(&front) = self.stamps.front() {
+            if ts_ns.saturating_sub(front) > self.window_ns {
+                self.stamps.pop_front();
+            } else {
+                break;
+            }
+        }
+        let count = self.stamps.len() as u32;
+
+        let flagged = self.counts.len() >= self.history
+            && count as f64 > self.threshold_mult * self.median_count();
+
+        self.counts.push_back(count);
+        if self.counts.len() > self.history {
+            self.counts.pop_front();
+        }
+        flagged
+    }
+
+    /// Messages per second over the current window.
+    pub fn current_rate(&self) -> f64 {
+        self.stamps.len() as f64 * 1e9 / self.window_ns as f64
+    }
+
+    /// Median messages per second over the trailing history (0 when empty).
+    pub fn median_rate(&mut self) -> f64 {
+        self.median_count() * 1e9 / self.window_ns as f64
+    }
+
+    pub fn reset(&mut self) {
+        self.stamps.clear();
+        self.counts.clear();
+    }
+
+    #[getter]
+    pub fn get_window_ns(&self) -> u64 {
+        self.window_ns
+    }
+
+    #[getter]
+    pub fn get_threshold_mult(&self) -> f64 {
+        self.threshold_mult
+    }
+}
+
+impl MessageRateMonitor {
+    fn median_count(&mut self) -> f64 {
+        let n = self.counts.len();
+        if n == 0 {
+            return 0.0;
+        }
+        self.scratch.clear();
+        self.scratch.extend(self.counts.iter().copied());
+        let (_, &mut hi, _) = self.scratch.select_nth_unstable(n / 2);
+        if n % 2 == 1 {
+            return hi as f64;
+        }
+        // Even length: the lower middle is the max of the left partition
+        let lo = self.scratch[..n / 2].iter().copied().max().unwrap_or(hi);
+        (lo as f64 + hi as f64) / 2.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -78,4 +185,48 @@ mod tests {
         sg.reset();
         assert_eq!(sg.state(), 0);
     }
+
+    #[test]
+    fn test_message_rate_flags_burst() {
+        let mut m = MessageRateMonitor::new(1_000, 3.0, 64).unwrap();
+        // Steady: one message per 250ns -> ~5 in a 1us window
+        let mut ts = 0u64;
+        for _ in 0..128 {
+            ts += 250;
+            assert!(!m.tick(ts));
+        }
+        assert_eq!(m.median_rate(), 5.0 * 1e6);
+        // Burst: 20 messages within 20ns
+        let mut n_flagged = 0;
+        for _ in 0..20 {
+            ts += 1;
+            n_flagged += m.tick(ts) as usize;
+        }
+        assert!(n_flagged > 0);
+        assert!(m.current_rate() > 15.0 * 1e6);
+    }
+
+    #[test]
+    fn test_message_rate_warmup_and_validation() {
+        let mut m = MessageRateMonitor::new(1_000, 2.0, 8).unwrap();
+        // Dense from the start, but no history to compare against yet
+        for i in 0..8 {
+            assert!(!m.tick(i));
+        }
+        m.reset();
+        assert_eq!(m.current_rate(), 0.0);
+        assert_eq!(m.median_rate(), 0.0);
+        assert!(MessageRateMonitor::new(0, 2.0, 8).is_err());
+        assert!(MessageRateMonitor::new(1_000, 0.0, 8).is_err());
+        assert!(MessageRateMonitor::new(1_000, 2.0, 0).is_err());
+    }
+
+    #[test]
+    fn test_median_count_even_and_odd() {
+        let mut m = MessageRateMonitor::new(1, 1.0, 8).unwrap();
+        m.counts.extend([4, 1, 3]);
+        assert_eq!(m.median_count(), 3.0);
+        m.counts.push_back(10);
+        assert_eq!(m.median_count(), 3.5);
+    }
 }
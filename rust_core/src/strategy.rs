@@ -41,10 +41,100 @@ impl HawkesTracker {
     }
 }
 
+/// Bivariate (buy/sell) Hawkes intensity with mutual excitation.
+///
+/// Each side decays toward its own baseline with a shared `beta`; an event
+/// on side `j` adds `alpha[i][j]` to side `i` (index 0 = buy, 1 = sell).
+/// `asymmetry()` is `lambda_buy - lambda_sell`.
+#[pyclass]
+pub struct BivariateHawkes {
+    mu: [f64; 2],
+    alpha: [[f64; 2]; 2],
+    beta: f64,
+    last_ts: i64,
+    intensity: [f64; 2],
+}
+
+#[pymethods]
+impl BivariateHawkes {
+    #[new]
+    pub fn new(mu_buy: f64, mu_sell: f64, alpha: [[f64; 2]; 2], beta: f64) -> PyResult<Self> {
+        let params_ok = mu_buy >= 0.0
+            && mu_sell >= 0.0
+            && beta > 0.0
+            && alpha.iter().flatten().all(|a| *a >= 0.0);
+        if !params_ok {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "mu and alpha must be >= 0 and beta > 0",
+            ));
+        }
+        Ok(Self {
+            mu: [mu_buy, mu_sell],
+            alpha,
+            beta,
+            last_ts: 0,
+            intensity: [mu_buy, mu_sell],
+        })
+    }
+
+    /// Decay to `ts` (ns) and apply one trade; returns (lambda_buy, lambda_sell).
+    pub fn update(&mut self, ts: i64, is_buy: bool) -> (f64, f64) {
+        self.decay_to(ts);
+        let j = if is_buy { 0 } else { 1 };
+        self.intensity[0] += self.alpha[0][j];
+        self.intensity[1] += self.alpha[1][j];
+        (self.intensity[0], self.intensity[1])
+    }
+
+    /// Decay to `ts` (ns) without an event; returns (lambda_buy, lambda_sell).
+    pub fn advance(&mut self, ts: i64) -> (f64, f64) {
+        self.decay_to(ts);
+        (self.intensity[0], self.intensity[1])
+    }
+
+    /// Current (lambda_buy, lambda_sell).
+    pub fn intensities(&self) -> (f64, f64) {
+        (self.intensity[0], self.intensity[1])
+    }
+
+    /// lambda_buy - lambda_sell
+    pub fn asymmetry(&self) -> f64 {
+        self.intensity[0] - self.intensity[1]
+    }
+
+    pub fn reset(&mut self) {
+        self.last_ts = 0;
+        self.intensity = self.mu;
+    }
+}
+
+impl BivariateHawkes {
+    #[inline(always)]
+    fn decay_to(&mut self, ts: i64) {
+        let dt = (ts - self.last_ts) as f64 / 1e9; // ns to seconds
+
+        // Same guard as HawkesTracker: non-monotonic time leaves intensity as is
+        if dt > 0.0 {
+            let decay = (-self.beta * dt).exp();
+            for i in 0..2 {
+                self.intensity[i] = self.mu[i] + (self.intensity[i] - self.mu[i]) * decay;
+            }
+        }
+        self.last_ts = ts;
+    }
+}
+
+/// Deep-level imbalance plus trade momentum, damped by trading intensity.
+///
+/// `signal = (imb * w_imb + mom * w_skew) / (1 + w_hawkes * max(lambda - mu, 0))`
+/// where `lambda` is the Hawkes intensity as of the last trade. With
+/// `w_hawkes = 0` (the default) intensity does not affect the signal.
 #[pyclass]
 pub struct AlphaStrategy {
     // Parameters
     deep_level: usize, // e.g., 4 (0-indexed logic depends on data, usually 1-5 means idx 0-4)
+    /// Sum qty over levels `0..deep_level` instead of reading only `deep_level`.
+    cumulative: bool,
 
     // State
     hawkes: HawkesTracker,
@@ -54,16 +144,17 @@ pub struct AlphaStrategy {
     // Weights
     w_imb: f64,
     w_skew: f64,
-    #[allow(dead_code)]
-    w_hawkes: f64, // Used for gating or scaling
+    w_hawkes: f64, // Dampens the signal when trade intensity runs above baseline
 }
 
 #[pymethods]
 impl AlphaStrategy {
     #[new]
-    pub fn new(level: usize, mu: f64, alpha: f64, beta: f64) -> Self {
+    #[pyo3(signature = (level, mu, alpha, beta, cumulative=false))]
+    pub fn new(level: usize, mu: f64, alpha: f64, beta: f64, cumulative: bool) -> Self {
         Self {
             deep_level: level,
+            cumulative,
             hawkes: HawkesTracker::new(mu, alpha, beta),
             last_trade_price: 0.0,
             mid_price: 0.0,
@@ -95,14 +186,22 @@ impl AlphaStrategy {
 
         let mut imb = 0.0;
 
-        // Safely access deep level
-        if idx < bids.len() && idx < asks.len() {
-            let b_qty = bids[idx].1;
-            let a_qty = asks[idx].1;
-            let total = b_qty + a_qty;
-            if total > 0.0 {
-                imb = (b_qty - a_qty) / total;
-            }
+        let (b_qty, a_qty) = if self.cumulative {
+            // Levels 0..=idx, truncated to the depth both sides have
+            let n = (idx + 1).min(bids.len()).min(asks.len());
+            (
+                bids[..n].iter().map(|l| l.1).sum::<f64>(),
+                asks[..n].iter().map(|l| l.1).sum::<f64>(),
+            )
+        } else if idx < bids.len() && idx < asks.len() {
+            // Safely access deep level
+            (bids[idx].1, asks[idx].1)
+        } else {
+            (0.0, 0.0)
+        };
+        let total = b_qty + a_qty;
+        if total > 0.0 {
+            imb = (b_qty - a_qty) / total;
         }
 
         // Strategy Logic:
@@ -115,7 +214,25 @@ impl AlphaStrategy {
             0.0
         };
 
-        imb * self.w_imb + mom * self.w_skew
+        (imb * self.w_imb + mom * self.w_skew) * self.hawkes_gate()
+    }
+
+    /// Set the imbalance, momentum and Hawkes-damping weights.
+    pub fn set_weights(&mut self, w_imb: f64, w_skew: f64, w_hawkes: f64) -> PyResult<()> {
+        if !(w_imb.is_finite() && w_skew.is_finite() && w_hawkes.is_finite() && w_hawkes >= 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "weights must be finite and w_hawkes >= 0",
+            ));
+        }
+        self.w_imb = w_imb;
+        self.w_skew = w_skew;
+        self.w_hawkes = w_hawkes;
+        Ok(())
+    }
+
+    /// Current (w_imb, w_skew, w_hawkes).
+    pub fn get_weights(&self) -> (f64, f64, f64) {
+        (self.w_imb, self.w_skew, self.w_hawkes)
     }
 
     /// Process Trade
@@ -139,6 +256,15 @@ impl AlphaStrategy {
     }
 }
 
+impl AlphaStrategy {
+    /// Multiplier in (0, 1]: 1 at or below baseline intensity.
+    #[inline(always)]
+    fn hawkes_gate(&self) -> f64 {
+        let excess = (self.hawkes.intensity - self.hawkes.mu).max(0.0);
+        1.0 / (1.0 + self.w_hawkes * excess)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -177,16 +303,56 @@ mod tests {
         assert!((intensity - 0.1).abs() < 1e-10);
     }
 
+    fn bivariate() -> BivariateHawkes {
+        // Buys excite buys by 0.5 and sells by 0.2; sells excite sells by 0.4
+        BivariateHawkes::new(0.1, 0.2, [[0.5, 0.0], [0.2, 0.4]], 1.0).unwrap()
+    }
+
+    #[test]
+    fn test_bivariate_cross_excitation() {
+        let mut h = bivariate();
+        let (b, s) = h.update(1_000_000_000, true);
+        assert!((b - 0.6).abs() < 1e-10);
+        assert!((s - 0.4).abs() < 1e-10);
+        assert!((h.asymmetry() - 0.2).abs() < 1e-10);
+
+        // A sell at the same instant does not excite the buy side
+        let (b, s) = h.update(1_000_000_000, false);
+        assert!((b - 0.6).abs() < 1e-10);
+        assert!((s - 0.8).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_bivariate_decays_to_own_baseline() {
+        let mut h = bivariate();
+        h.update(1_000_000_000, true);
+        let (b, s) = h.advance(2_000_000_000);
+        let decay = (-1.0_f64).exp();
+        assert!((b - (0.1 + 0.5 * decay)).abs() < 1e-10);
+        assert!((s - (0.2 + 0.2 * decay)).abs() < 1e-10);
+        // Out-of-order timestamp: no decay
+        assert_eq!(h.advance(1_500_000_000), (b, s));
+        h.reset();
+        assert_eq!(h.intensities(), (0.1, 0.2));
+    }
+
+    #[test]
+    fn test_bivariate_rejects_bad_params() {
+        assert!(BivariateHawkes::new(0.1, 0.1, [[0.5, -0.1], [0.0, 0.5]], 1.0).is_err());
+        assert!(BivariateHawkes::new(0.1, 0.1, [[0.5, 0.0], [0.0, 0.5]], 0.0).is_err());
+        assert!(BivariateHawkes::new(-0.1, 0.1, [[0.5, 0.0], [0.0, 0.5]], 1.0).is_err());
+    }
+
     #[test]
     fn test_strategy_on_depth_empty() {
-        let mut s = AlphaStrategy::new(4, 0.1, 0.5, 1.0);
+        let mut s = AlphaStrategy::new(4, 0.1, 0.5, 1.0, false);
         let signal = s.on_depth(vec![], vec![]);
         assert_eq!(signal, 0.0);
     }
 
     #[test]
     fn test_strategy_on_depth_basic() {
-        let mut s = AlphaStrategy::new(1, 0.1, 0.5, 1.0);
+        let mut s = AlphaStrategy::new(1, 0.1, 0.5, 1.0, false);
         let bids = vec![(100.0, 200.0)];
         let asks = vec![(102.0, 100.0)];
         let signal = s.on_depth(bids, asks);
@@ -198,7 +364,7 @@ mod tests {
 
     #[test]
     fn test_strategy_on_trade() {
-        let mut s = AlphaStrategy::new(4, 0.1, 0.5, 1.0);
+        let mut s = AlphaStrategy::new(4, 0.1, 0.5, 1.0, false);
         let intensity = s.on_trade(1_000_000_000, 100.5, 10.0, true);
         assert!(intensity > 0.1); // Should have jumped
         assert_eq!(s.last_trade_price, 100.5);
@@ -206,7 +372,7 @@ mod tests {
 
     #[test]
     fn test_strategy_get_signal_initial() {
-        let s = AlphaStrategy::new(4, 0.1, 0.5, 1.0);
+        let s = AlphaStrategy::new(4, 0.1, 0.5, 1.0, false);
         let (intensity, mom) = s.get_signal();
         assert_eq!(intensity, 0.1);
         assert_eq!(mom, 0.0);
@@ -214,7 +380,7 @@ mod tests {
 
     #[test]
     fn test_strategy_get_signal_after_trade() {
-        let mut s = AlphaStrategy::new(4, 0.1, 0.5, 1.0);
+        let mut s = AlphaStrategy::new(4, 0.1, 0.5, 1.0, false);
         s.on_depth(vec![(100.0, 50.0)], vec![(102.0, 50.0)]);
         s.on_trade(1_000_000_000, 101.5, 10.0, true);
         let (intensity, mom) = s.get_signal();
@@ -224,7 +390,7 @@ mod tests {
 
     #[test]
     fn test_strategy_deep_level_bounds() {
-        let mut s = AlphaStrategy::new(5, 0.1, 0.5, 1.0);
+        let mut s = AlphaStrategy::new(5, 0.1, 0.5, 1.0, false);
         // Only 2 levels available but deep_level wants idx 4
         let bids = vec![(100.0, 200.0), (99.0, 150.0)];
         let asks = vec![(101.0, 100.0), (102.0, 80.0)];
@@ -235,7 +401,7 @@ mod tests {
 
     #[test]
     fn test_strategy_level_zero() {
-        let mut s = AlphaStrategy::new(0, 0.1, 0.5, 1.0);
+        let mut s = AlphaStrategy::new(0, 0.1, 0.5, 1.0, false);
         let bids = vec![(100.0, 200.0)];
         let asks = vec![(102.0, 100.0)];
         let signal = s.on_depth(bids, asks);
@@ -245,7 +411,7 @@ mod tests {
 
     #[test]
     fn test_strategy_momentum_with_trade() {
-        let mut s = AlphaStrategy::new(1, 0.1, 0.5, 1.0);
+        let mut s = AlphaStrategy::new(1, 0.1, 0.5, 1.0, false);
         s.on_depth(vec![(100.0, 100.0)], vec![(102.0, 100.0)]);
         s.on_trade(1_000_000_000, 103.0, 10.0, true);
         let signal = s.on_depth(vec![(100.0, 100.0)], vec![(102.0, 100.0)]);
@@ -253,4 +419,43 @@ mod tests {
         // signal = 0 * 1.0 + 2.0 * 0.5 = 1.0
         assert!((signal - 1.0).abs() < 1e-10);
     }
+
+    #[test]
+    fn test_strategy_hawkes_damping() {
+        let mut s = AlphaStrategy::new(1, 0.1, 0.5, 1.0, false);
+        let bids = vec![(100.0, 200.0)];
+        let asks = vec![(102.0, 100.0)];
+        // intensity 0.6, mom 0
+        s.on_trade(1_000_000_000, 101.0, 10.0, true);
+        // Default w_hawkes = 0: intensity is ignored
+        let undamped = s.on_depth(bids.clone(), asks.clone());
+        assert!((undamped - 1.0 / 3.0).abs() < 1e-10);
+
+        s.set_weights(1.0, 0.5, 2.0).unwrap();
+        // excess = 0.5 → gate = 1 / (1 + 2 * 0.5) = 0.5
+        let damped = s.on_depth(bids, asks);
+        assert!((damped - undamped * 0.5).abs() < 1e-10);
+        assert_eq!(s.get_weights(), (1.0, 0.5, 2.0));
+
+        assert!(s.set_weights(1.0, 0.5, -1.0).is_err());
+        assert!(s.set_weights(f64::NAN, 0.5, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_strategy_cumulative_imbalance() {
+        let bids = vec![(100.0, 10.0), (99.0, 50.0), (98.0, 40.0)];
+        let asks = vec![(101.0, 30.0), (102.0, 10.0), (103.0, 10.0)];
+        let mut single = AlphaStrategy::new(3, 0.1, 0.5, 1.0, false);
+        // Level 3 only: (40 - 10) / 50
+        assert!((single.on_depth(bids.clone(), asks.clone()) - 0.6).abs() < 1e-10);
+        let mut cum = AlphaStrategy::new(3, 0.1, 0.5, 1.0, true);
+        // Levels 1-3: (100 - 50) / 150
+        assert!((cum.on_depth(bids.clone(), asks.clone()) - 1.0 / 3.0).abs() < 1e-10);
+
+        // Book shorter than deep_level: sums the levels both sides have
+        let mut deep = AlphaStrategy::new(5, 0.1, 0.5, 1.0, true);
+        let short_asks = asks[..2].to_vec();
+        // Levels 1-2: (60 - 40) / 100
+        assert!((deep.on_depth(bids, short_asks) - 0.2).abs() < 1e-10);
+    }
 }
@@ -0,0 +1,674 @@
+use pyo3::prelude::*;
+use std::collections::VecDeque;
+
+/// Default short/long vol ratio above which the regime is high (1).
+pub const DEFAULT_REGIME_HIGH: f64 = 1.5;
+/// Default short/long vol ratio below which the regime is low (-1).
+pub const DEFAULT_REGIME_LOW: f64 = 0.7;
+
+/// EWMA volatility of simple mid returns, shared by the regime-gated alphas.
+///
+/// `variance = a * r^2 + (1 - a) * variance` with `a = 2 / (window + 1)` and
+/// `r = (mid - prev_mid) / prev_mid`. Ticks where either mid is not positive
+/// leave the variance untouched. Warm once `window` returns have been seen.
+#[pyclass]
+pub struct VolMonitor {
+    alpha: f64,
+    window: usize,
+    ewma_variance: f64,
+    prev_mid: f64,
+    n_returns: usize,
+    initialized: bool,
+}
+
+#[pymethods]
+impl VolMonitor {
+    #[new]
+    pub fn new(window: usize) -> PyResult<Self> {
+        if window == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "window must be >= 1, got 0",
+            ));
+        }
+        // Center of mass ~ window
+        Ok(VolMonitor {
+            alpha: 2.0 / (window as f64 + 1.0),
+            window,
+            ewma_variance: 0.0,
+            prev_mid: f64::NAN,
+            n_returns: 0,
+            initialized: false,
+        })
+    }
+
+    /// Feed one mid price; returns the volatility after this tick, or 0.0 if
+    /// no return could be formed (first tick, or a non-positive mid).
+    #[inline(always)]
+    pub fn update(&mut self, mid: f64) -> f64 {
+        let mut vol = 0.0;
+        if self.initialized {
+            if mid > 0.0 && self.prev_mid > 0.0 {
+                let ret = (mid - self.prev_mid) / self.prev_mid;
+                self.ewma_variance =
+                    self.alpha * (ret * ret) + (1.0 - self.alpha) * self.ewma_variance;
+                vol = self.ewma_variance.sqrt();
+                self.n_returns = self.n_returns.saturating_add(1);
+            }
+        } else {
+            self.initialized = true;
+        }
+        self.prev_mid = mid;
+        vol
+    }
+
+    /// Current volatility estimate (sqrt of the EWMA variance).
+    pub fn current_vol(&self) -> f64 {
+        self.ewma_variance.sqrt()
+    }
+
+    /// True once `window` returns have been folded into the estimate.
+    pub fn is_warm(&self) -> bool {
+        self.n_returns >= self.window
+    }
+
+    /// Clear the estimate (e.g. at session start).
+    pub fn reset(&mut self) {
+        self.ewma_variance = 0.0;
+        self.prev_mid = f64::NAN;
+        self.n_returns = 0;
+        self.initialized = false;
+    }
+
+    #[getter]
+    pub fn get_window(&self) -> usize {
+        self.window
+    }
+
+    #[getter]
+    pub fn get_n_returns(&self) -> usize {
+        self.n_returns
+    }
+}
+
+/// Volatility Regime Detector
+/// Returns: 1.0 = high vol, 0.0 = normal, -1.0 = low vol
+pub(crate) fn compute_vol_regime(
+    returns: &VecDeque<f64>,
+    short_window: usize,
+    long_window: usize,
+    high: f64,
+    low: f64,
+) -> f64 {
+    if returns.len() < long_window {
+        return 0.0;
+    }
+
+    let n = returns.len();
+
+    // Short vol
+    let short_start = n.saturating_sub(short_window);
+    let short_slice: Vec<f64> = returns.iter().skip(short_start).copied().collect();
+    let short_mean: f64 = short_slice.iter().sum::<f64>() / short_slice.len() as f64;
+    let short_var: f64 = short_slice
+        .iter()
+        .map(|x| (x - short_mean).powi(2))
+        .sum::<f64>()
+        / short_slice.len() as f64;
+    let short_vol = short_var.sqrt();
+
+    // Long vol
+    let long_start = n.saturating_sub(long_window);
+    let long_slice: Vec<f64> = returns.iter().skip(long_start).copied().collect();
+    let long_mean: f64 = long_slice.iter().sum::<f64>() / long_slice.len() as f64;
+    let long_var: f64 = long_slice
+        .iter()
+        .map(|x| (x - long_mean).powi(2))
+        .sum::<f64>()
+        / long_slice.len() as f64;
+    let long_vol = long_var.sqrt();
+
+    classify_vol_ratio(short_vol, long_vol, high, low) as f64
+}
+
+/// 1 when `short_vol / long_vol > high`, -1 when `< low`, else 0 (also 0
+/// when the long vol is degenerate).
+#[inline(always)]
+fn classify_vol_ratio(short_vol: f64, long_vol: f64, high: f64, low: f64) -> i8 {
+    if long_vol > 1e-10 {
+        let vol_ratio = short_vol / long_vol;
+        if vol_ratio > high {
+            1 // High vol
+        } else if vol_ratio < low {
+            -1 // Low vol
+        } else {
+            0 // Normal
+        }
+    } else {
+        0
+    }
+}
+
+/// Population standard deviation from running `sum` / `sum_sq` over `n`.
+#[inline(always)]
+fn running_std(sum: f64, sum_sq: f64, n: usize) -> f64 {
+    let n = n as f64;
+    let mean = sum / n;
+    // Running sums can drift just below zero on flat input
+    (sum_sq / n - mean * mean).max(0.0).sqrt()
+}
+
+/// Streaming short/long volatility-ratio regime (the detector `MetaAlpha`
+/// uses), for switching strategy parameters on its own.
+///
+/// Keeps the last `long_window` returns; the regime is 0 (normal) until the
+/// window is full, then 1 when short/long vol > `high`, -1 when < `low`.
+/// Running sums over both windows make each update O(1).
+#[pyclass]
+pub struct VolRegime {
+    short_window: usize,
+    long_window: usize,
+    high: f64,
+    low: f64,
+    returns: VecDeque<f64>,
+    short_sum: f64,
+    short_sum_sq: f64,
+    long_sum: f64,
+    long_sum_sq: f64,
+    regime: i8,
+}
+
+#[pymethods]
+impl VolRegime {
+    #[new]
+    #[pyo3(signature = (short_window=100, long_window=500, high=DEFAULT_REGIME_HIGH, low=DEFAULT_REGIME_LOW))]
+    pub fn new(short_window: usize, long_window: usize, high: f64, low: f64) -> PyResult<Self> {
+        if short_window == 0 || long_window < short_window {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "need 1 <= short_window <= long_window, got {short_window} / {long_window}"
+            )));
+        }
+        if !(low.is_finite() && high.is_finite() && 0.0 <= low && low <= high) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "need 0 <= low <= high, got low={low} high={high}"
+            )));
+        }
+        Ok(VolRegime {
+            short_window,
+            long_window,
+            high,
+            low,
+            returns: VecDeque::with_capacity(long_window + 1),
+            short_sum: 0.0,
+            short_sum_sq: 0.0,
+            long_sum: 0.0,
+            long_sum_sq: 0.0,
+            regime: 0,
+        })
+    }
+
+    /// Feed one return; returns the regime as `regime()` does.
+    pub fn update(&mut self, ret: f64) -> i8 {
+        self.returns.push_back(ret);
+        self.short_sum += ret;
+        self.short_sum_sq += ret * ret;
+        self.long_sum += ret;
+        self.long_sum_sq += ret * ret;
+        let n = self.returns.len();
+        if n > self.short_window {
+            let old = self.returns[n - 1 - self.short_window];
+            self.short_sum -= old;
+            self.short_sum_sq -= old * old;
+        }
+        if n > self.long_window {
+            if let Some(old) = self.returns.pop_front() {
+                self.long_sum -= old;
+                self.long_sum_sq -= old * old;
+            }
+        }
+        self.regime = if self.returns.len() < self.long_window {
+            0
+        } else {
+            classify_vol_ratio(
+                running_std(self.short_sum, self.short_sum_sq, self.short_window),
+                running_std(self.long_sum, self.long_sum_sq, self.long_window),
+                self.high,
+                self.low,
+            )
+        };
+        self.regime
+    }
+
+    /// Current regime: 1 = high vol, 0 = normal, -1 = low vol.
+    pub fn regime(&self) -> i8 {
+        self.regime
+    }
+
+    pub fn reset(&mut self) {
+        self.returns.clear();
+        self.short_sum = 0.0;
+        self.short_sum_sq = 0.0;
+        self.long_sum = 0.0;
+        self.long_sum_sq = 0.0;
+        self.regime = 0;
+    }
+
+    #[getter]
+    pub fn get_short_window(&self) -> usize {
+        self.short_window
+    }
+
+    #[getter]
+    pub fn get_long_window(&self) -> usize {
+        self.long_window
+    }
+
+    #[getter]
+    pub fn get_thresholds(&self) -> (f64, f64) {
+        (self.high, self.low)
+    }
+}
+
+/// Per-period variance estimator used by `RealizedVol`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RvEstimator {
+    /// Sample variance of log close-to-close returns.
+    CloseToClose,
+    /// `ln(H/L)^2 / (4 ln 2)` per bar.
+    Parkinson,
+    /// `0.5 ln(H/L)^2 - (2 ln 2 - 1) ln(C/O)^2` per bar.
+    GarmanKlass,
+}
+
+impl RvEstimator {
+    /// Parse the Python-facing estimator name: "close_to_close", "parkinson"
+    /// or "garman_klass".
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "close_to_close" => Ok(Self::CloseToClose),
+            "parkinson" => Ok(Self::Parkinson),
+            "garman_klass" => Ok(Self::GarmanKlass),
+            other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown estimator '{other}' (expected 'close_to_close', 'parkinson' or 'garman_klass')"
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CloseToClose => "close_to_close",
+            Self::Parkinson => "parkinson",
+            Self::GarmanKlass => "garman_klass",
+        }
+    }
+}
+
+/// Rolling realized volatility over the last `window` observations.
+///
+/// `update(close, high, low, open)` takes what the estimator needs:
+/// close-to-close uses `close` only, Parkinson needs `high`/`low`, and
+/// Garman-Klass needs all four. Each observation contributes one term to
+/// running sums, so updates are O(1). The returned vol is per observation,
+/// scaled by `sqrt(periods_per_year)` (1.0 leaves it per-tick/per-bar).
+/// Observations with non-positive prices are skipped.
+#[pyclass]
+pub struct RealizedVol {
+    estimator: RvEstimator,
+    window: usize,
+    periods_per_year: f64,
+    terms: VecDeque<f64>,
+    sum: f64,
+    sum_sq: f64,
+    prev_close: f64,
+}
+
+#[pymethods]
+impl RealizedVol {
+    #[new]
+    #[pyo3(signature = (window, estimator="close_to_close", periods_per_year=1.0))]
+    pub fn new(window: usize, estimator: &str, periods_per_year: f64) -> PyResult<Self> {
+        let estimator = RvEstimator::parse(estimator)?;
+        if window < 2 {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "window must be >= 2, got {window}"
+            )));
+        }
+        if !(periods_per_year.is_finite() && periods_per_year > 0.0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "periods_per_year must be positive, got {periods_per_year}"
+            )));
+        }
+        Ok(RealizedVol {
+            estimator,
+            window,
+            periods_per_year,
+            terms: VecDeque::with_capacity(window + 1),
+            sum: 0.0,
+            sum_sq: 0.0,
+            prev_close: f64::NAN,
+        })
+    }
+
+    /// Feed one observation; returns `vol()` after it.
+    #[pyo3(signature = (close, high=None, low=None, open=None))]
+    pub fn update(
+        &mut self,
+        close: f64,
+        high: Option<f64>,
+        low: Option<f64>,
+        open: Option<f64>,
+    ) -> PyResult<f64> {
+        let term = match self.estimator {
+            RvEstimator::CloseToClose => {
+                let prev = self.prev_close;
+                if close > 0.0 {
+                    self.prev_close = close;
+                }
+                (close > 0.0 && prev > 0.0).then(|| (close / prev).ln())
+            }
+            RvEstimator::Parkinson => {
+                let (h, l) = require_range(self.estimator, high, low)?;
+                (h > 0.0 && l > 0.0).then(|| {
+                    let hl = (h / l).ln();
+                    hl * hl / (4.0 * std::f64::consts::LN_2)
+                })
+            }
+            RvEstimator::GarmanKlass => {
+                let (h, l) = require_range(self.estimator, high, low)?;
+                let o = open.ok_or_else(|| {
+                    pyo3::exceptions::PyValueError::new_err("garman_klass requires open")
+                })?;
+                (h > 0.0 && l > 0.0 && o > 0.0 && close > 0.0).then(|| {
+                    let hl = (h / l).ln();
+                    let co = (close / o).ln();
+                    0.5 * hl * hl - (2.0 * std::f64::consts::LN_2 - 1.0) * co * co
+                })
+            }
+        };
+        if let Some(term) = term {
+            self.push_term(term);
+        }
+        Ok(self.vol())
+    }
+
+    /// Current volatility; 0 until enough observations are in the window
+    /// (two returns for close-to-close, one bar for the range estimators).
+    pub fn vol(&self) -> f64 {
+        let n = self.terms.len();
+        let var = match self.estimator {
+            RvEstimator::CloseToClose => {
+                if n < 2 {
+                    return 0.0;
+                }
+                let n_f = n as f64;
+                (self.sum_sq - self.sum * self.sum / n_f) / (n_f - 1.0)
+            }
+            RvEstimator::Parkinson | RvEstimator::GarmanKlass => {
+                if n == 0 {
+                    return 0.0;
+                }
+                self.sum / n as f64
+            }
+        };
+        // Running sums can drift just below zero on flat input
+        (var.max(0.0) * self.periods_per_year).sqrt()
+    }
+
+    /// True once the window is full.
+    pub fn is_warm(&self) -> bool {
+        self.terms.len() >= self.window
+    }
+
+    pub fn reset(&mut self) {
+        self.terms.clear();
+        self.sum = 0.0;
+        self.sum_sq = 0.0;
+        self.prev_close = f64::NAN;
+    }
+
+    #[getter]
+    pub fn get_window(&self) -> usize {
+        self.window
+    }
+
+    #[getter]
+    pub fn get_estimator(&self) -> &'static str {
+        self.estimator.name()
+    }
+
+    #[getter]
+    pub fn get_periods_per_year(&self) -> f64 {
+        self.periods_per_year
+    }
+}
+
+impl RealizedVol {
+    fn push_term(&mut self, term: f64) {
+        self.terms.push_back(term);
+        self.sum += term;
+        self.sum_sq += term * term;
+        if self.terms.len() > self.window {
+            if let Some(old) = self.terms.pop_front() {
+                self.sum -= old;
+                self.sum_sq -= old * old;
+            }
+        }
+    }
+}
+
+fn require_range(
+    estimator: RvEstimator,
+    high: Option<f64>,
+    low: Option<f64>,
+) -> PyResult<(f64, f64)> {
+    match (high, low) {
+        (Some(h), Some(l)) => Ok((h, l)),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{} requires high and low",
+            estimator.name()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The inline monitor `AlphaRegimePressure` carried before `VolMonitor`.
+    struct Legacy {
+        vol_alpha: f64,
+        ewma_variance: f64,
+        prev_mid: f64,
+        n_returns: usize,
+        initialized: bool,
+    }
+
+    impl Legacy {
+        fn step(&mut self, mid: f64) -> f64 {
+            let mut current_vol = 0.0;
+            if self.initialized {
+                if mid > 0.0 && self.prev_mid > 0.0 {
+                    let ret = (mid - self.prev_mid) / self.prev_mid;
+                    let ret_sq = ret * ret;
+                    self.ewma_variance =
+                        self.vol_alpha * ret_sq + (1.0 - self.vol_alpha) * self.ewma_variance;
+                    current_vol = self.ewma_variance.sqrt();
+                    self.n_returns = self.n_returns.saturating_add(1);
+                }
+            } else {
+                self.initialized = true;
+            }
+            self.prev_mid = mid;
+            current_vol
+        }
+    }
+
+    #[test]
+    fn test_bit_identical_to_inline_monitor() {
+        let mut seed: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        for window in [1, 20, 100] {
+            let mut vm = VolMonitor::new(window).unwrap();
+            let mut legacy = Legacy {
+                vol_alpha: 2.0 / (window as f64 + 1.0),
+                ewma_variance: 0.0,
+                prev_mid: f64::NAN,
+                n_returns: 0,
+                initialized: false,
+            };
+            let mut mid = 100.0;
+            for i in 0..5000 {
+                mid += ((next() % 201) as f64 - 100.0) * 0.0005;
+                // Occasional bad mid exercises the skip path
+                let m = if i % 997 == 0 { 0.0 } else { mid };
+                assert_eq!(vm.update(m).to_bits(), legacy.step(m).to_bits());
+                assert_eq!(
+                    vm.current_vol().to_bits(),
+                    legacy.ewma_variance.sqrt().to_bits()
+                );
+                assert_eq!(vm.is_warm(), legacy.n_returns >= window);
+            }
+        }
+    }
+
+    #[test]
+    fn test_first_tick_and_reset() {
+        assert!(VolMonitor::new(0).is_err());
+        let mut vm = VolMonitor::new(2).unwrap();
+        assert_eq!(vm.update(100.0), 0.0);
+        assert!(vm.update(101.0) > 0.0);
+        assert!(!vm.is_warm());
+        vm.update(100.0);
+        assert!(vm.is_warm());
+        vm.reset();
+        assert_eq!(vm.current_vol(), 0.0);
+        assert_eq!(vm.get_n_returns(), 0);
+        assert_eq!(vm.update(200.0), 0.0);
+    }
+
+    #[test]
+    fn test_vol_regime_detects_switch() {
+        let mut r = VolRegime::new(5, 20, DEFAULT_REGIME_HIGH, DEFAULT_REGIME_LOW).unwrap();
+        // Cold until long_window returns
+        for i in 0..19 {
+            assert_eq!(r.update(if i % 2 == 0 { 0.01 } else { -0.01 }), 0);
+        }
+        // Steady vol: normal
+        assert_eq!(r.update(0.01), 0);
+        for i in 0..5 {
+            r.update(if i % 2 == 0 { 0.05 } else { -0.05 });
+        }
+        assert_eq!(r.regime(), 1);
+        for _ in 0..5 {
+            r.update(0.0);
+        }
+        assert_eq!(r.regime(), -1);
+        r.reset();
+        assert_eq!(r.regime(), 0);
+    }
+
+    #[test]
+    fn test_vol_regime_matches_batch_detector() {
+        let mut r = VolRegime::new(5, 20, DEFAULT_REGIME_HIGH, DEFAULT_REGIME_LOW).unwrap();
+        let mut window = VecDeque::new();
+        let mut seed = 7u64;
+        let mut seen = [false; 3];
+        for i in 0..400 {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            // Cycle through calm, burst and quiet stretches of 25 returns
+            let scale = match (i / 25) % 3 {
+                0 => 0.01,
+                1 => 0.05,
+                _ => 0.001,
+            };
+            let ret = ((seed >> 33) as f64 / (1u64 << 31) as f64 - 0.5) * scale;
+            window.push_back(ret);
+            if window.len() > 20 {
+                window.pop_front();
+            }
+            let batch = compute_vol_regime(&window, 5, 20, DEFAULT_REGIME_HIGH, DEFAULT_REGIME_LOW);
+            assert_eq!(r.update(ret) as f64, batch, "tick {i}");
+            seen[(r.regime() + 1) as usize] = true;
+        }
+        assert_eq!(seen, [true; 3]);
+    }
+
+    #[test]
+    fn test_vol_regime_thresholds_configurable() {
+        // A never-reachable high threshold keeps the burst "normal"
+        let mut r = VolRegime::new(5, 20, 100.0, 0.0).unwrap();
+        for i in 0..20 {
+            r.update(if i % 2 == 0 { 0.01 } else { -0.01 });
+        }
+        for i in 0..5 {
+            r.update(if i % 2 == 0 { 0.05 } else { -0.05 });
+        }
+        assert_eq!(r.regime(), 0);
+        assert!(VolRegime::new(0, 20, 1.5, 0.7).is_err());
+        assert!(VolRegime::new(30, 20, 1.5, 0.7).is_err());
+        assert!(VolRegime::new(5, 20, 0.5, 0.7).is_err());
+    }
+
+    #[test]
+    fn test_realized_vol_matches_brute_force() {
+        let closes = [100.0, 101.0, 100.5, 102.0, 101.0, 103.0, 102.5];
+        let mut rv = RealizedVol::new(4, "close_to_close", 1.0).unwrap();
+        for c in closes {
+            rv.update(c, None, None, None).unwrap();
+        }
+        let rets: Vec<f64> = closes.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+        let last = &rets[rets.len() - 4..];
+        let mean = last.iter().sum::<f64>() / 4.0;
+        let var = last.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / 3.0;
+        assert!((rv.vol() - var.sqrt()).abs() < 1e-12);
+        assert!(rv.is_warm());
+
+        let bars = [(100.0, 102.0, 99.0, 101.0), (101.0, 101.5, 98.0, 99.0)];
+        let mut pk = RealizedVol::new(2, "parkinson", 252.0).unwrap();
+        let mut gk = RealizedVol::new(2, "GARMAN_KLASS", 1.0).unwrap();
+        for (o, h, l, c) in bars {
+            pk.update(c, Some(h), Some(l), None).unwrap();
+            gk.update(c, Some(h), Some(l), Some(o)).unwrap();
+        }
+        let ln2 = std::f64::consts::LN_2;
+        let pk_var = bars
+            .iter()
+            .map(|&(_, h, l, _)| (h / l).ln().powi(2) / (4.0 * ln2))
+            .sum::<f64>()
+            / 2.0;
+        let gk_var = bars
+            .iter()
+            .map(|&(o, h, l, c)| {
+                0.5 * (h / l).ln().powi(2) - (2.0 * ln2 - 1.0) * (c / o).ln().powi(2)
+            })
+            .sum::<f64>()
+            / 2.0;
+        assert!((pk.vol() - (pk_var * 252.0).sqrt()).abs() < 1e-12);
+        assert!((gk.vol() - gk_var.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_realized_vol_inputs() {
+        let mut rv = RealizedVol::new(3, "close_to_close", 1.0).unwrap();
+        assert_eq!(rv.update(100.0, None, None, None).unwrap(), 0.0);
+        rv.update(0.0, None, None, None).unwrap(); // skipped
+        assert_eq!(rv.update(101.0, None, None, None).unwrap(), 0.0); // one return
+        assert!(rv.update(100.0, None, None, None).unwrap() > 0.0);
+        rv.reset();
+        assert_eq!(rv.vol(), 0.0);
+
+        let mut pk = RealizedVol::new(3, "parkinson", 1.0).unwrap();
+        assert!(pk.update(100.0, Some(101.0), None, None).is_err());
+        let mut gk = RealizedVol::new(3, "garman_klass", 1.0).unwrap();
+        assert!(gk.update(100.0, Some(101.0), Some(99.0), None).is_err());
+        assert!(RealizedVol::new(3, "yang_zhang", 1.0).is_err());
+        assert!(RealizedVol::new(1, "parkinson", 1.0).is_err());
+        assert!(RealizedVol::new(3, "parkinson", 0.0).is_err());
+    }
+}
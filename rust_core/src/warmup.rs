@@ -0,0 +1,77 @@
+use pyo3::prelude::*;
+
+/// Constructor default for `warmup_policy`.
+pub const DEFAULT_WARMUP_POLICY: &str = "legacy";
+
+/// What a streaming factor emits while its state is still cold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarmupPolicy {
+    /// Pass the factor's own value through while cold (default; identical
+    /// to the output before warmup policies existed).
+    Legacy,
+    /// Emit 0.0 until warm.
+    Zero,
+    /// Emit NaN until warm so callers can mask the value explicitly.
+    Nan,
+    /// Emit the last value produced while warm (0.0 if there is none yet).
+    Hold,
+}
+
+impl WarmupPolicy {
+    /// Parse the Python-facing policy name: "legacy", "zero", "nan" or "hold".
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "legacy" => Ok(Self::Legacy),
+            "zero" => Ok(Self::Zero),
+            "nan" => Ok(Self::Nan),
+            "hold" => Ok(Self::Hold),
+            other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown warmup_policy '{other}' (expected 'legacy', 'zero', 'nan' or 'hold')"
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Legacy => "legacy",
+            Self::Zero => "zero",
+            Self::Nan => "nan",
+            Self::Hold => "hold",
+        }
+    }
+}
+
+/// Applies a `WarmupPolicy` to a factor's output stream.
+#[derive(Debug, Clone, Copy)]
+pub struct WarmupGate {
+    policy: WarmupPolicy,
+    last_valid: f64,
+}
+
+impl WarmupGate {
+    pub fn new(policy: WarmupPolicy) -> Self {
+        Self {
+            policy,
+            last_valid: 0.0,
+        }
+    }
+
+    pub fn policy(&self) -> WarmupPolicy {
+        self.policy
+    }
+
+    /// Pass `value` through when `warm`, otherwise substitute per policy.
+    #[inline(always)]
+    pub fn emit(&mut self, warm: bool, value: f64) -> f64 {
+        if warm {
+            self.last_valid = value;
+            return value;
+        }
+        match self.policy {
+            WarmupPolicy::Legacy => value,
+            WarmupPolicy::Zero => 0.0,
+            WarmupPolicy::Nan => f64::NAN,
+            WarmupPolicy::Hold => self.last_valid,
+        }
+    }
+}
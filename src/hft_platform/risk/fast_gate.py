@@ -6,7 +6,8 @@ import numpy as np
 from numba import njit
 
 # Kill Switch Shared Memory
-# 1 Byte Flag: 0 = OK, 1 = KILL
+# 1 Byte Flag: 0 = OK, 1 = KILL, 2 = HALT_NEW (rust_core.FastGate lets
+# reduce-only orders through; this gate treats any non-zero byte as KILL)
 KILL_SWITCH_SHM_NAME = "hft_kill_switch"
 
 
@@ -47,6 +47,21 @@ def test_get_field_parity_dict_and_object():
     assert _rust_core.get_field(payload_obj, keys) == _python_get_field(payload_obj, keys)
 
 
+@pytest.mark.skipif(_rust_core is None, reason="rust_core extension not available")
+def test_get_field_dotted_path():
+    class Quote:
+        def __init__(self):
+            self.bid_price = [100.5]
+            self.ask_price = None
+
+    payload = {"data": {"quote": Quote()}, "a.b": 7}
+
+    assert _rust_core.get_field(payload, ["data.quote.bid_price"]) == [100.5]
+    # Missing or None segment falls through to the next key
+    assert _rust_core.get_field(payload, ["data.quote.ask_price", "data.missing", "a.b"]) == 7
+    assert _rust_core.get_field(payload, ["data.quote.ask_price"]) is None
+
+
 @pytest.mark.skipif(_rust_core is None, reason="rust_core extension not available")
 def test_scale_book_seq_parity():
     prices = [100.0, 0.0, 99.5, 99.0]
@@ -96,3 +111,38 @@ def test_compute_book_stats_parity():
     assert math.isclose(result[4], mid_price, rel_tol=1e-12)
     assert math.isclose(result[5], spread, rel_tol=1e-12)
     assert math.isclose(result[6], imbalance, rel_tol=1e-12)
+
+
+@pytest.mark.skipif(
+    _rust_core is None or not hasattr(_rust_core, "encode_bidask"), reason="rust_core bidask codec not available"
+)
+def test_bidask_codec_round_trip():
+    result = _rust_core.normalize_bidask_tuple_np(
+        "2330",
+        1_700_000_000_000_000_000,
+        np.array([100.0, 99.5]),
+        np.array([10, 8], dtype=np.int64),
+        np.array([100.5]),
+        np.array([9], dtype=np.int64),
+        10_000,
+        True,
+    )
+    decoded = _rust_core.decode_bidask(_rust_core.encode_bidask(result))
+
+    assert len(decoded) == len(result) == 14
+    np.testing.assert_array_equal(decoded[2], result[2])
+    np.testing.assert_array_equal(decoded[3], result[3])
+    assert decoded[:2] == result[:2]
+    assert decoded[4:] == result[4:]
+
+
+@pytest.mark.skipif(
+    _rust_core is None or not hasattr(_rust_core, "round_to_tick"), reason="rust_core round_to_tick not available"
+)
+def test_round_to_tick_modes():
+    assert _rust_core.round_to_tick(10.5, 1.0) == 11.0
+    assert _rust_core.round_to_tick(10.5, 1.0, "floor") == 10.0
+    assert _rust_core.round_to_tick(10.2, 1.0, "ceil") == 11.0
+    assert _rust_core.round_to_tick(0.3, 0.1, "floor") == pytest.approx(0.3)
+    with pytest.raises(ValueError):
+        _rust_core.round_to_tick(1.0, 0.0)
@@ -0,0 +1,47 @@
+"""Tests for ShmRingBuffer iteration."""
+
+from __future__ import annotations
+
+import pytest
+
+try:
+    from hft_platform import rust_core as _rust_core  # type: ignore[attr-defined]
+except Exception:  # pragma: no cover - optional extension
+    try:
+        import rust_core as _rust_core  # type: ignore[no-redef]
+    except Exception:  # pragma: no cover
+        _rust_core = None
+
+pytestmark = pytest.mark.skipif(
+    _rust_core is None or not hasattr(_rust_core, "ShmRingBuffer"), reason="rust_core ShmRingBuffer not available"
+)
+
+
+def _ring(tmp_path, capacity=8):
+    return _rust_core.ShmRingBuffer(str(tmp_path / "ring"), capacity, True)
+
+
+def test_iter_drains_available_records(tmp_path):
+    ring = _ring(tmp_path)
+    for payload in (b"a", b"bb", b"ccc"):
+        assert ring.write(payload)
+
+    assert list(ring) == [b"a", b"bb", b"ccc"]
+    assert list(ring) == []
+    assert ring.pending() == 0
+
+
+def test_iter_blocking_stops_on_timeout(tmp_path):
+    ring = _ring(tmp_path)
+    ring.write(b"x")
+
+    assert list(ring.iter_blocking(1)) == [b"x"]
+
+
+def test_crc_ring_round_trips(tmp_path):
+    ring = _rust_core.ShmRingBuffer(str(tmp_path / "crc_ring"), 4, True, crc=True)
+    assert ring.crc
+    ring.write(b"payload")
+
+    assert ring.read() == b"payload"
+    assert ring.read() is None